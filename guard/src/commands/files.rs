@@ -1,10 +1,10 @@
 use std::cmp::Ordering;
 use std::fs::File;
 use std::io::{BufReader, Read};
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::path::{Component, Path, PathBuf};
 
 use crate::rules::errors::Error;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 use walkdir::{DirEntry, WalkDir};
 
 pub(crate) fn read_file_content(file: File) -> Result<String, std::io::Error> {
@@ -14,56 +14,142 @@ pub(crate) fn read_file_content(file: File) -> Result<String, std::io::Error> {
     Ok(file_content)
 }
 
-pub(crate) fn get_files<F>(file: &str, sort: F) -> Result<Vec<PathBuf>, Error>
-where
-    F: FnMut(&walkdir::DirEntry, &walkdir::DirEntry) -> Ordering + Send + Sync + 'static,
-{
-    let path = PathBuf::from_str(file)?;
-    let input_file = File::open(file)?;
-    let metadata = input_file.metadata()?;
-    Ok(if metadata.is_file() {
-        vec![path]
-    } else {
-        let result = get_files_with_filter(file, sort, |entry| {
-            entry
-                .file_name()
-                .to_str()
-                .map(|name| !name.ends_with("/"))
-                .unwrap_or(false)
-        })?;
-        result
-    })
+//
+// Selects the files for a --rules, --data or --input-parameters value. A value is a file, a
+// directory that is walked for files with one of the extensions, or a glob pattern such as
+// stacks/**/*.template.json that is matched against the files under its leading directory.
+// Files and directories matching one of the exclude patterns are left out, the patterns are
+// matched against the path as walked, the path relative to the value and the file name
+//
+#[derive(Debug, Clone)]
+pub(crate) struct FileSelector {
+    extensions: Vec<String>,
+    excludes: GlobSet,
 }
 
-pub(crate) fn get_files_with_filter<S, F>(
-    file: &str,
-    sort: S,
-    filter: F,
-) -> Result<Vec<PathBuf>, Error>
-where
-    S: FnMut(&walkdir::DirEntry, &walkdir::DirEntry) -> Ordering + Send + Sync + 'static,
-    F: Fn(&walkdir::DirEntry) -> bool,
-{
-    let mut selected = Vec::with_capacity(10);
-    let walker = WalkDir::new(file).sort_by(sort).into_iter();
-    let dir_check = |entry: &DirEntry| {
-        // select directories to traverse
-        if entry.path().is_dir() {
-            return true;
+impl FileSelector {
+    pub(crate) fn new<'a, E, X>(extensions: E, excludes: X) -> Result<FileSelector, Error>
+    where
+        E: IntoIterator<Item = &'a str>,
+        X: IntoIterator<Item = &'a str>,
+    {
+        let extensions = extensions
+            .into_iter()
+            .map(|extension| {
+                if extension.starts_with('.') {
+                    extension.to_string()
+                } else {
+                    format!(".{extension}")
+                }
+            })
+            .collect();
+        let mut builder = GlobSetBuilder::new();
+        for exclude in excludes {
+            builder.add(glob(exclude)?);
+        }
+        let excludes = builder
+            .build()
+            .map_err(|e| Error::ParseError(format!("Invalid exclude patterns, {e}")))?;
+        Ok(FileSelector {
+            extensions,
+            excludes,
+        })
+    }
+
+    pub(crate) fn has_extension(&self, name: &str) -> bool {
+        self.strip_extension(name).is_some()
+    }
+
+    pub(crate) fn strip_extension<'n>(&self, name: &'n str) -> Option<&'n str> {
+        self.extensions
+            .iter()
+            .find_map(|extension| name.strip_suffix(extension.as_str()))
+    }
+
+    pub(crate) fn is_excluded(&self, path: &Path, base: &Path) -> bool {
+        if self.excludes.is_empty() {
+            return false;
+        }
+        let path = normalize(path);
+        self.excludes.is_match(&path)
+            || path
+                .strip_prefix(normalize(base))
+                .is_ok_and(|relative| self.excludes.is_match(relative))
+            || path
+                .file_name()
+                .is_some_and(|name| self.excludes.is_match(name))
+    }
+
+    //
+    // The directory the value is walked from, the value itself unless it is a pattern
+    //
+    pub(crate) fn base(file_or_pattern: &str) -> &str {
+        if !is_pattern(file_or_pattern) {
+            return file_or_pattern;
+        }
+        let literal = &file_or_pattern[..file_or_pattern
+            .find(|c| GLOB_CHARACTERS.contains(&c))
+            .unwrap()];
+        match literal.rfind(std::path::is_separator) {
+            Some(0) => &file_or_pattern[..1],
+            Some(index) => &file_or_pattern[..index],
+            None => ".",
         }
-        filter(entry)
-    };
-    for each in walker.filter_entry(dir_check) {
-        //
-        // We are ignoring errors here. TODO fix this later
-        //
-        if let Ok(entry) = each {
-            if entry.path().is_file() {
-                selected.push(entry.into_path());
+    }
+
+    pub(crate) fn select<S>(&self, file_or_pattern: &str, sort: S) -> Result<Vec<PathBuf>, Error>
+    where
+        S: FnMut(&walkdir::DirEntry, &walkdir::DirEntry) -> Ordering + Send + Sync + 'static,
+    {
+        let base = Path::new(Self::base(file_or_pattern));
+        let pattern = if is_pattern(file_or_pattern) {
+            let pattern = normalize(Path::new(file_or_pattern));
+            Some(glob(&pattern.to_string_lossy())?.compile_matcher())
+        } else {
+            None
+        };
+        let selected = |entry: &DirEntry| match &pattern {
+            Some(pattern) => pattern.is_match(normalize(entry.path())),
+            None => entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| self.has_extension(name)),
+        };
+
+        let mut files = Vec::new();
+        let walker = WalkDir::new(base)
+            .sort_by(sort)
+            .into_iter()
+            .filter_entry(|entry| !self.is_excluded(entry.path(), base));
+        for entry in walker.flatten() {
+            if entry.path().is_file() && selected(&entry) {
+                files.push(entry.into_path());
             }
         }
+        Ok(files)
     }
-    Ok(selected)
+}
+
+const GLOB_CHARACTERS: [char; 4] = ['*', '?', '[', '{'];
+
+pub(crate) fn is_pattern(file_or_pattern: &str) -> bool {
+    file_or_pattern.contains(&GLOB_CHARACTERS[..])
+}
+
+fn glob(pattern: &str) -> Result<Glob, Error> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| Error::ParseError(format!("Invalid pattern `{pattern}`, {e}")))
+}
+
+//
+// ./stacks/a.json and stacks/a.json are the same file for matching
+//
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
 }
 
 #[derive(Debug)]
@@ -111,6 +197,8 @@ where
     }
 }
 
+pub(crate) type FileOrdering = fn(&walkdir::DirEntry, &walkdir::DirEntry) -> Ordering;
+
 pub(crate) fn alpabetical(first: &walkdir::DirEntry, second: &walkdir::DirEntry) -> Ordering {
     first.file_name().cmp(second.file_name())
 }
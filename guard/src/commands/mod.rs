@@ -3,8 +3,11 @@ pub(crate) mod helper;
 pub mod migrate;
 pub mod parse_tree;
 pub mod rulegen;
+pub(crate) mod rules_cache;
 pub mod test;
 pub mod validate;
+pub(crate) mod value_path;
+pub(crate) mod watch;
 
 mod aws_meta_appender;
 mod common_test_helpers;
@@ -24,17 +27,35 @@ pub const TEST: &str = "test";
 pub const VALIDATE: &str = "validate";
 // Arguments for validate
 pub const ALPHABETICAL: (&str, char) = ("alphabetical", 'a');
+pub const BASELINE: (&str, char) = ("baseline", 'b');
+pub const CONFIG: (&str, char) = ("config", 'c');
 pub const DATA: (&str, char) = ("data", 'd');
+pub const DETAILED_EXIT_CODES: (&str, char) = ("detailed-exit-codes", 'x');
+pub const DISALLOW_SUPPRESSIONS: (&str, char) = ("disallow-suppressions", 'D');
+pub const EXCLUDE_RULE: (&str, char) = ("exclude-rule", 'N');
 pub const LAST_MODIFIED: (&str, char) = ("last-modified", 'm');
+pub const NO_CONFIG: (&str, char) = ("no-config", 'I');
 pub const OUTPUT_FORMAT: (&str, char) = ("output-format", 'o');
 pub const INPUT_PARAMETERS: (&str, char) = ("input-parameters", 'i');
+pub const FAIL_ON_SKIP: (&str, char) = ("fail-on-skip", 'k');
+pub const JOBS: (&str, char) = ("jobs", 'j');
 pub const PAYLOAD: (&str, char) = ("payload", 'P');
 pub const PREVIOUS_ENGINE: (&str, char) = ("previous-engine", 'E');
 pub const PRINT_JSON: (&str, char) = ("print-json", 'p');
+pub const RULE_NAME: (&str, char) = ("rule-name", 'n');
+pub const RUN_SUMMARY: (&str, char) = ("run-summary", 'R');
 pub const SHOW_CLAUSE_FAILURES: (&str, char) = ("show-clause-failures", 's');
 pub const SHOW_SUMMARY: (&str, char) = ("show-summary", 'S');
+pub const TAGS: (&str, char) = ("tags", 'T');
 pub const TYPE: (&str, char) = ("type", 't');
 pub const VERBOSE: (&str, char) = ("verbose", 'v');
+pub const WRITE_BASELINE: (&str, char) = ("write-baseline", 'w');
+// Arguments for validate, test
+pub const CACHE_DIR: (&str, char) = ("cache-dir", 'C');
+pub const DATA_EXTENSIONS: (&str, char) = ("data-extensions", 'X');
+pub const EXCLUDE: (&str, char) = ("exclude", 'e');
+pub const RULES_EXTENSIONS: (&str, char) = ("rules-extensions", 'G');
+pub const WATCH: (&str, char) = ("watch", 'W');
 // Arguments for validate, migrate, parse tree
 pub const RULES: (&str, char) = ("rules", 'r');
 // Arguments for migrate, parse-tree, rulegen
@@ -42,17 +63,43 @@ pub const OUTPUT: (&str, char) = ("output", 'o');
 // Arguments for parse-tree
 pub const PRINT_YAML: (&str, char) = ("print-yaml", 'y');
 // Arguments for test
+pub const COVERAGE: (&str, char) = ("coverage", 'c');
+pub const FAIL_FAST: (&str, char) = ("fail-fast", 'F');
+pub const FILTER: (&str, char) = ("filter", 'f');
+pub const FUZZ: (&str, char) = ("fuzz", 'z');
+pub const FUZZ_INPUT: (&str, char) = ("fuzz-input", 'i');
+pub const FUZZ_SEED: (&str, char) = ("fuzz-seed", 'Z');
 pub const RULES_FILE: (&str, char) = ("rules-file", 'r');
+pub const SNAPSHOT: (&str, char) = ("snapshot", 'n');
 pub const TEST_DATA: (&str, char) = ("test-data", 't');
+pub const TEST_MAPPING: (&str, char) = ("test-mapping", 'M');
+pub const TEST_SUFFIX: (&str, char) = ("test-suffix", 's');
+pub const TESTS_DIR: (&str, char) = ("tests-dir", 'D');
+pub const TIMING: (&str, char) = ("timing", 'T');
+pub const UPDATE_SNAPSHOTS: (&str, char) = ("update-snapshots", 'u');
 pub const DIRECTORY: (&str, char) = ("dir", 'd');
 // Arguments for rulegen
+pub const ENUM_THRESHOLD: (&str, char) = ("enum-threshold", 'e');
+pub const INFER: (&str, char) = ("infer", 'i');
 pub const TEMPLATE: (&str, char) = ("template", 't');
+pub const WITH_TESTS: (&str, char) = ("with-tests", 'w');
 // Arg group for validate
 pub(crate) const REQUIRED_FLAGS: &str = "required_flags";
 // Arg group for test
 pub const RULES_AND_TEST_FILE: &str = "rules-and-test-file";
 pub const DIRECTORY_ONLY: &str = "directory-only";
+// Exit codes for validate. By default failed rules, rules files and data files that cannot be
+// parsed all exit with RULES_FAILED. The other codes are only used with --detailed-exit-codes,
+// ALL_SKIPPED also needs --fail-on-skip
+pub const SUCCESS_EXIT_CODE: i32 = 0;
+pub const INTERNAL_ERROR_EXIT_CODE: i32 = -1;
+pub const RULES_FAILED_EXIT_CODE: i32 = 5;
+pub const RULES_FILE_PARSE_ERROR_EXIT_CODE: i32 = 6;
+pub const DATA_FILE_PARSE_ERROR_EXIT_CODE: i32 = 7;
+pub const ALL_SKIPPED_EXIT_CODE: i32 = 8;
 
 pub(crate) const DATA_FILE_SUPPORTED_EXTENSIONS: [&'static str; 5] =
     [".yaml", ".yml", ".json", ".jsn", ".template"];
 pub(crate) const RULE_FILE_SUPPORTED_EXTENSIONS: [&'static str; 2] = [".guard", ".ruleset"];
+pub(crate) const TEST_DATA_FILE_SUPPORTED_EXTENSIONS: [&'static str; 6] =
+    [".json", ".yaml", ".JSON", ".YAML", ".yml", ".jsn"];
@@ -1,8 +1,16 @@
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use crate::command::Command;
-use crate::commands::{OUTPUT, RULEGEN, TEMPLATE};
+use crate::commands::files::{alpabetical, FileSelector};
+use crate::commands::rulegen::infer::Samples;
+use crate::commands::rulegen::test_specs::write_test_specs;
+use crate::commands::{
+    DATA_FILE_SUPPORTED_EXTENSIONS, ENUM_THRESHOLD, INFER, OUTPUT, RULEGEN, TEMPLATE, TYPE,
+    WITH_TESTS,
+};
+use crate::rules::errors::Error;
 use crate::rules::Result;
 use crate::utils::writer::Writer;
 use clap::{App, Arg, ArgMatches};
@@ -12,6 +20,9 @@ use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use string_builder::Builder;
 
+mod infer;
+mod test_specs;
+
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Rulegen {}
 
@@ -29,28 +40,254 @@ impl Command for Rulegen {
 
     fn command(&self) -> App<'static> {
         App::new(RULEGEN)
-            .about(r#"Autogenerate rules from an existing JSON- or YAML- formatted data. (Currently works with only CloudFormation templates)
+            .about(r#"Autogenerate rules from an existing JSON- or YAML- formatted data. (Works with CloudFormation templates, Terraform plans in JSON and any other JSON or YAML data)
 "#)
-            .arg(Arg::with_name(TEMPLATE.0).long(TEMPLATE.0).short(TEMPLATE.1).takes_value(true).help("Provide path to a CloudFormation template file in JSON or YAML").required(true))
+            .arg(Arg::with_name(TEMPLATE.0).long(TEMPLATE.0).short(TEMPLATE.1).takes_value(true).multiple_occurrences(true)
+                .help("Provide path to a CloudFormation template, Terraform plan in JSON or any other JSON or YAML data file, or a directory of them. Can be given more than once").required(true))
+            .arg(Arg::with_name(INFER.0).long(INFER.0).short(INFER.1).required(false)
+                .help("Infer rules from the values of each property across all templates instead of matching their exact values. \
+                       Numbers get a range, strings and booleans an IN list of at most --enum-threshold values or else a regular expression \
+                       for their common shape, and properties set in every resource must exist. Each rule has a comment with the number of samples it was learned from"))
+            .arg(Arg::with_name(ENUM_THRESHOLD.0).long(ENUM_THRESHOLD.0).short(ENUM_THRESHOLD.1).takes_value(true)
+                .requires(INFER.0)
+                .default_value("5")
+                .validator(|threshold| threshold.parse::<usize>().map(|_| ()).map_err(|_| format!("expected a number for the enum threshold, got {threshold}")))
+                .help("The most distinct values of a property that are listed with IN when inferring rules"))
+            .arg(Arg::with_name(TYPE.0).long(TYPE.0).takes_value(true)
+                .possible_values(&InputType::NAMES)
+                .help("The kind of data rules are generated from. By default a template with Resources is a CloudFormation template, \
+                       data with resource_changes is a Terraform plan from terraform show -json and anything else gets rules for the values at each path"))
+            .arg(Arg::with_name(WITH_TESTS.0).long(WITH_TESTS.0).short(WITH_TESTS.1).takes_value(true)
+                .help("Write test cases for the generated rules to a YAML file in this directory, named after --output or rulegen_tests.yaml otherwise. \
+                       Each template is a case, with a failing case for each property changed so that a rule no longer passes"))
             .arg(Arg::with_name(OUTPUT.0).long(OUTPUT.0).short(OUTPUT.1).takes_value(true).help("Write to output file").required(false))
     }
 
     fn execute(&self, app: &ArgMatches, writer: &mut Writer) -> Result<i32> {
-        let file = app.value_of(TEMPLATE.0).unwrap();
-        let template_contents = fs::read_to_string(file)?;
+        let files = template_files(app.values_of(TEMPLATE.0).unwrap())?;
+        let input_type = app.value_of(TYPE.0).and_then(InputType::from_name);
+
+        if app.is_present(INFER.0) {
+            let mut samples = Samples::default();
+            for file in &files {
+                let content = fs::read_to_string(file)?;
+                samples.add_template(&file.display().to_string(), &content, input_type)?;
+            }
+            let threshold = app
+                .value_of(ENUM_THRESHOLD.0)
+                .unwrap()
+                .parse::<usize>()
+                .unwrap();
+            let generated = write_rules(samples.rules(threshold), writer)?;
+            write_tests(app, generated, &files, input_type, writer)?;
+            return Ok(0_i32);
+        }
 
-        let result = parse_template_and_call_gen(&template_contents, writer);
-        print_rules(result, writer)?;
+        let mut result: HashMap<String, HashMap<String, HashSet<String>>> = HashMap::new();
+        let mut detected = None;
+        for file in &files {
+            let template_contents = fs::read_to_string(file)?;
+            let (file_type, rule_map) =
+                parse_data_and_call_gen(&template_contents, input_type, writer);
+            match detected {
+                Some(detected) if detected != file_type => {
+                    return Err(Error::ParseError(format!(
+                        "{} is a {file_type:?} input while the ones before are {detected:?}, use --{} to read them all the same way",
+                        file.display(),
+                        TYPE.0
+                    )));
+                }
+                _ => detected = Some(file_type),
+            }
+            for (resource, properties) in rule_map {
+                let property_map = result.entry(resource).or_default();
+                for (property, values) in properties {
+                    property_map.entry(property).or_default().extend(values);
+                }
+            }
+        }
+        let generated = print_rules(
+            detected.unwrap_or(InputType::CloudFormation),
+            result,
+            writer,
+        )?;
+        write_tests(app, generated, &files, input_type, writer)?;
 
         Ok(0_i32)
     }
 }
 
+fn write_tests(
+    app: &ArgMatches,
+    generated: Option<String>,
+    files: &[PathBuf],
+    input_type: Option<InputType>,
+    writer: &mut Writer,
+) -> Result<()> {
+    match (app.value_of(WITH_TESTS.0), generated) {
+        (Some(dir), Some(generated)) => {
+            let rules_name = app
+                .value_of(OUTPUT.0)
+                .and_then(|output| Path::new(output).file_stem())
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(RULEGEN);
+            write_test_specs(dir, rules_name, &generated, files, input_type, writer)
+        }
+        _ => Ok(()),
+    }
+}
+
+//
+// Templates given as a directory are read in alphabetical order so the rules come out the same
+//
+fn template_files<'a, T>(templates: T) -> Result<Vec<PathBuf>>
+where
+    T: IntoIterator<Item = &'a str>,
+{
+    let selector = FileSelector::new(DATA_FILE_SUPPORTED_EXTENSIONS.iter().copied(), None)?;
+    let mut files = vec![];
+    for template in templates {
+        let path = Path::new(template);
+        if path.is_dir() {
+            let selected = selector.select(template, alpabetical)?;
+            if selected.is_empty() {
+                return Err(Error::ParseError(format!(
+                    "No templates found in directory {template}"
+                )));
+            }
+            files.extend(selected);
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+//
+// The kinds of data rules are generated from. Unless --type is given it is detected from the top
+// level keys, Resources for a CloudFormation template and resource_changes for a Terraform plan
+// in JSON. For anything else the rules check the values at the paths of the data
+//
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InputType {
+    CloudFormation,
+    Terraform,
+    Generic,
+}
+
+impl InputType {
+    pub(crate) const NAMES: [&'static str; 3] = ["cloudformation", "terraform", "generic"];
+
+    pub(crate) fn from_name(name: &str) -> Option<InputType> {
+        match name {
+            "cloudformation" => Some(InputType::CloudFormation),
+            "terraform" => Some(InputType::Terraform),
+            "generic" => Some(InputType::Generic),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn detect(data: &serde_yaml::Value) -> InputType {
+        if data.get("Resources").is_some() {
+            InputType::CloudFormation
+        } else if data
+            .get("resource_changes")
+            .is_some_and(serde_yaml::Value::is_sequence)
+        {
+            InputType::Terraform
+        } else {
+            InputType::Generic
+        }
+    }
+
+    //
+    // The type and the properties of each resource, a Terraform resource that is deleted has no
+    // properties after the change and is left out
+    //
+    pub(crate) fn resources<'d>(
+        self,
+        data: &'d serde_yaml::Value,
+    ) -> std::result::Result<Vec<(&'d str, Option<&'d serde_yaml::Mapping>)>, String> {
+        let mut resources = vec![];
+        match self {
+            InputType::CloudFormation => {
+                let cfn_resources = match data.get("Resources") {
+                    Some(serde_yaml::Value::Mapping(cfn_resources)) => cfn_resources,
+                    Some(_) => {
+                        return Err(String::from(
+                            "Template Resources section has an invalid structure",
+                        ))
+                    }
+                    None => return Err(String::from("Template lacks a Resources section")),
+                };
+                for cfn_resource in cfn_resources.values() {
+                    if let Some(resource_type) = cfn_resource.get("Type").and_then(|t| t.as_str()) {
+                        let properties = cfn_resource
+                            .get("Properties")
+                            .and_then(serde_yaml::Value::as_mapping);
+                        resources.push((resource_type, properties));
+                    }
+                }
+            }
+            InputType::Terraform => {
+                let changes = match data
+                    .get("resource_changes")
+                    .and_then(serde_yaml::Value::as_sequence)
+                {
+                    Some(changes) => changes,
+                    None => {
+                        return Err(String::from(
+                            "Terraform plan lacks a resource_changes section",
+                        ))
+                    }
+                };
+                for change in changes {
+                    let after = change
+                        .get("change")
+                        .and_then(|change| change.get("after"))
+                        .and_then(serde_yaml::Value::as_mapping);
+                    if let (Some(resource_type), Some(after)) =
+                        (change.get("type").and_then(|t| t.as_str()), after)
+                    {
+                        resources.push((resource_type, Some(after)));
+                    }
+                }
+            }
+            InputType::Generic => {
+                return Err(String::from("Generic data has no resources"));
+            }
+        }
+        Ok(resources)
+    }
+
+    pub(crate) fn selection(self, resource_type: &str) -> String {
+        match self {
+            InputType::Terraform => format!("resource_changes[ type == '{resource_type}' ]"),
+            _ => format!("Resources.*[ Type == '{resource_type}' ]"),
+        }
+    }
+
+    pub(crate) fn properties(self) -> &'static str {
+        match self {
+            InputType::Terraform => "change.after",
+            _ => "Properties",
+        }
+    }
+}
+
 pub fn parse_template_and_call_gen(
     template_contents: &str,
     writer: &mut Writer,
 ) -> HashMap<String, HashMap<String, HashSet<String>>> {
-    let cfn_template: HashMap<String, Value> = match serde_yaml::from_str(template_contents) {
+    parse_data_and_call_gen(template_contents, None, writer).1
+}
+
+pub(crate) fn parse_data_and_call_gen(
+    template_contents: &str,
+    input_type: Option<InputType>,
+    writer: &mut Writer,
+) -> (InputType, HashMap<String, HashMap<String, HashSet<String>>>) {
+    let data: serde_yaml::Value = match serde_yaml::from_str(template_contents) {
         Ok(s) => s,
         Err(e) => {
             writer
@@ -60,34 +297,29 @@ pub fn parse_template_and_call_gen(
         }
     };
 
-    let cfn_resources_clone = match cfn_template.get("Resources") {
-        Some(y) => y.clone(),
-        None => {
-            writer
-                .write_err(String::from("Template lacks a Resources section"))
-                .expect("failed to write to stderr");
-            process::exit(1);
-        }
-    };
+    let input_type = input_type.unwrap_or_else(|| InputType::detect(&data));
+    if input_type == InputType::Generic {
+        return (input_type, gen_generic_rules(&data));
+    }
 
-    let cfn_resources: HashMap<String, Value> = match serde_json::from_value(cfn_resources_clone) {
-        Ok(y) => y,
+    let resources = match input_type.resources(&data) {
+        Ok(resources) => resources
+            .into_iter()
+            .filter_map(|(resource_type, properties)| {
+                let properties = serde_json::to_value(properties?).ok()?;
+                Some((resource_type.to_string(), properties))
+            })
+            .collect(),
         Err(e) => {
-            writer
-                .write_err(format!(
-                    "Template Resources section has an invalid structure: {e}"
-                ))
-                .expect("failed to write to stderr");
+            writer.write_err(e).expect("failed to write to stderr");
             process::exit(1);
         }
     };
 
-    gen_rules(cfn_resources)
+    (input_type, gen_rules(resources))
 }
 
-fn gen_rules(
-    cfn_resources: HashMap<String, Value>,
-) -> HashMap<String, HashMap<String, HashSet<String>>> {
+fn gen_rules(resources: Vec<(String, Value)>) -> HashMap<String, HashMap<String, HashSet<String>>> {
     // Create hashmap of resource name, property name and property values
     // For example, the following template:
     //
@@ -121,94 +353,179 @@ fn gen_rules(
     //
     //
     let mut rule_map: HashMap<String, HashMap<String, HashSet<String>>> = HashMap::new();
-    for (_name, cfn_resource) in cfn_resources {
-        let props: HashMap<String, Value> =
-            match serde_json::from_value(cfn_resource["Properties"].clone()) {
-                Ok(s) => s,
-                Err(_) => continue,
-            };
+    for (resource_name, properties) in resources {
+        let props: HashMap<String, Value> = match serde_json::from_value(properties) {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
 
         for (prop_name, prop_val) in props {
-            let stripped_val = match prop_val.as_str() {
-                Some(v) => String::from(v),
-                None => prop_val.to_string(),
-            };
+            // A null is a property that is not set, such as an attribute only known after apply
+            if prop_val.is_null() {
+                continue;
+            }
+            let no_newline_stripped_val = literal(&prop_val);
 
-            let mut no_newline_stripped_val = stripped_val.trim().replace("\n", "");
+            rule_map
+                .entry(resource_name.clone())
+                .or_default()
+                .entry(prop_name)
+                .or_default()
+                .insert(no_newline_stripped_val);
+        }
+    }
 
-            // Preserve double quotes for strings.
-            if prop_val.is_string() {
-                let test_str = format!("{}{}{}", "\"", no_newline_stripped_val, "\"");
-                no_newline_stripped_val = test_str;
-            }
-            let resource_name = format!("{}", &cfn_resource["Type"].as_str().unwrap());
+    return rule_map;
+}
+
+fn literal(value: &Value) -> String {
+    let stripped_val = match value.as_str() {
+        Some(v) => String::from(v),
+        None => value.to_string(),
+    };
 
-            if !rule_map.contains_key(&resource_name) {
-                let value_set: HashSet<String> =
-                    vec![no_newline_stripped_val].into_iter().collect();
+    let no_newline_stripped_val = stripped_val.trim().replace("\n", "");
 
-                let mut property_map = HashMap::new();
-                property_map.insert(prop_name, value_set);
-                rule_map.insert(resource_name, property_map);
-            } else {
-                let property_map = rule_map.get_mut(&resource_name).unwrap();
+    // Preserve double quotes for strings.
+    if value.is_string() {
+        format!("{}{}{}", "\"", no_newline_stripped_val, "\"")
+    } else {
+        no_newline_stripped_val
+    }
+}
 
-                if !property_map.contains_key(&prop_name) {
-                    let value_set: HashSet<String> =
-                        vec![no_newline_stripped_val].into_iter().collect();
-                    property_map.insert(prop_name, value_set);
-                } else {
-                    let value_set = property_map.get_mut(&prop_name).unwrap();
-                    value_set.insert(no_newline_stripped_val);
+// For data that is neither a template nor a plan there is a rule for each top level key, with a
+// clause for every value below it. The values of all elements of a list are checked together
+//     database:
+//       port: 5432
+//       replicas: [{ zone: a }, { zone: b }]
+// generates
+//     rule database {
+//       database.port == 5432
+//       database.replicas[*].zone IN ["a", "b"]
+//     }
+fn gen_generic_rules(
+    data: &serde_yaml::Value,
+) -> HashMap<String, HashMap<String, HashSet<String>>> {
+    let mut rule_map: HashMap<String, HashMap<String, HashSet<String>>> = HashMap::new();
+    if let serde_yaml::Value::Mapping(map) = data {
+        for (key, value) in map {
+            if let Some(key) = key.as_str() {
+                let rule_name = key
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                    .collect::<String>();
+                let clauses = rule_map.entry(format!("{rule_name}_values")).or_default();
+                gen_generic_clauses(value, query_key(key), clauses);
+            }
+        }
+    }
+    rule_map.retain(|_, clauses| !clauses.is_empty());
+    rule_map
+}
+
+fn gen_generic_clauses(
+    value: &serde_yaml::Value,
+    path: String,
+    clauses: &mut HashMap<String, HashSet<String>>,
+) {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            for (key, each) in map {
+                if let Some(key) = key.as_str() {
+                    gen_generic_clauses(each, format!("{path}.{}", query_key(key)), clauses);
                 }
-            };
+            }
+        }
+        serde_yaml::Value::Sequence(list) => {
+            for each in list {
+                gen_generic_clauses(each, format!("{path}[*]"), clauses);
+            }
+        }
+        serde_yaml::Value::Null => {}
+        _ => {
+            if let Ok(value) = serde_json::to_value(value) {
+                clauses.entry(path).or_default().insert(literal(&value));
+            }
         }
     }
+}
 
-    return rule_map;
+pub(crate) fn query_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        key.to_string()
+    } else if key.contains('"') {
+        format!("'{key}'")
+    } else {
+        format!("\"{key}\"")
+    }
 }
 
 // Prints the generated rules data structure to stdout. If there are properties mapping to
 // multiple values in the template, the rules are put in one statement using the IN keyword so that
-// the generated rules are interpreted as ALL by default.
+// the generated rules are interpreted as ALL by default. Rules, clauses and values are sorted so the
+// same input always generates the same rules.
 // Using the same example in the comment above, the rules printed for the template will be:
 //     let aws_ec2_volume_resources = Resources.*[ Type == 'AWS::EC2::Volume' ]
 //     rule aws_ec2_volume when %aws_ec2_volume_resources !empty {
-//          %aws_ec2_volume_resources.Properties.Size IN [500, 50]
 //          %aws_ec2_volume_resources.Properties.AvailabilityZone IN ["us-west-2b", "us-west-2c"]
 //          %aws_ec2_volume_resources.Properties.Encrypted == false
+//          %aws_ec2_volume_resources.Properties.Size IN [50, 500]
 //     }
 fn print_rules(
+    input_type: InputType,
     rule_map: HashMap<String, HashMap<String, HashSet<String>>>,
     writer: &mut Writer,
-) -> Result<()> {
+) -> Result<Option<String>> {
     let mut str = Builder::default();
 
-    for (resource, properties) in &rule_map {
+    if input_type == InputType::Generic {
+        for (rule_name, clauses) in rule_map.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            str.append(format!("rule {} {{\n", rule_name));
+            for (path, values) in clauses.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+                if values.len() > 1 {
+                    str.append(format!(
+                        "  {} IN [{}]\n",
+                        path,
+                        values.iter().sorted().iter().join(", ")
+                    ));
+                } else {
+                    str.append(format!("  {} == {}\n", path, values.iter().next().unwrap()));
+                }
+            }
+            str.append("}\n");
+        }
+        return write_rules(str.string().unwrap(), writer);
+    }
+
+    for (resource, properties) in rule_map.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
         let resource_name_underscore = resource.replace("::", "_").to_lowercase();
         let variable_name = format!("{}_resources", resource_name_underscore);
 
         str.append(format!(
-            "let {} = Resources.*[ Type == '{}' ]\n",
-            variable_name, resource
+            "let {} = {}\n",
+            variable_name,
+            input_type.selection(resource)
         ));
         str.append(format!(
             "rule {} when %{} !empty {{\n",
             resource_name_underscore, variable_name
         ));
 
-        for (property, values) in properties {
+        for (property, values) in properties.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
             if values.len() > 1 {
                 str.append(format!(
-                    "  %{}.Properties.{} IN [{}]\n",
+                    "  %{}.{}.{} IN [{}]\n",
                     variable_name,
+                    input_type.properties(),
                     property,
-                    values.iter().join(", ")
+                    values.iter().sorted().iter().join(", ")
                 ));
             } else {
                 str.append(format!(
-                    "  %{}.Properties.{} == {}\n",
+                    "  %{}.{}.{} == {}\n",
                     variable_name,
+                    input_type.properties(),
                     property,
                     values.iter().next().unwrap()
                 ));
@@ -218,9 +535,11 @@ fn print_rules(
         str.append("}\n");
     }
 
-    // validate rules generated
-    let generated_rules = str.string().unwrap();
+    write_rules(str.string().unwrap(), writer)
+}
 
+// Writes the generated rules once they parse, returning them when they did
+fn write_rules(generated_rules: String, writer: &mut Writer) -> Result<Option<String>> {
     let span = crate::rules::parser::Span::new_extra(&generated_rules, "");
     match crate::rules::parser::rules_file(span) {
         Ok(_rules) => {
@@ -233,9 +552,10 @@ fn print_rules(
             writer.write_err(format!(
                 "Parsing error with generated rules file, Error = {e}"
             ))?;
+            return Ok(None);
         }
     }
-    Ok(())
+    Ok(Some(generated_rules))
 }
 
 #[cfg(test)]
@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde_yaml::Value;
+
+use crate::commands::rulegen::InputType;
+use crate::rules::errors::Error;
+use crate::rules::Result;
+
+//
+// Rules learned from the resources of several templates instead of the literal values of a
+// single one. The values of each top level property are collected across all resources of the
+// same type, and each property gets the most useful constraint its values agree on
+//
+//   numbers                         %x.Properties.Size IN r[50, 500]
+//   at most enum threshold values   %x.Properties.VolumeType IN ["gp2", "gp3"]
+//   strings of the same shape       %x.Properties.BucketName == /^[a-z]+\-[a-z]+$/
+//   anything else                   %x.Properties.Tags exists
+//
+// A property that is not set in every resource is only checked where it is set, and exists is
+// only inferred for the properties set in every resource. Each clause has a comment with the
+// number of resources it was learned from. The resources of Terraform plans are the resources
+// changed by the plan, with their attributes after the change as properties
+//
+#[derive(Debug, Default)]
+pub(crate) struct Samples {
+    input_type: Option<InputType>,
+    templates: usize,
+    resources: BTreeMap<String, ResourceSamples>,
+}
+
+#[derive(Debug, Default)]
+struct ResourceSamples {
+    count: usize,
+    templates: usize,
+    last_template: usize,
+    properties: BTreeMap<String, Vec<Value>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Constraint {
+    Equals(String),
+    In(Vec<String>),
+    Range(String, String),
+    Regex(String),
+}
+
+//
+// Characters escaped in generated regular expressions, / ends the regular expression in a rules
+// file and is escaped for the parser
+//
+const REGEX_SPECIAL: &str = "\\.+*?()|[]{}^$#&-~/";
+const MIN_AFFIX: usize = 3;
+
+impl Samples {
+    pub(crate) fn add_template(
+        &mut self,
+        file: &str,
+        content: &str,
+        input_type: Option<InputType>,
+    ) -> Result<()> {
+        let template = serde_yaml::from_str::<Value>(content).map_err(|e| {
+            Error::ParseError(format!("Parsing error handling template file {file}, {e}"))
+        })?;
+        let input_type = input_type.unwrap_or_else(|| InputType::detect(&template));
+        match self.input_type {
+            _ if input_type == InputType::Generic => {
+                return Err(Error::ParseError(format!(
+                    "Rules can only be inferred from CloudFormation templates and Terraform plans, \
+                     {file} has neither Resources nor resource_changes"
+                )))
+            }
+            Some(previous) if previous != input_type => {
+                return Err(Error::ParseError(format!(
+                    "{file} is a {input_type:?} input while the ones before are {previous:?}"
+                )))
+            }
+            _ => self.input_type = Some(input_type),
+        }
+        let resources = input_type
+            .resources(&template)
+            .map_err(|e| Error::ParseError(format!("{e} in {file}")))?;
+
+        self.templates += 1;
+        for (resource_type, properties) in resources {
+            let samples = self.resources.entry(resource_type.to_string()).or_default();
+            samples.count += 1;
+            if samples.last_template != self.templates {
+                samples.templates += 1;
+                samples.last_template = self.templates;
+            }
+            if let Some(properties) = properties {
+                for (name, value) in properties {
+                    if value.is_null() {
+                        continue;
+                    }
+                    if let Some(name) = name.as_str().filter(|name| is_identifier(name)) {
+                        samples
+                            .properties
+                            .entry(name.to_string())
+                            .or_default()
+                            .push(value.clone());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn rules(&self, enum_threshold: usize) -> String {
+        let input_type = self.input_type.unwrap_or(InputType::CloudFormation);
+        let properties = input_type.properties();
+        let mut rules = String::new();
+        for (resource_type, samples) in &self.resources {
+            let mut clauses = Vec::with_capacity(samples.properties.len());
+            for (property, values) in &samples.properties {
+                let always = values.len() == samples.count;
+                let constraint = infer(values, enum_threshold);
+                if constraint.is_none() && !always {
+                    continue;
+                }
+                clauses.push((property, values, always, constraint));
+            }
+            if clauses.is_empty() {
+                continue;
+            }
+
+            let rule_name = resource_type.replace("::", "_").to_lowercase();
+            let variable_name = format!("{rule_name}_resources");
+            rules.push_str(&format!(
+                "# Learned from {} {resource_type} resources in {} of {} templates\n",
+                samples.count, samples.templates, self.templates
+            ));
+            rules.push_str(&format!(
+                "let {variable_name} = {}\n",
+                input_type.selection(resource_type)
+            ));
+            rules.push_str(&format!(
+                "rule {rule_name} when %{variable_name} !empty {{\n"
+            ));
+            for (property, values, always, constraint) in clauses {
+                let distinct = values.iter().collect::<HashSet<_>>().len();
+                rules.push_str(&format!(
+                    "  # {property} is set in {} of {} resources, {distinct} distinct values\n",
+                    values.len(),
+                    samples.count
+                ));
+                let query = if always {
+                    format!("%{variable_name}.{properties}.{property}")
+                } else {
+                    format!("%{variable_name}[ {properties}.{property} exists ].{properties}.{property}")
+                };
+                let clause = match constraint {
+                    None => String::from("exists"),
+                    Some(Constraint::Equals(value)) => format!("== {value}"),
+                    Some(Constraint::In(values)) => format!("IN [{}]", values.join(", ")),
+                    Some(Constraint::Range(min, max)) => format!("IN r[{min}, {max}]"),
+                    Some(Constraint::Regex(regex)) => format!("== /{regex}/"),
+                };
+                rules.push_str(&format!("  {query} {clause}\n"));
+            }
+            rules.push_str("}\n");
+        }
+        rules
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+//
+// The constraint all values agree on, None when they are not all scalars, such as intrinsic
+// functions, lists and maps, or when there is no pattern to the strings
+//
+pub(crate) fn infer(values: &[Value], enum_threshold: usize) -> Option<Constraint> {
+    if !values
+        .iter()
+        .all(|value| matches!(value, Value::Bool(_) | Value::Number(_) | Value::String(_)))
+    {
+        return None;
+    }
+
+    let literals = values
+        .iter()
+        .map(literal)
+        .collect::<Option<BTreeSet<_>>>()?;
+    if literals.len() == 1 {
+        return literals.into_iter().next().map(Constraint::Equals);
+    }
+
+    if literals.len() <= enum_threshold {
+        return Some(Constraint::In(literals.into_iter().collect()));
+    }
+
+    if values.iter().all(Value::is_number) {
+        return range(values);
+    }
+
+    let strings = values
+        .iter()
+        .map(Value::as_str)
+        .collect::<Option<Vec<_>>>()?;
+    pattern(&strings).map(Constraint::Regex)
+}
+
+//
+// The value as it is written in rules. Only the quote a string is delimited with can be escaped,
+// so a string that ends in a backslash cannot be written, nor can a negative or infinite float.
+// Values that cannot be written get no constraint
+//
+fn literal(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) if string.ends_with('\\') => None,
+        Value::String(string) => Some(format!("\"{}\"", string.replace('"', "\\\""))),
+        Value::Bool(boolean) => Some(boolean.to_string()),
+        Value::Number(number) if number.is_f64() => number
+            .as_f64()
+            .filter(|float| float.is_finite() && *float >= 0.0)
+            .map(|_| number.to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+//
+// Both ends are integers, or both are floats when any of the numbers is not an integer. Floats
+// are written with a fraction so they are not read back as integers, the rules cannot write a
+// negative float
+//
+fn range(values: &[Value]) -> Option<Constraint> {
+    if let Some(integers) = values.iter().map(Value::as_i64).collect::<Option<Vec<_>>>() {
+        let (min, max) = (integers.iter().min()?, integers.iter().max()?);
+        return Some(Constraint::Range(min.to_string(), max.to_string()));
+    }
+
+    let floats = values
+        .iter()
+        .map(Value::as_f64)
+        .collect::<Option<Vec<_>>>()?;
+    let min = floats.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = floats.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if min < 0.0 || !max.is_finite() {
+        return None;
+    }
+    let float = |number: f64| {
+        let written = number.to_string();
+        if written.contains('.') {
+            written
+        } else {
+            format!("{written}.0")
+        }
+    };
+    Some(Constraint::Range(float(min), float(max)))
+}
+
+//
+// A regular expression the strings match. Strings made of the same runs of lowercase, uppercase
+// and digits with the same characters in between get that shape, otherwise runs of letters and
+// digits are taken together. Strings of different shapes fall back to the prefix or suffix they
+// have in common
+//
+pub(crate) fn pattern(strings: &[&str]) -> Option<String> {
+    if strings
+        .iter()
+        .any(|string| string.chars().any(char::is_control))
+    {
+        return None;
+    }
+
+    for class in [fine_class as fn(char) -> Option<&'static str>, coarse_class].iter() {
+        let shapes = strings
+            .iter()
+            .map(|string| shape(string, *class))
+            .collect::<BTreeSet<_>>();
+        if shapes.len() == 1 {
+            return shapes.into_iter().next().map(|shape| format!("^{shape}$"));
+        }
+    }
+
+    let chars = strings
+        .iter()
+        .map(|string| string.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let shortest = chars.iter().map(Vec::len).min()?;
+    let prefix = (0..shortest)
+        .take_while(|index| chars.iter().all(|each| each[*index] == chars[0][*index]))
+        .count();
+    let suffix = (1..=shortest)
+        .take_while(|index| {
+            chars
+                .iter()
+                .all(|each| each[each.len() - index] == chars[0][chars[0].len() - index])
+        })
+        .count();
+    let escaped = |part: &[char]| part.iter().map(|c| escape(*c)).collect::<String>();
+    let prefix = (prefix >= MIN_AFFIX).then(|| escaped(&chars[0][..prefix]));
+    let suffix = (suffix >= MIN_AFFIX).then(|| escaped(&chars[0][chars[0].len() - suffix..]));
+    match (prefix, suffix) {
+        (Some(prefix), _) => Some(format!("^{prefix}")),
+        (None, Some(suffix)) => Some(format!("{suffix}$")),
+        (None, None) => None,
+    }
+}
+
+fn fine_class(c: char) -> Option<&'static str> {
+    if c.is_ascii_lowercase() {
+        Some("[a-z]")
+    } else if c.is_ascii_uppercase() {
+        Some("[A-Z]")
+    } else if c.is_ascii_digit() {
+        Some("[0-9]")
+    } else {
+        None
+    }
+}
+
+fn coarse_class(c: char) -> Option<&'static str> {
+    if c.is_ascii_alphanumeric() {
+        Some("[a-zA-Z0-9]")
+    } else {
+        None
+    }
+}
+
+fn shape(string: &str, class: fn(char) -> Option<&'static str>) -> String {
+    let mut shape = String::new();
+    let mut last = None;
+    for c in string.chars() {
+        match class(c) {
+            Some(run) if last == Some(run) => {}
+            Some(run) => {
+                shape.push_str(run);
+                shape.push('+');
+                last = Some(run);
+            }
+            None => {
+                shape.push_str(&escape(c));
+                last = None;
+            }
+        }
+    }
+    shape
+}
+
+fn escape(c: char) -> String {
+    if REGEX_SPECIAL.contains(c) {
+        format!("\\{c}")
+    } else {
+        c.to_string()
+    }
+}
+
+#[cfg(test)]
+#[path = "infer_tests.rs"]
+mod infer_tests;
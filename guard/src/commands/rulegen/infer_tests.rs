@@ -0,0 +1,96 @@
+use super::*;
+
+fn values(yaml: &str) -> Vec<Value> {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn test_infer_constraints() {
+    assert_eq!(
+        infer(&values("[50, 500, 100]"), 2),
+        Some(Constraint::Range("50".to_string(), "500".to_string()))
+    );
+    assert_eq!(
+        infer(&values("[80, 443, 80]"), 5),
+        Some(Constraint::In(vec!["443".to_string(), "80".to_string()]))
+    );
+    assert_eq!(
+        infer(&values("[1, 2.5, 4]"), 2),
+        Some(Constraint::Range("1.0".to_string(), "4.0".to_string()))
+    );
+    assert_eq!(infer(&values("[-1.5, 2.5, 4]"), 2), None);
+    assert_eq!(
+        infer(&values("[gp3, gp2, gp3]"), 5),
+        Some(Constraint::In(vec![
+            "\"gp2\"".to_string(),
+            "\"gp3\"".to_string()
+        ]))
+    );
+    assert_eq!(
+        infer(&values("[true, true]"), 5),
+        Some(Constraint::Equals("true".to_string()))
+    );
+    assert_eq!(
+        infer(&values("[us-west-2a, us-east-1b, eu-west-3c]"), 2),
+        Some(Constraint::Regex(
+            "^[a-z]+\\-[a-z]+\\-[0-9]+[a-z]+$".to_string()
+        ))
+    );
+    assert_eq!(infer(&values("[{ Ref: Bucket }, name]"), 5), None);
+}
+
+#[test]
+fn test_string_literals_read_back() {
+    for string in ["say \"hi\"", "it's", "both ' and \"", "a\\b", "a\\\"b"].iter() {
+        let written = literal(&Value::from(*string)).unwrap();
+        let span = crate::rules::parser::Span::new_extra(&written, "");
+        let (_, read) = crate::rules::parser::parse_string(span).unwrap();
+        assert_eq!(
+            read,
+            crate::rules::values::Value::String(string.to_string())
+        );
+    }
+    assert_eq!(literal(&Value::from("ends with \\")), None);
+    assert_eq!(infer(&values(r#"['a\', 'b\']"#), 5), None);
+}
+
+#[test]
+fn test_pattern() {
+    assert_eq!(
+        pattern(&["MyBucket1", "logs2"]),
+        Some("^[a-zA-Z0-9]+$".to_string())
+    );
+    assert_eq!(
+        pattern(&["arn:aws:s3:::a", "arn:aws:s3:::b-c"]),
+        Some("^arn:aws:s3:::".to_string())
+    );
+    assert_eq!(
+        pattern(&["a/b.json", "c-d.json"]),
+        Some("\\.json$".to_string())
+    );
+    assert_eq!(pattern(&["a-b", "c.d"]), None);
+}
+
+#[test]
+fn test_rules_with_optional_property() -> Result<()> {
+    let mut samples = Samples::default();
+    samples.add_template(
+        "first.yaml",
+        "Resources: { A: { Type: AWS::EC2::Volume, Properties: { Size: 50, Tags: [] } } }",
+        None,
+    )?;
+    samples.add_template(
+        "second.yaml",
+        "Resources: { B: { Type: AWS::EC2::Volume, Properties: { Size: 500, Tags: [], Iops: 3000 } } }",
+        None,
+    )?;
+    let rules = samples.rules(1);
+    assert!(rules.contains("# Learned from 2 AWS::EC2::Volume resources in 2 of 2 templates\n"));
+    assert!(rules.contains("  %aws_ec2_volume_resources.Properties.Size IN r[50, 500]\n"));
+    assert!(rules.contains("  %aws_ec2_volume_resources.Properties.Tags exists\n"));
+    assert!(rules.contains("  # Iops is set in 1 of 2 resources, 1 distinct values\n"));
+    assert!(rules.contains(
+        "  %aws_ec2_volume_resources[ Properties.Iops exists ].Properties.Iops == 3000\n"
+    ));
+    Ok(())
+}
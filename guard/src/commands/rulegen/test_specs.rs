@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::Value;
+
+use crate::commands::rulegen::{query_key, InputType};
+use crate::commands::test::TestSpec;
+use crate::commands::value_path::{remove, value_at, value_at_mut, Step};
+use crate::rules::eval::eval_rules_file;
+use crate::rules::eval_context::root_scope;
+use crate::rules::exprs::RulesFile;
+use crate::rules::path_value::PathAwareValue;
+use crate::rules::{NamedStatus, RecordType, Result, Status};
+use crate::utils::writer::Writer;
+
+//
+// Test cases for the generated rules in the format read by the test command. Every template the
+// rules were generated from is a case, and each property of a template gives a failing case
+// where only that property is changed, to a value of another type, an empty string or a number
+// outside the usual ranges, or removed. A change is kept when it makes a rule FAIL that PASSed
+// for the template, the expectations of every case are the status of each rule after the change
+//
+pub(crate) fn write_test_specs(
+    dir: &str,
+    rules_name: &str,
+    generated_rules: &str,
+    files: &[PathBuf],
+    input_type: Option<InputType>,
+    writer: &mut Writer,
+) -> Result<()> {
+    let span = crate::rules::parser::Span::new_extra(generated_rules, rules_name);
+    let rules = crate::rules::parser::rules_file(span)?;
+    let mut templates = Vec::with_capacity(files.len());
+    for file in files {
+        let template = serde_yaml::from_str::<Value>(&fs::read_to_string(file)?)?;
+        let input_type = input_type.unwrap_or_else(|| InputType::detect(&template));
+        let name = file.file_name().map_or_else(
+            || file.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        templates.push((name, template, input_type));
+    }
+
+    let specs = test_specs(&rules, &templates)?;
+    fs::create_dir_all(dir)?;
+    let path = Path::new(dir).join(format!("{rules_name}_tests.yaml"));
+    fs::write(&path, serde_yaml::to_string(&specs)?)?;
+    writer.write_err(format!(
+        "Written {} test cases to {}",
+        specs.len(),
+        path.display()
+    ))?;
+    Ok(())
+}
+
+pub(crate) fn test_specs(
+    rules: &RulesFile<'_>,
+    templates: &[(String, Value, InputType)],
+) -> Result<Vec<TestSpec>> {
+    let mut specs = vec![];
+    let mut covered = BTreeSet::new();
+    for (name, template, input_type) in templates {
+        let expected = statuses(rules, template)?;
+        specs.push(TestSpec::new(
+            name.clone(),
+            template.clone(),
+            expected.clone(),
+        ));
+
+        for (label, path) in targets(template, *input_type) {
+            if covered.contains(&label) {
+                continue;
+            }
+            let current = match value_at(template, &path) {
+                Some(current) => current.clone(),
+                None => continue,
+            };
+            for (change, replacement) in mutations(&current) {
+                let mut mutated = template.clone();
+                match (replacement, value_at_mut(&mut mutated, &path)) {
+                    (Some(replacement), Some(value)) => *value = replacement,
+                    (None, _) => remove(&mut mutated, &path),
+                    _ => continue,
+                }
+                let got = match statuses(rules, &mutated) {
+                    Ok(got) => got,
+                    Err(_) => continue,
+                };
+                let fails = expected.iter().any(|(rule, status)| {
+                    status == "PASS" && got.get(rule).is_some_and(|status| status == "FAIL")
+                });
+                if fails {
+                    specs.push(TestSpec::new(
+                        format!("{name} with {label} {change}"),
+                        mutated,
+                        got,
+                    ));
+                    covered.insert(label);
+                    break;
+                }
+            }
+        }
+    }
+    Ok(specs)
+}
+
+fn statuses(rules: &RulesFile<'_>, input: &Value) -> Result<BTreeMap<String, String>> {
+    let root = PathAwareValue::try_from(input.clone())?;
+    let mut scope = root_scope(rules, &root)?;
+    eval_rules_file(rules, &mut scope)?;
+    let top = scope.reset_recorder().extract();
+    Ok(top
+        .children
+        .iter()
+        .filter_map(|rule| match &rule.container {
+            Some(RecordType::RuleCheck(NamedStatus { name, status, .. })) => {
+                let status = match status {
+                    Status::PASS => "PASS",
+                    Status::FAIL => "FAIL",
+                    Status::SKIP => "SKIP",
+                };
+                Some((name.to_string(), status.to_string()))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+//
+// The properties of each resource, or each value for generic data, with the clause they are
+// checked by
+//
+fn targets(template: &Value, input_type: InputType) -> Vec<(String, Vec<Step>)> {
+    let mut targets = vec![];
+    match input_type {
+        InputType::CloudFormation => {
+            if let Some(Value::Mapping(resources)) = template.get("Resources") {
+                for (name, resource) in resources {
+                    let resource_type = resource.get("Type").and_then(Value::as_str);
+                    if let (Some(resource_type), Some(Value::Mapping(properties))) =
+                        (resource_type, resource.get("Properties"))
+                    {
+                        for key in properties.keys() {
+                            if let Some(property) = key.as_str() {
+                                targets.push((
+                                    format!("{resource_type} {property}"),
+                                    vec![
+                                        Step::Key(Value::from("Resources")),
+                                        Step::Key(name.clone()),
+                                        Step::Key(Value::from("Properties")),
+                                        Step::Key(key.clone()),
+                                    ],
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        InputType::Terraform => {
+            if let Some(Value::Sequence(changes)) = template.get("resource_changes") {
+                for (index, change) in changes.iter().enumerate() {
+                    let resource_type = change.get("type").and_then(Value::as_str);
+                    let after = change.get("change").and_then(|change| change.get("after"));
+                    if let (Some(resource_type), Some(Value::Mapping(after))) =
+                        (resource_type, after)
+                    {
+                        for (key, value) in after {
+                            if let (Some(attribute), false) = (key.as_str(), value.is_null()) {
+                                targets.push((
+                                    format!("{resource_type} {attribute}"),
+                                    vec![
+                                        Step::Key(Value::from("resource_changes")),
+                                        Step::Index(index),
+                                        Step::Key(Value::from("change")),
+                                        Step::Key(Value::from("after")),
+                                        Step::Key(key.clone()),
+                                    ],
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        InputType::Generic => leaves(template, &mut vec![], String::new(), &mut targets),
+    }
+    targets
+}
+
+fn leaves(
+    value: &Value,
+    path: &mut Vec<Step>,
+    query: String,
+    targets: &mut Vec<(String, Vec<Step>)>,
+) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, each) in map {
+                if let Some(name) = key.as_str() {
+                    let query = if query.is_empty() {
+                        query_key(name)
+                    } else {
+                        format!("{query}.{}", query_key(name))
+                    };
+                    path.push(Step::Key(key.clone()));
+                    leaves(each, path, query, targets);
+                    path.pop();
+                }
+            }
+        }
+        Value::Sequence(list) => {
+            for (index, each) in list.iter().enumerate() {
+                path.push(Step::Index(index));
+                leaves(each, path, format!("{query}[*]"), targets);
+                path.pop();
+            }
+        }
+        Value::Null => {}
+        _ => targets.push((query, path.clone())),
+    }
+}
+
+fn mutations(current: &Value) -> Vec<(String, Option<Value>)> {
+    let replacements = match current {
+        Value::Number(_) => vec![Value::from(-1), Value::from(i64::from(i32::MAX))],
+        Value::Bool(boolean) => vec![Value::Bool(!boolean)],
+        Value::String(_) => vec![Value::from(""), Value::from("rulegen-mutated")],
+        _ => vec![Value::from("rulegen-mutated")],
+    };
+    let mut mutations = replacements
+        .into_iter()
+        .filter(|replacement| replacement != current)
+        .map(|replacement| {
+            let shown = match &replacement {
+                Value::String(string) => format!("\"{string}\""),
+                Value::Bool(boolean) => boolean.to_string(),
+                Value::Number(number) => number.to_string(),
+                _ => String::new(),
+            };
+            (format!("set to {shown}"), Some(replacement))
+        })
+        .collect::<Vec<_>>();
+    mutations.push((String::from("removed"), None));
+    mutations
+}
+
+#[cfg(test)]
+#[path = "test_specs_tests.rs"]
+mod test_specs_tests;
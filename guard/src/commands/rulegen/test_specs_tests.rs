@@ -0,0 +1,77 @@
+use super::*;
+
+fn rules_and_template(rules: &str, template: &str) -> (String, Value) {
+    (rules.to_string(), serde_yaml::from_str(template).unwrap())
+}
+
+#[test]
+fn test_specs_for_cloudformation_template() {
+    let (rules, template) = rules_and_template(
+        r#"
+let volumes = Resources.*[ Type == 'AWS::EC2::Volume' ]
+rule aws_ec2_volume when %volumes !empty {
+  %volumes.Properties.Size IN r[50, 500]
+  %volumes.Properties.Encrypted == true
+}
+"#,
+        r#"
+Resources:
+  Volume:
+    Type: AWS::EC2::Volume
+    Properties:
+      Size: 100
+      Encrypted: true
+"#,
+    );
+    let rules = crate::rules::parser::rules_file(crate::rules::parser::Span::new_extra(&rules, ""))
+        .unwrap();
+    let specs = test_specs(
+        &rules,
+        &[(
+            String::from("volume.yaml"),
+            template,
+            InputType::CloudFormation,
+        )],
+    )
+    .unwrap();
+    assert_eq!(specs.len(), 3);
+
+    let written = serde_yaml::to_string(&specs).unwrap();
+    assert!(written.contains("name: volume.yaml\n"));
+    assert!(written.contains("name: volume.yaml with AWS::EC2::Volume Size set to -1\n"));
+    assert!(written.contains("name: volume.yaml with AWS::EC2::Volume Encrypted set to false\n"));
+    assert!(written.contains("aws_ec2_volume: PASS"));
+    assert!(written.contains("aws_ec2_volume: FAIL"));
+}
+
+#[test]
+fn test_targets_and_removal_for_generic_data() {
+    let (_, mut data) = rules_and_template(
+        "",
+        r#"
+logging:
+  level: info
+listeners:
+  - port: 443
+"#,
+    );
+    let targets = targets(&data, InputType::Generic);
+    assert_eq!(
+        targets
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .collect::<Vec<_>>(),
+        vec!["logging.level", "listeners[*].port"]
+    );
+    assert_eq!(value_at(&data, &targets[1].1), Some(&Value::from(443)));
+
+    remove(&mut data, &targets[0].1);
+    assert_eq!(value_at(&data, &targets[0].1), None);
+    assert_eq!(
+        mutations(&Value::from("info"))
+            .into_iter()
+            .map(|(change, _)| change)
+            .collect::<Vec<_>>(),
+        vec!["set to \"\"", "set to \"rulegen-mutated\"", "removed"]
+    );
+}
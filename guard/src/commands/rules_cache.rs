@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::commands::rules_cache::entry::{CachedRulesFile, Restore, Store};
+use crate::commands::APP_VERSION;
+use crate::rules::exprs::RulesFile;
+use crate::rules::Result;
+
+mod entry;
+
+//
+// Bumped whenever the parser or the rules tree changes how a rules file is stored, so entries
+// of an earlier build with the same Guard version are not read back. The rules_cache_tests
+// check the encoding of a rules file with every kind of clause against this version
+//
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+const MAX_ENTRIES: usize = 1000;
+
+//
+// On-disk cache of parsed rules files. Entries are keyed by a digest of the Guard version,
+// the format of the entries, the rules file name and its content, so any change to either the
+// file or the tool yields a different entry. Entries that cannot be read back (missing,
+// truncated, written by another version) are ignored and the file is parsed again. Failed
+// parses are never cached, so parse errors are always reported exactly as without a cache.
+// Entries that are not read again stay until the directory holds more than MAX_ENTRIES, the
+// oldest are removed then.
+//
+#[derive(Debug)]
+pub(crate) struct RulesCache {
+    directory: PathBuf,
+}
+
+impl RulesCache {
+    pub(crate) fn new(directory: &str) -> Result<RulesCache> {
+        fs::create_dir_all(directory)?;
+        Ok(RulesCache {
+            directory: PathBuf::from(directory),
+        })
+    }
+
+    pub(crate) fn parse<'r>(&self, content: &'r str, file_name: &'r str) -> Result<RulesFile<'r>> {
+        let digest = digest_of(content, file_name);
+        let entry = self.directory.join(format!("{digest}.cbor"));
+        if let Some(rules) = load(&entry, &digest, file_name) {
+            return Ok(rules);
+        }
+
+        let span = crate::rules::parser::Span::new_extra(content, file_name);
+        let rules = crate::rules::parser::rules_file(span)?;
+        store(&entry, &digest, &rules);
+        prune(&self.directory, MAX_ENTRIES);
+        Ok(rules)
+    }
+}
+
+pub(crate) fn parse_rules<'r>(
+    content: &'r str,
+    file_name: &'r str,
+    cache: Option<&RulesCache>,
+) -> Result<RulesFile<'r>> {
+    match cache {
+        Some(cache) => cache.parse(content, file_name),
+        None => {
+            let span = crate::rules::parser::Span::new_extra(content, file_name);
+            crate::rules::parser::rules_file(span)
+        }
+    }
+}
+
+fn digest_of(content: &str, file_name: &str) -> String {
+    let mut hasher = Sha256::new();
+    let format_version = CACHE_FORMAT_VERSION.to_string();
+    for part in [APP_VERSION, &format_version, file_name, content] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn load<'r>(entry: &Path, digest: &str, file_name: &'r str) -> Option<RulesFile<'r>> {
+    let bytes = fs::read(entry).ok()?;
+    match ciborium::from_reader::<(String, String, CachedRulesFile), _>(bytes.as_slice()) {
+        Ok((version, stored_digest, rules))
+            if version == APP_VERSION && stored_digest == digest =>
+        {
+            Some(rules.restore(file_name))
+        }
+        _ => None,
+    }
+}
+
+//
+// Best effort, a cache that cannot be written to only costs the parse next time. The entry is
+// written to a temporary file first and renamed so concurrent runs never read a partial entry.
+//
+fn store(entry: &Path, digest: &str, rules: &RulesFile<'_>) {
+    let mut bytes = vec![];
+    if ciborium::into_writer(&(APP_VERSION, digest, rules.store()), &mut bytes).is_ok() {
+        let temporary = entry.with_extension(format!("{}.tmp", std::process::id()));
+        if fs::write(&temporary, bytes).is_ok() && fs::rename(&temporary, entry).is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+    }
+}
+
+//
+// Removes the entries written longest ago when there are more than limit of them
+//
+fn prune(directory: &Path, limit: usize) {
+    let mut entries = match fs::read_dir(directory) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "cbor"))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+    if entries.len() <= limit {
+        return;
+    }
+    entries.sort();
+    for (_, entry) in &entries[..entries.len() - limit] {
+        let _ = fs::remove_file(entry);
+    }
+}
+
+#[cfg(test)]
+#[path = "rules_cache_tests.rs"]
+mod rules_cache_tests;
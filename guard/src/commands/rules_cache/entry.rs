@@ -0,0 +1,755 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rules::exprs::{
+    AccessClause, AccessQuery, Block, BlockGuardClause, FileLocation, FunctionExpr,
+    GuardAccessClause, GuardClause, GuardNamedRuleClause, LetExpr, LetValue, MapKeyFilterClause,
+    ParameterizedNamedRuleClause, ParameterizedRule, QueryPart, Rule, RuleClause, RulesFile,
+    TypeBlock, WhenGuardClause,
+};
+use crate::rules::path_value::{MapValue, Path, PathAwareValue};
+use crate::rules::values::{CmpOperator, RangeType};
+
+//
+// The form of a parsed rules file kept in the cache. The Serialize implementations of the parse
+// tree print it for people, which flattens regexes, ranges and locations, so the cache keeps its
+// own copy of the tree that serde can write and read back exactly. Locations are kept without
+// the file name, every location of a rules file points to the name the file is loaded with
+//
+pub(super) trait Store {
+    type Entry;
+    fn store(&self) -> Self::Entry;
+}
+
+pub(super) trait Restore<'r> {
+    type Tree;
+    fn restore(self, file_name: &'r str) -> Self::Tree;
+}
+
+impl<T: Store> Store for Vec<T> {
+    type Entry = Vec<T::Entry>;
+    fn store(&self) -> Self::Entry {
+        self.iter().map(Store::store).collect()
+    }
+}
+
+impl<'r, T: Restore<'r>> Restore<'r> for Vec<T> {
+    type Tree = Vec<T::Tree>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        self.into_iter()
+            .map(|each| each.restore(file_name))
+            .collect()
+    }
+}
+
+impl<T: Store> Store for Option<T> {
+    type Entry = Option<T::Entry>;
+    fn store(&self) -> Self::Entry {
+        self.as_ref().map(Store::store)
+    }
+}
+
+impl<'r, T: Restore<'r>> Restore<'r> for Option<T> {
+    type Tree = Option<T::Tree>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        self.map(|each| each.restore(file_name))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedLocation(u32, u32);
+
+impl Store for FileLocation<'_> {
+    type Entry = CachedLocation;
+    fn store(&self) -> Self::Entry {
+        CachedLocation(self.line, self.column)
+    }
+}
+
+impl<'r> Restore<'r> for CachedLocation {
+    type Tree = FileLocation<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        FileLocation {
+            line: self.0,
+            column: self.1,
+            file_name,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CachedValue {
+    Null(Path),
+    String(Path, String),
+    Regex(Path, String),
+    Bool(Path, bool),
+    Int(Path, i64),
+    Float(Path, f64),
+    Char(Path, char),
+    List(Path, Vec<CachedValue>),
+    Map(Path, Vec<CachedValue>, Vec<(String, CachedValue)>),
+    RangeInt(Path, RangeType<i64>),
+    RangeFloat(Path, RangeType<f64>),
+    RangeChar(Path, RangeType<char>),
+}
+
+impl Store for PathAwareValue {
+    type Entry = CachedValue;
+    fn store(&self) -> Self::Entry {
+        match self {
+            PathAwareValue::Null(path) => CachedValue::Null(path.clone()),
+            PathAwareValue::String((path, v)) => CachedValue::String(path.clone(), v.clone()),
+            PathAwareValue::Regex((path, v)) => CachedValue::Regex(path.clone(), v.clone()),
+            PathAwareValue::Bool((path, v)) => CachedValue::Bool(path.clone(), *v),
+            PathAwareValue::Int((path, v)) => CachedValue::Int(path.clone(), *v),
+            PathAwareValue::Float((path, v)) => CachedValue::Float(path.clone(), *v),
+            PathAwareValue::Char((path, v)) => CachedValue::Char(path.clone(), *v),
+            PathAwareValue::List((path, v)) => CachedValue::List(path.clone(), v.store()),
+            PathAwareValue::Map((path, map)) => CachedValue::Map(
+                path.clone(),
+                map.keys.store(),
+                map.values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.store()))
+                    .collect(),
+            ),
+            PathAwareValue::RangeInt((path, v)) => CachedValue::RangeInt(path.clone(), v.clone()),
+            PathAwareValue::RangeFloat((path, v)) => {
+                CachedValue::RangeFloat(path.clone(), v.clone())
+            }
+            PathAwareValue::RangeChar((path, v)) => CachedValue::RangeChar(path.clone(), v.clone()),
+        }
+    }
+}
+
+impl CachedValue {
+    fn restore(self) -> PathAwareValue {
+        match self {
+            CachedValue::Null(path) => PathAwareValue::Null(path),
+            CachedValue::String(path, v) => PathAwareValue::String((path, v)),
+            CachedValue::Regex(path, v) => PathAwareValue::Regex((path, v)),
+            CachedValue::Bool(path, v) => PathAwareValue::Bool((path, v)),
+            CachedValue::Int(path, v) => PathAwareValue::Int((path, v)),
+            CachedValue::Float(path, v) => PathAwareValue::Float((path, v)),
+            CachedValue::Char(path, v) => PathAwareValue::Char((path, v)),
+            CachedValue::List(path, v) => {
+                PathAwareValue::List((path, v.into_iter().map(CachedValue::restore).collect()))
+            }
+            CachedValue::Map(path, keys, values) => PathAwareValue::Map((
+                path,
+                MapValue {
+                    keys: keys.into_iter().map(CachedValue::restore).collect(),
+                    values: values
+                        .into_iter()
+                        .map(|(key, value)| (key, value.restore()))
+                        .collect(),
+                },
+            )),
+            CachedValue::RangeInt(path, v) => PathAwareValue::RangeInt((path, v)),
+            CachedValue::RangeFloat(path, v) => PathAwareValue::RangeFloat((path, v)),
+            CachedValue::RangeChar(path, v) => PathAwareValue::RangeChar((path, v)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CachedLetValue {
+    Value(CachedValue),
+    AccessClause(CachedAccessQuery),
+    FunctionCall(CachedFunctionExpr),
+}
+
+impl Store for LetValue<'_> {
+    type Entry = CachedLetValue;
+    fn store(&self) -> Self::Entry {
+        match self {
+            LetValue::Value(value) => CachedLetValue::Value(value.store()),
+            LetValue::AccessClause(query) => CachedLetValue::AccessClause(query.store()),
+            LetValue::FunctionCall(function) => CachedLetValue::FunctionCall(function.store()),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedLetValue {
+    type Tree = LetValue<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        match self {
+            CachedLetValue::Value(value) => LetValue::Value(value.restore()),
+            CachedLetValue::AccessClause(query) => LetValue::AccessClause(query.restore(file_name)),
+            CachedLetValue::FunctionCall(function) => {
+                LetValue::FunctionCall(function.restore(file_name))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedLetExpr {
+    var: String,
+    value: CachedLetValue,
+}
+
+impl Store for LetExpr<'_> {
+    type Entry = CachedLetExpr;
+    fn store(&self) -> Self::Entry {
+        CachedLetExpr {
+            var: self.var.clone(),
+            value: self.value.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedLetExpr {
+    type Tree = LetExpr<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        LetExpr {
+            var: self.var,
+            value: self.value.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CachedQueryPart {
+    This,
+    Key(String),
+    MapKeyFilter(Option<String>, CachedMapKeyFilterClause),
+    AllValues(Option<String>),
+    AllIndices(Option<String>),
+    Index(i32),
+    Filter(Option<String>, Vec<Vec<CachedGuardClause>>),
+}
+
+impl Store for QueryPart<'_> {
+    type Entry = CachedQueryPart;
+    fn store(&self) -> Self::Entry {
+        match self {
+            QueryPart::This => CachedQueryPart::This,
+            QueryPart::Key(key) => CachedQueryPart::Key(key.clone()),
+            QueryPart::MapKeyFilter(name, filter) => {
+                CachedQueryPart::MapKeyFilter(name.clone(), filter.store())
+            }
+            QueryPart::AllValues(name) => CachedQueryPart::AllValues(name.clone()),
+            QueryPart::AllIndices(name) => CachedQueryPart::AllIndices(name.clone()),
+            QueryPart::Index(index) => CachedQueryPart::Index(*index),
+            QueryPart::Filter(name, conjunctions) => {
+                CachedQueryPart::Filter(name.clone(), conjunctions.store())
+            }
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedQueryPart {
+    type Tree = QueryPart<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        match self {
+            CachedQueryPart::This => QueryPart::This,
+            CachedQueryPart::Key(key) => QueryPart::Key(key),
+            CachedQueryPart::MapKeyFilter(name, filter) => {
+                QueryPart::MapKeyFilter(name, filter.restore(file_name))
+            }
+            CachedQueryPart::AllValues(name) => QueryPart::AllValues(name),
+            CachedQueryPart::AllIndices(name) => QueryPart::AllIndices(name),
+            CachedQueryPart::Index(index) => QueryPart::Index(index),
+            CachedQueryPart::Filter(name, conjunctions) => {
+                QueryPart::Filter(name, conjunctions.restore(file_name))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedAccessQuery {
+    query: Vec<CachedQueryPart>,
+    match_all: bool,
+}
+
+impl Store for AccessQuery<'_> {
+    type Entry = CachedAccessQuery;
+    fn store(&self) -> Self::Entry {
+        CachedAccessQuery {
+            query: self.query.store(),
+            match_all: self.match_all,
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedAccessQuery {
+    type Tree = AccessQuery<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        AccessQuery {
+            query: self.query.restore(file_name),
+            match_all: self.match_all,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedAccessClause {
+    query: CachedAccessQuery,
+    comparator: (CmpOperator, bool),
+    compare_with: Option<CachedLetValue>,
+    custom_message: Option<String>,
+    location: CachedLocation,
+}
+
+impl Store for AccessClause<'_> {
+    type Entry = CachedAccessClause;
+    fn store(&self) -> Self::Entry {
+        CachedAccessClause {
+            query: self.query.store(),
+            comparator: self.comparator,
+            compare_with: self.compare_with.store(),
+            custom_message: self.custom_message.clone(),
+            location: self.location.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedAccessClause {
+    type Tree = AccessClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        AccessClause {
+            query: self.query.restore(file_name),
+            comparator: self.comparator,
+            compare_with: self.compare_with.restore(file_name),
+            custom_message: self.custom_message,
+            location: self.location.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedGuardAccessClause {
+    access_clause: CachedAccessClause,
+    negation: bool,
+}
+
+impl Store for GuardAccessClause<'_> {
+    type Entry = CachedGuardAccessClause;
+    fn store(&self) -> Self::Entry {
+        CachedGuardAccessClause {
+            access_clause: self.access_clause.store(),
+            negation: self.negation,
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedGuardAccessClause {
+    type Tree = GuardAccessClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        GuardAccessClause {
+            access_clause: self.access_clause.restore(file_name),
+            negation: self.negation,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedMapKeyFilterClause {
+    comparator: (CmpOperator, bool),
+    compare_with: CachedLetValue,
+}
+
+impl Store for MapKeyFilterClause<'_> {
+    type Entry = CachedMapKeyFilterClause;
+    fn store(&self) -> Self::Entry {
+        CachedMapKeyFilterClause {
+            comparator: self.comparator,
+            compare_with: self.compare_with.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedMapKeyFilterClause {
+    type Tree = MapKeyFilterClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        MapKeyFilterClause {
+            comparator: self.comparator,
+            compare_with: self.compare_with.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedGuardNamedRuleClause {
+    dependent_rule: String,
+    negation: bool,
+    custom_message: Option<String>,
+    location: CachedLocation,
+}
+
+impl Store for GuardNamedRuleClause<'_> {
+    type Entry = CachedGuardNamedRuleClause;
+    fn store(&self) -> Self::Entry {
+        CachedGuardNamedRuleClause {
+            dependent_rule: self.dependent_rule.clone(),
+            negation: self.negation,
+            custom_message: self.custom_message.clone(),
+            location: self.location.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedGuardNamedRuleClause {
+    type Tree = GuardNamedRuleClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        GuardNamedRuleClause {
+            dependent_rule: self.dependent_rule,
+            negation: self.negation,
+            custom_message: self.custom_message,
+            location: self.location.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedBlockGuardClause {
+    query: CachedAccessQuery,
+    block: CachedBlock<CachedGuardClause>,
+    location: CachedLocation,
+    not_empty: bool,
+}
+
+impl Store for BlockGuardClause<'_> {
+    type Entry = CachedBlockGuardClause;
+    fn store(&self) -> Self::Entry {
+        CachedBlockGuardClause {
+            query: self.query.store(),
+            block: self.block.store(),
+            location: self.location.store(),
+            not_empty: self.not_empty,
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedBlockGuardClause {
+    type Tree = BlockGuardClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        BlockGuardClause {
+            query: self.query.restore(file_name),
+            block: self.block.restore(file_name),
+            location: self.location.restore(file_name),
+            not_empty: self.not_empty,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedParameterizedNamedRuleClause {
+    parameters: Vec<CachedLetValue>,
+    named_rule: CachedGuardNamedRuleClause,
+}
+
+impl Store for ParameterizedNamedRuleClause<'_> {
+    type Entry = CachedParameterizedNamedRuleClause;
+    fn store(&self) -> Self::Entry {
+        CachedParameterizedNamedRuleClause {
+            parameters: self.parameters.store(),
+            named_rule: self.named_rule.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedParameterizedNamedRuleClause {
+    type Tree = ParameterizedNamedRuleClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        ParameterizedNamedRuleClause {
+            parameters: self.parameters.restore(file_name),
+            named_rule: self.named_rule.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedFunctionExpr {
+    parameters: Vec<CachedLetValue>,
+    name: String,
+    location: CachedLocation,
+}
+
+impl Store for FunctionExpr<'_> {
+    type Entry = CachedFunctionExpr;
+    fn store(&self) -> Self::Entry {
+        CachedFunctionExpr {
+            parameters: self.parameters.store(),
+            name: self.name.clone(),
+            location: self.location.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedFunctionExpr {
+    type Tree = FunctionExpr<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        FunctionExpr {
+            parameters: self.parameters.restore(file_name),
+            name: self.name,
+            location: self.location.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CachedGuardClause {
+    Clause(CachedGuardAccessClause),
+    NamedRule(CachedGuardNamedRuleClause),
+    ParameterizedNamedRule(CachedParameterizedNamedRuleClause),
+    BlockClause(CachedBlockGuardClause),
+    WhenBlock(
+        Vec<Vec<CachedWhenGuardClause>>,
+        CachedBlock<CachedGuardClause>,
+    ),
+}
+
+impl Store for GuardClause<'_> {
+    type Entry = CachedGuardClause;
+    fn store(&self) -> Self::Entry {
+        match self {
+            GuardClause::Clause(clause) => CachedGuardClause::Clause(clause.store()),
+            GuardClause::NamedRule(clause) => CachedGuardClause::NamedRule(clause.store()),
+            GuardClause::ParameterizedNamedRule(clause) => {
+                CachedGuardClause::ParameterizedNamedRule(clause.store())
+            }
+            GuardClause::BlockClause(clause) => CachedGuardClause::BlockClause(clause.store()),
+            GuardClause::WhenBlock(conditions, block) => {
+                CachedGuardClause::WhenBlock(conditions.store(), block.store())
+            }
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedGuardClause {
+    type Tree = GuardClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        match self {
+            CachedGuardClause::Clause(clause) => GuardClause::Clause(clause.restore(file_name)),
+            CachedGuardClause::NamedRule(clause) => {
+                GuardClause::NamedRule(clause.restore(file_name))
+            }
+            CachedGuardClause::ParameterizedNamedRule(clause) => {
+                GuardClause::ParameterizedNamedRule(clause.restore(file_name))
+            }
+            CachedGuardClause::BlockClause(clause) => {
+                GuardClause::BlockClause(clause.restore(file_name))
+            }
+            CachedGuardClause::WhenBlock(conditions, block) => {
+                GuardClause::WhenBlock(conditions.restore(file_name), block.restore(file_name))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CachedWhenGuardClause {
+    Clause(CachedGuardAccessClause),
+    NamedRule(CachedGuardNamedRuleClause),
+    ParameterizedNamedRule(CachedParameterizedNamedRuleClause),
+}
+
+impl Store for WhenGuardClause<'_> {
+    type Entry = CachedWhenGuardClause;
+    fn store(&self) -> Self::Entry {
+        match self {
+            WhenGuardClause::Clause(clause) => CachedWhenGuardClause::Clause(clause.store()),
+            WhenGuardClause::NamedRule(clause) => CachedWhenGuardClause::NamedRule(clause.store()),
+            WhenGuardClause::ParameterizedNamedRule(clause) => {
+                CachedWhenGuardClause::ParameterizedNamedRule(clause.store())
+            }
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedWhenGuardClause {
+    type Tree = WhenGuardClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        match self {
+            CachedWhenGuardClause::Clause(clause) => {
+                WhenGuardClause::Clause(clause.restore(file_name))
+            }
+            CachedWhenGuardClause::NamedRule(clause) => {
+                WhenGuardClause::NamedRule(clause.restore(file_name))
+            }
+            CachedWhenGuardClause::ParameterizedNamedRule(clause) => {
+                WhenGuardClause::ParameterizedNamedRule(clause.restore(file_name))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedBlock<T> {
+    assignments: Vec<CachedLetExpr>,
+    conjunctions: Vec<Vec<T>>,
+}
+
+impl<T: Store> Store for Block<'_, T> {
+    type Entry = CachedBlock<T::Entry>;
+    fn store(&self) -> Self::Entry {
+        CachedBlock {
+            assignments: self.assignments.store(),
+            conjunctions: self.conjunctions.store(),
+        }
+    }
+}
+
+impl<'r, T: Restore<'r>> Restore<'r> for CachedBlock<T> {
+    type Tree = Block<'r, T::Tree>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        Block {
+            assignments: self.assignments.restore(file_name),
+            conjunctions: self.conjunctions.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedTypeBlock {
+    type_name: String,
+    conditions: Option<Vec<Vec<CachedWhenGuardClause>>>,
+    block: CachedBlock<CachedGuardClause>,
+    query: Vec<CachedQueryPart>,
+}
+
+impl Store for TypeBlock<'_> {
+    type Entry = CachedTypeBlock;
+    fn store(&self) -> Self::Entry {
+        CachedTypeBlock {
+            type_name: self.type_name.clone(),
+            conditions: self.conditions.store(),
+            block: self.block.store(),
+            query: self.query.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedTypeBlock {
+    type Tree = TypeBlock<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        TypeBlock {
+            type_name: self.type_name,
+            conditions: self.conditions.restore(file_name),
+            block: self.block.restore(file_name),
+            query: self.query.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CachedRuleClause {
+    Clause(CachedGuardClause),
+    WhenBlock(
+        Vec<Vec<CachedWhenGuardClause>>,
+        CachedBlock<CachedGuardClause>,
+    ),
+    TypeBlock(CachedTypeBlock),
+}
+
+impl Store for RuleClause<'_> {
+    type Entry = CachedRuleClause;
+    fn store(&self) -> Self::Entry {
+        match self {
+            RuleClause::Clause(clause) => CachedRuleClause::Clause(clause.store()),
+            RuleClause::WhenBlock(conditions, block) => {
+                CachedRuleClause::WhenBlock(conditions.store(), block.store())
+            }
+            RuleClause::TypeBlock(block) => CachedRuleClause::TypeBlock(block.store()),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedRuleClause {
+    type Tree = RuleClause<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        match self {
+            CachedRuleClause::Clause(clause) => RuleClause::Clause(clause.restore(file_name)),
+            CachedRuleClause::WhenBlock(conditions, block) => {
+                RuleClause::WhenBlock(conditions.restore(file_name), block.restore(file_name))
+            }
+            CachedRuleClause::TypeBlock(block) => RuleClause::TypeBlock(block.restore(file_name)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedRule {
+    rule_name: String,
+    conditions: Option<Vec<Vec<CachedWhenGuardClause>>>,
+    block: CachedBlock<CachedRuleClause>,
+}
+
+impl Store for Rule<'_> {
+    type Entry = CachedRule;
+    fn store(&self) -> Self::Entry {
+        CachedRule {
+            rule_name: self.rule_name.clone(),
+            conditions: self.conditions.store(),
+            block: self.block.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedRule {
+    type Tree = Rule<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        Rule {
+            rule_name: self.rule_name,
+            conditions: self.conditions.restore(file_name),
+            block: self.block.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedParameterizedRule {
+    parameter_names: Vec<String>,
+    rule: CachedRule,
+}
+
+impl Store for ParameterizedRule<'_> {
+    type Entry = CachedParameterizedRule;
+    fn store(&self) -> Self::Entry {
+        CachedParameterizedRule {
+            parameter_names: self.parameter_names.iter().cloned().collect(),
+            rule: self.rule.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedParameterizedRule {
+    type Tree = ParameterizedRule<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        ParameterizedRule {
+            parameter_names: self.parameter_names.into_iter().collect(),
+            rule: self.rule.restore(file_name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CachedRulesFile {
+    assignments: Vec<CachedLetExpr>,
+    guard_rules: Vec<CachedRule>,
+    parameterized_rules: Vec<CachedParameterizedRule>,
+}
+
+impl Store for RulesFile<'_> {
+    type Entry = CachedRulesFile;
+    fn store(&self) -> Self::Entry {
+        CachedRulesFile {
+            assignments: self.assignments.store(),
+            guard_rules: self.guard_rules.store(),
+            parameterized_rules: self.parameterized_rules.store(),
+        }
+    }
+}
+
+impl<'r> Restore<'r> for CachedRulesFile {
+    type Tree = RulesFile<'r>;
+    fn restore(self, file_name: &'r str) -> Self::Tree {
+        RulesFile {
+            assignments: self.assignments.restore(file_name),
+            guard_rules: self.guard_rules.restore(file_name),
+            parameterized_rules: self.parameterized_rules.restore(file_name),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use super::*;
+
+const RULES: &str = r#"
+let buckets = Resources.*[ Type == 'AWS::S3::Bucket' ]
+
+rule s3_buckets_encrypted when %buckets !empty {
+    %buckets.Properties {
+        BucketName == /^prod-[a-z]+$/
+        Tags[*] {
+            Key in ["env", "team"]
+            Value IN r[1, 10)
+        }
+        VersioningConfiguration == { "Status": "Enabled" } <<versioning required>>
+    }
+}
+
+rule depends_on_encryption when s3_buckets_encrypted {
+    Resources.*.Properties.Size IN r(1.5, 20.0]
+}
+"#;
+
+fn cache_directory(name: &str) -> String {
+    let directory =
+        std::env::temp_dir().join(format!("guard-rules-cache-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&directory);
+    format!("{}", directory.display())
+}
+
+//
+// PartialEq on range values is not reflexive, compare the full debug form instead
+//
+fn assert_same_rules(left: &RulesFile<'_>, right: &RulesFile<'_>) {
+    assert_eq!(format!("{left:?}"), format!("{right:?}"));
+}
+
+fn cache_entries(directory: &str) -> Vec<PathBuf> {
+    fs::read_dir(directory)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect()
+}
+
+#[test]
+fn test_cached_rules_match_parsed_rules() -> Result<()> {
+    let directory = cache_directory("round-trip");
+    let cache = RulesCache::new(&directory)?;
+    let parsed = parse_rules(RULES, "s3.guard", None)?;
+
+    let first = cache.parse(RULES, "s3.guard")?;
+    let entries = cache_entries(&directory);
+    assert_eq!(entries.len(), 1);
+    let cached = load(&entries[0], &digest_of(RULES, "s3.guard"), "s3.guard").unwrap();
+
+    assert_same_rules(&first, &parsed);
+    assert_same_rules(&cached, &parsed);
+    fs::remove_dir_all(&directory)?;
+    Ok(())
+}
+
+#[test]
+fn test_changed_content_gets_new_entry() -> Result<()> {
+    let directory = cache_directory("changed");
+    let cache = RulesCache::new(&directory)?;
+    cache.parse(RULES, "s3.guard")?;
+    let changed = RULES.replace("prod-", "dev-");
+    let rules = cache.parse(&changed, "s3.guard")?;
+
+    assert_same_rules(&rules, &parse_rules(&changed, "s3.guard", None)?);
+    assert_eq!(cache_entries(&directory).len(), 2);
+    fs::remove_dir_all(&directory)?;
+    Ok(())
+}
+
+#[test]
+fn test_corrupt_entry_is_parsed_again() -> Result<()> {
+    let directory = cache_directory("corrupt");
+    let cache = RulesCache::new(&directory)?;
+    cache.parse(RULES, "s3.guard")?;
+    for entry in cache_entries(&directory) {
+        fs::write(entry, b"not a cache entry")?;
+    }
+
+    assert_same_rules(
+        &cache.parse(RULES, "s3.guard")?,
+        &parse_rules(RULES, "s3.guard", None)?,
+    );
+    fs::remove_dir_all(&directory)?;
+    Ok(())
+}
+
+#[test]
+fn test_parse_errors_are_not_cached() -> Result<()> {
+    let directory = cache_directory("errors");
+    let cache = RulesCache::new(&directory)?;
+
+    assert!(cache.parse("rule broken {", "broken.guard").is_err());
+    assert!(cache_entries(&directory).is_empty());
+    fs::remove_dir_all(&directory)?;
+    Ok(())
+}
+
+#[test]
+fn test_prune_keeps_the_limit() -> Result<()> {
+    let directory = cache_directory("prune");
+    let cache = RulesCache::new(&directory)?;
+    for name in ["a.guard", "b.guard", "c.guard"].iter() {
+        cache.parse(RULES, name)?;
+    }
+    fs::write(Path::new(&directory).join("notes.txt"), "kept")?;
+
+    prune(Path::new(&directory), 2);
+    let entries = cache_entries(&directory);
+    assert_eq!(entries.len(), 3);
+    assert!(entries.iter().any(|entry| entry.ends_with("notes.txt")));
+    fs::remove_dir_all(&directory)?;
+    Ok(())
+}
+
+const EVERY_CLAUSE: &str = r#"
+let buckets = Resources.*[ Type == 'AWS::S3::Bucket' ]
+let names = %buckets[ keys == /^prod/ ].Properties.BucketName
+let count_of = Parameters.Count
+let allowed = ["a", 'b', 1, 2.5, true, { "key": [null] }]
+
+rule with_parameters(values, limit) {
+    %values !empty
+    %limit IN r[1, 10)
+}
+
+rule every_clause when %buckets !empty or %count_of > 0 {
+    AWS::S3::Bucket when Properties.Versioning exists {
+        Properties.Versioning.Status == "Enabled" <<versioning>>
+    }
+    %buckets.Properties {
+        some Tags[*].Key IN %allowed
+        BucketName != /tmp-.*/ or BucketName NOT EXISTS
+    }
+    when %names exists {
+        with_parameters(Resources.*, Parameters.Limit)
+    }
+}
+
+rule depends when every_clause {
+    not every_clause
+    Resources.*.Properties.Size IN r(1.5, 20.0]
+}
+"#;
+
+//
+// The encoding of a rules file with every kind of clause. It changes when the parser or the
+// rules tree changes how rules are stored, entries written before cannot be read back the same
+// then. Bump CACHE_FORMAT_VERSION and update the digest together
+//
+#[test]
+fn test_cache_format_version_matches_encoding() -> Result<()> {
+    let rules = parse_rules(EVERY_CLAUSE, "every.guard", None)?;
+    let mut bytes = vec![];
+    ciborium::into_writer(&rules.store(), &mut bytes).unwrap();
+    let encoding = format!("{:x}", Sha256::digest(&bytes));
+    assert_eq!(
+        (CACHE_FORMAT_VERSION, encoding.as_str()),
+        (
+            1,
+            "4a8abc03fe5293841e72efd2435e13de431d5d7fa6c419a85a878a39a256634f"
+        ),
+        "the encoding of cache entries changed, bump CACHE_FORMAT_VERSION"
+    );
+
+    let restored = ciborium::from_reader::<CachedRulesFile, _>(bytes.as_slice())
+        .unwrap()
+        .restore("every.guard");
+    assert_same_rules(&restored, &rules);
+    Ok(())
+}
@@ -1,32 +1,53 @@
 use clap::{App, Arg, ArgGroup, ArgMatches};
+use globset::{Glob, GlobMatcher};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use walkdir::DirEntry;
 
 use validate::validate_path;
 
 use crate::command::Command;
 use crate::commands::files::{
-    alpabetical, get_files_with_filter, iterate_over, last_modified, read_file_content,
-    regular_ordering,
+    alpabetical, is_pattern, iterate_over, last_modified, read_file_content, regular_ordering,
+    FileOrdering, FileSelector,
 };
+use crate::commands::rules_cache::{parse_rules, RulesCache};
+use crate::commands::test::coverage::Coverage;
+use crate::commands::test::fuzz::Fuzz;
+use crate::commands::test::layout::TestLayout;
+use crate::commands::test::snapshot::Snapshots;
 use crate::commands::tracker::StackTracker;
+use crate::commands::validate::common::{collect_clause_failures, ClauseFailure};
+use crate::commands::validate::junit::{JunitReport, JunitTestCase, TestOutcome};
+use crate::commands::watch::{Evaluated, Statuses, Watched, WatchedFiles, Watcher};
 use crate::commands::{
-    validate, ALPHABETICAL, DIRECTORY, DIRECTORY_ONLY, LAST_MODIFIED, PREVIOUS_ENGINE,
-    RULES_AND_TEST_FILE, RULES_FILE, TEST, TEST_DATA, VERBOSE,
+    validate, ALPHABETICAL, CACHE_DIR, COVERAGE, DATA_EXTENSIONS, DIRECTORY, DIRECTORY_ONLY,
+    EXCLUDE, FAIL_FAST, FILTER, FUZZ, FUZZ_INPUT, FUZZ_SEED, LAST_MODIFIED, OUTPUT_FORMAT,
+    PREVIOUS_ENGINE, RULES_AND_TEST_FILE, RULES_EXTENSIONS, RULES_FILE,
+    RULE_FILE_SUPPORTED_EXTENSIONS, SNAPSHOT, TEST, TESTS_DIR, TEST_DATA,
+    TEST_DATA_FILE_SUPPORTED_EXTENSIONS, TEST_MAPPING, TEST_SUFFIX, TIMING, UPDATE_SNAPSHOTS,
+    VERBOSE, WATCH,
 };
 use crate::rules::errors::Error;
 use crate::rules::eval::eval_rules_file;
+use crate::rules::eval_context::{simplifed_json_from_root, ClauseReport};
 use crate::rules::evaluate::RootScope;
 use crate::rules::exprs::RulesFile;
 use crate::rules::path_value::PathAwareValue;
 use crate::rules::Status::SKIP;
 use crate::rules::{Evaluate, NamedStatus, RecordType, Result, Status};
-use crate::utils::writer::Writer;
+use crate::utils::writer::{WriteBuffer, Writer};
+
+mod coverage;
+mod fuzz;
+mod layout;
+mod snapshot;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Test {}
@@ -58,7 +79,7 @@ or failure testing.
                 .long(TEST_DATA.0)
                 .short(TEST_DATA.1)
                 .takes_value(true)
-                .help("Provide a file or dir for data files in JSON or YAML"))
+                .help("Provide a file, a dir or a glob pattern for data files in JSON or YAML"))
             .arg(Arg::with_name(DIRECTORY.0)
                 .long(DIRECTORY.0)
                 .short(DIRECTORY.1)
@@ -70,6 +91,17 @@ or failure testing.
                 .args(&["dir"])
                 .requires_all(&[DIRECTORY.0])
                 .conflicts_with(RULES_AND_TEST_FILE))
+            .arg(Arg::with_name(TESTS_DIR.0).long(TESTS_DIR.0).short(TESTS_DIR.1).takes_value(true)
+                .default_value("tests")
+                .help("Directory of the test files relative to each rules file with --dir, . for the directory of the rules file itself"))
+            .arg(Arg::with_name(TEST_SUFFIX.0).long(TEST_SUFFIX.0).short(TEST_SUFFIX.1).takes_value(true).use_delimiter(true).multiple(true)
+                .requires(DIRECTORY.0)
+                .help("Test files are named after their rules file followed by one of these suffixes, such as .test.yaml for s3.guard and s3.test.yaml. \
+                       By default a test file name only has to start with the name of the rules file"))
+            .arg(Arg::with_name(TEST_MAPPING.0).long(TEST_MAPPING.0).short(TEST_MAPPING.1).takes_value(true)
+                .requires(DIRECTORY.0)
+                .help("A YAML or JSON file that maps rules files to their test files, directories or glob patterns, relative to the mapping file. \
+                       Rules files listed in it get their test files from it instead of the tests directory"))
             .arg(Arg::with_name(PREVIOUS_ENGINE.0).long(PREVIOUS_ENGINE.0).short(PREVIOUS_ENGINE.1).takes_value(false)
                 .help("Uses the old engine for evaluation. This parameter will allow customers to evaluate old changes before migrating"))
             .arg(Arg::with_name(ALPHABETICAL.0).long(ALPHABETICAL.0).short(ALPHABETICAL.1).help("Sort alphabetically inside a directory").required(false))
@@ -77,11 +109,80 @@ or failure testing.
                 .help("Sort by last modified times within a directory"))
             .arg(Arg::with_name(VERBOSE.0).long(VERBOSE.0).short(VERBOSE.1).required(false)
                 .help("Verbose logging"))
+            .arg(Arg::with_name(CACHE_DIR.0).long(CACHE_DIR.0).short(CACHE_DIR.1).takes_value(true)
+                .help("Cache parsed rules files in the given directory and reuse them on later runs. \
+                       Entries are keyed by the rules file content and the Guard version, changed files are parsed again. \
+                       The oldest entries are removed once the directory holds more than 1000"))
+            .arg(Arg::with_name(OUTPUT_FORMAT.0).long(OUTPUT_FORMAT.0).short(OUTPUT_FORMAT.1).takes_value(true)
+                .possible_values(&["single-line-summary", "json", "yaml", "junit"])
+                .default_value("single-line-summary")
+                .help("Specify the format in which the output should be displayed. \
+                       json and yaml write the expected and evaluated status of each rule for every test case, \
+                       with the clause failures of each rule when --verbose is set. \
+                       junit writes a single JUnit XML report, with a test suite for each rules file and a test case for each rule expectation"))
+            .arg(Arg::with_name(COVERAGE.0).long(COVERAGE.0).short(COVERAGE.1).required(false)
+                .conflicts_with_all(&[PREVIOUS_ENGINE.0, WATCH.0])
+                .help("Report which rules, clauses and when branches the test cases exercised once all tests ran. \
+                       Lists the rules that were never PASS or FAIL, the clauses that were never evaluated or never failed, \
+                       the when branches that were never taken and an overall percentage. \
+                       Written as a final document for json and yaml output, not supported with junit"))
+            .arg(Arg::with_name(SNAPSHOT.0).long(SNAPSHOT.0).short(SNAPSHOT.1).required(false)
+                .requires(DIRECTORY.0)
+                .conflicts_with_all(&[PREVIOUS_ENGINE.0, COVERAGE.0, WATCH.0, OUTPUT_FORMAT.0])
+                .help("Compare the validate output for the data files in the snapshots directory next to each rules file with the stored snapshots. \
+                       A data file is evaluated with the rules file its name starts with, the single-line-summary and json output \
+                       are stored together in a .snap file next to the data file and a unified diff is shown for each mismatch"))
+            .arg(Arg::with_name(UPDATE_SNAPSHOTS.0).long(UPDATE_SNAPSHOTS.0).short(UPDATE_SNAPSHOTS.1).required(false)
+                .requires(SNAPSHOT.0)
+                .help("Write the snapshots that are missing or do not match instead of reporting them"))
+            .arg(Arg::with_name(WATCH.0).long(WATCH.0).short(WATCH.1).required(false)
+                .conflicts_with(OUTPUT_FORMAT.0)
+                .help("Keep running and poll the rules and test files for changes, re-running only the (rules file, test file) pairs \
+                       affected by a change and printing the rule expectations whose result changed since the previous run. \
+                       Rule expectations that are not met are listed on start"))
+            .arg(Arg::with_name(FILTER.0).long(FILTER.0).short(FILTER.1).takes_value(true)
+                .conflicts_with_all(&[SNAPSHOT.0, WATCH.0])
+                .help("Only run the test cases whose name matches this glob pattern. For the other test cases only the expectations \
+                       of the rules whose name matches are checked, test cases without any are left out. \
+                       A pattern without glob characters matches anywhere in the name"))
+            .arg(Arg::with_name(FAIL_FAST.0).long(FAIL_FAST.0).short(FAIL_FAST.1).required(false)
+                .conflicts_with_all(&[SNAPSHOT.0, WATCH.0])
+                .help("Stop at the first test case that fails or cannot be run"))
+            .arg(Arg::with_name(TIMING.0).long(TIMING.0).short(TIMING.1).required(false)
+                .conflicts_with_all(&[SNAPSHOT.0, WATCH.0])
+                .help("Write a summary line with the result and duration of each test case, and the totals of the run once all tests ran. \
+                       The duration is part of each test case for json and yaml output"))
+            .arg(Arg::with_name(FUZZ.0).long(FUZZ.0).short(FUZZ.1).takes_value(true)
+                .requires_all(&[RULES_FILE.0, FUZZ_INPUT.0])
+                .conflicts_with_all(&[TEST_DATA.0, DIRECTORY.0, PREVIOUS_ENGINE.0, COVERAGE.0, SNAPSHOT.0, WATCH.0, FILTER.0, FAIL_FAST.0, TIMING.0])
+                .validator(|cases| match cases.parse::<usize>() {
+                    Ok(count) if count > 0 => Ok(()),
+                    _ => Err(format!("expected a positive number of fuzz cases, got {cases}")),
+                })
+                .help("Evaluate the rules file against this many inputs made by mutating the --fuzz-input documents, changing the type of values, \
+                       removing keys and emptying lists, maps and strings. Reports the inputs the rules cannot be evaluated against, \
+                       with a minimized reproducer for each distinct error written as a test spec"))
+            .arg(Arg::with_name(FUZZ_INPUT.0).long(FUZZ_INPUT.0).short(FUZZ_INPUT.1).takes_value(true).use_delimiter(true).multiple(true)
+                .requires(FUZZ.0)
+                .help("Example documents in JSON or YAML to mutate for --fuzz, a template or a single resource with its Type and Properties"))
+            .arg(Arg::with_name(FUZZ_SEED.0).long(FUZZ_SEED.0).short(FUZZ_SEED.1).takes_value(true)
+                .requires(FUZZ.0)
+                .validator(|seed| seed.parse::<u64>().map(|_| ()).map_err(|_| format!("expected a number for the fuzz seed, got {seed}")))
+                .help("Seed for --fuzz, the same seed makes the same inputs. Defaults to a random seed that is written out with the results"))
+            .arg(Arg::with_name(EXCLUDE.0).long(EXCLUDE.0).short(EXCLUDE.1).takes_value(true).multiple_occurrences(true)
+                .help("Leave out files and directories matching these glob patterns when scanning --test-data or --dir. \
+                       Patterns are matched against the path of each file, its path relative to the argument it was found under and its file name"))
+            .arg(Arg::with_name(DATA_EXTENSIONS.0).long(DATA_EXTENSIONS.0).short(DATA_EXTENSIONS.1).takes_value(true).use_delimiter(true).multiple(true)
+                .default_values(&TEST_DATA_FILE_SUPPORTED_EXTENSIONS)
+                .help("Extensions of the test data files selected when scanning directories for --test-data or --dir"))
+            .arg(Arg::with_name(RULES_EXTENSIONS.0).long(RULES_EXTENSIONS.0).short(RULES_EXTENSIONS.1).takes_value(true).use_delimiter(true).multiple(true)
+                .default_values(&RULE_FILE_SUPPORTED_EXTENSIONS)
+                .help("Extensions of the rules files selected when scanning --dir"))
     }
 
     fn execute(&self, app: &ArgMatches, writer: &mut Writer) -> Result<i32> {
         let mut exit_code = 0;
-        let cmp = if let Some(_ignored) = app.value_of(ALPHABETICAL.0) {
+        let cmp: FileOrdering = if let Some(_ignored) = app.value_of(ALPHABETICAL.0) {
             alpabetical
         } else if let Some(_ignored) = app.value_of(LAST_MODIFIED.0) {
             last_modified
@@ -90,83 +191,117 @@ or failure testing.
         };
         let verbose = app.is_present(VERBOSE.0);
         let new_engine = !app.is_present(PREVIOUS_ENGINE.0);
+        let rules_cache = app.value_of(CACHE_DIR.0).map(RulesCache::new).transpose()?;
+        let excludes = app.values_of(EXCLUDE.0).into_iter().flatten();
+        let data_selector =
+            FileSelector::new(app.values_of(DATA_EXTENSIONS.0).unwrap(), excludes.clone())?;
+        let rules_selector =
+            FileSelector::new(app.values_of(RULES_EXTENSIONS.0).unwrap(), excludes)?;
+
+        let layout = TestLayout::new(
+            app.value_of(TESTS_DIR.0).unwrap(),
+            app.values_of(TEST_SUFFIX.0).into_iter().flatten(),
+            app.value_of(TEST_MAPPING.0),
+            &data_selector,
+        )?;
+
+        if app.is_present(FUZZ.0) {
+            let seed = match app.value_of(FUZZ_SEED.0) {
+                Some(seed) => seed.parse::<u64>().unwrap(),
+                None => std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+            };
+            let fuzz = Fuzz {
+                rules_file: app.value_of(RULES_FILE.0).unwrap(),
+                inputs: app.values_of(FUZZ_INPUT.0).unwrap().collect(),
+                cases: app.value_of(FUZZ.0).unwrap().parse::<usize>().unwrap(),
+                seed,
+                rules_cache,
+            };
+            return fuzz.run(writer);
+        }
+
+        if app.is_present(SNAPSHOT.0) {
+            let snapshots = Snapshots {
+                dir: app.value_of(DIRECTORY.0).unwrap(),
+                rules_selector,
+                data_selector,
+                layout,
+                rules_cache,
+                update: app.is_present(UPDATE_SNAPSHOTS.0),
+            };
+            return snapshots.run(writer);
+        }
+
+        if app.is_present(WATCH.0) {
+            let watched = TestWatch {
+                dir: app.value_of(DIRECTORY.0),
+                rules_file: app.value_of(RULES_FILE.0),
+                test_data: app.value_of(TEST_DATA.0),
+                rules_selector,
+                data_selector,
+                layout,
+                cmp,
+                new_engine,
+                rules_cache,
+            };
+            return Watcher::new(&watched).run(writer);
+        }
+
+        //
+        // With a report format the console output is only kept to report errors, the report
+        // itself is the only thing written out when all tests ran
+        //
+        let mut report = app.value_of(OUTPUT_FORMAT.0).and_then(TestReport::new);
+        let mut coverage = app.is_present(COVERAGE.0).then(Coverage::new);
+        if coverage.is_some() && matches!(report, Some(TestReport::JUnit(_))) {
+            writer.write_err(format!(
+                "--{} is not supported with --{} junit",
+                COVERAGE.0, OUTPUT_FORMAT.0
+            ))?;
+            return Ok(1);
+        }
+        let mut run = TestRun::new(
+            app.value_of(FILTER.0),
+            app.is_present(FAIL_FAST.0),
+            app.is_present(TIMING.0),
+        )?;
+        let mut console = Writer::new(WriteBuffer::Vec(vec![]), WriteBuffer::Vec(vec![]));
+        let report_writer = writer;
+        let writer = if report.is_some() {
+            &mut console
+        } else {
+            &mut *report_writer
+        };
 
         if app.is_present(DIRECTORY_ONLY) {
-            struct GuardFile {
-                prefix: String,
-                file: DirEntry,
-                test_files: Vec<DirEntry>,
-            }
             let dir = app.value_of(DIRECTORY.0).unwrap();
             validate_path(dir)?;
-            let walk = walkdir::WalkDir::new(dir);
-            let mut non_guard: Vec<DirEntry> = vec![];
-            let mut ordered_guard_files: BTreeMap<String, Vec<GuardFile>> = BTreeMap::new();
-            for file in walk
-                .follow_links(true)
-                .sort_by_file_name()
-                .into_iter()
+            let ordered_guard_files = guard_files(dir, &rules_selector, &data_selector, &layout)?;
+            let untested = ordered_guard_files
+                .values()
                 .flatten()
-            {
-                if file.path().is_file() {
-                    let name = file
-                        .file_name()
-                        .to_str()
-                        .map_or("".to_string(), |s| s.to_string());
-                    if name.ends_with(".guard") || name.ends_with(".ruleset") {
-                        let prefix = name
-                            .strip_suffix(".guard")
-                            .or_else(|| name.strip_suffix(".ruleset"))
-                            .unwrap()
-                            .to_string();
-                        ordered_guard_files
-                            .entry(
-                                file.path()
-                                    .parent()
-                                    .map_or("".to_string(), |p| format!("{}", p.display())),
-                            )
-                            .or_insert(vec![])
-                            .push(GuardFile {
-                                prefix,
-                                file,
-                                test_files: vec![],
-                            });
-                        continue;
-                    } else {
-                        non_guard.push(file);
-                    }
-                }
-            }
-            for file in non_guard {
-                let name = file
-                    .file_name()
-                    .to_str()
-                    .map_or("".to_string(), |s| s.to_string());
-                if name.ends_with(".yaml")
-                    || name.ends_with(".yml")
-                    || name.ends_with(".json")
-                    || name.ends_with(".jsn")
-                {
-                    let parent = file.path().parent();
-                    if parent.map_or(false, |p| p.ends_with("tests")) {
-                        if let Some(candidates) = parent.unwrap().parent().and_then(|grand| {
-                            let grand = format!("{}", grand.display());
-                            ordered_guard_files.get_mut(&grand)
-                        }) {
-                            for guard_file in candidates {
-                                if name.starts_with(&guard_file.prefix) {
-                                    guard_file.test_files.push(file);
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            for (_dir, guard_files) in ordered_guard_files {
+                .filter(|guard_file| guard_file.test_files.is_empty())
+                .map(|guard_file| format!("{}", guard_file.file.path().display()))
+                .collect::<Vec<_>>();
+            let guard_files_count = ordered_guard_files.values().map(Vec::len).sum::<usize>();
+            'guard_files: for (_dir, guard_files) in ordered_guard_files {
                 for each_rule_file in guard_files {
                     if each_rule_file.test_files.is_empty() {
+                        //
+                        // Rules files without tests are still part of the coverage, none of
+                        // their rules are tested
+                        //
+                        if let Some(coverage) = coverage.as_mut() {
+                            let content =
+                                read_file_content(File::open(each_rule_file.file.path())?)?;
+                            if let Ok(rules) =
+                                parse_rules(&content, &each_rule_file.prefix, rules_cache.as_ref())
+                            {
+                                coverage.add_rules_file(&each_rule_file.prefix, &rules);
+                            }
+                        }
                         writeln!(
                             writer,
                             "Guard File {} did not have any tests associated, skipping.",
@@ -182,24 +317,21 @@ or failure testing.
                     )?;
                     let rule_file = File::open(each_rule_file.file.path())?;
                     let content = read_file_content(rule_file)?;
-                    let span =
-                        crate::rules::parser::Span::new_extra(&content, &each_rule_file.prefix);
-                    match crate::rules::parser::rules_file(span) {
+                    match parse_rules(&content, &each_rule_file.prefix, rules_cache.as_ref()) {
                         Err(e) => {
                             writeln!(writer, "Parse Error on ruleset file {e}",)?;
                             exit_code = 1;
                         }
                         Ok(rules) => {
-                            let data_test_files = each_rule_file
-                                .test_files
-                                .iter()
-                                .map(|de| de.path().to_path_buf())
-                                .collect::<Vec<PathBuf>>();
                             let test_exit_code = test_with_data(
-                                &data_test_files,
+                                &each_rule_file.test_files,
                                 &rules,
+                                &each_rule_file.prefix,
                                 verbose,
                                 new_engine,
+                                report.as_mut(),
+                                coverage.as_mut(),
+                                &mut run,
                                 writer,
                             )?;
                             exit_code = if exit_code == 0 {
@@ -210,6 +342,20 @@ or failure testing.
                         }
                     }
                     writeln!(writer, "---")?;
+                    if run.fail_fast && exit_code != 0 {
+                        run.stopped = true;
+                        break 'guard_files;
+                    }
+                }
+            }
+            if !untested.is_empty() {
+                writeln!(
+                    writer,
+                    "{} of {guard_files_count} guard files have no tests",
+                    untested.len()
+                )?;
+                for each in &untested {
+                    writeln!(writer, "  {each}")?;
                 }
             }
         } else {
@@ -217,22 +363,9 @@ or failure testing.
             let data = app.value_of(TEST_DATA.0).unwrap();
 
             validate_path(file)?;
-            validate_path(data)?;
-
-            let data_test_files = get_files_with_filter(data, cmp, |entry| {
-                entry
-                    .file_name()
-                    .to_str()
-                    .map(|name| {
-                        name.ends_with(".json")
-                            || name.ends_with(".yaml")
-                            || name.ends_with(".JSON")
-                            || name.ends_with(".YAML")
-                            || name.ends_with(".yml")
-                            || name.ends_with(".jsn")
-                    })
-                    .unwrap_or(false)
-            })?;
+            validate_path(FileSelector::base(data))?;
+
+            let data_test_files = data_selector.select(data, cmp)?;
 
             let path = PathBuf::try_from(file)?;
 
@@ -253,8 +386,7 @@ or failure testing.
                         exit_code = 1;
                     }
                     Ok((context, path)) => {
-                        let span = crate::rules::parser::Span::new_extra(&context, &path);
-                        match crate::rules::parser::rules_file(span) {
+                        match parse_rules(&context, &path, rules_cache.as_ref()) {
                             Err(e) => {
                                 writeln!(writer, "Parse Error on ruleset file {e}")?;
                                 exit_code = 1;
@@ -263,8 +395,12 @@ or failure testing.
                                 let curr_exit_code = test_with_data(
                                     &data_test_files,
                                     &rules,
+                                    &path,
                                     verbose,
                                     new_engine,
+                                    report.as_mut(),
+                                    coverage.as_mut(),
+                                    &mut run,
                                     writer,
                                 )?;
                                 if curr_exit_code != 0 {
@@ -277,37 +413,632 @@ or failure testing.
             }
         }
 
+        run.write(writer)?;
+        if let Some(report) = &report {
+            if exit_code == 1 {
+                let console = console
+                    .into_string()
+                    .map_err(|e| Error::ParseError(e.to_string()))?;
+                report_writer.write_err(console)?;
+            }
+            report.write(report_writer)?;
+        }
+        if let Some(coverage) = &coverage {
+            coverage.write(app.value_of(OUTPUT_FORMAT.0).unwrap(), report_writer)?;
+        }
         Ok(exit_code)
     }
 }
 
+struct GuardFile {
+    prefix: String,
+    file: DirEntry,
+    test_files: Vec<PathBuf>,
+    snapshot_files: Vec<DirEntry>,
+}
+
+//
+// Rules files found under the directory, grouped by their parent directory. Test files are
+// matched to a rules file as laid out by the test layout, data files for test --snapshot are
+// matched when they are in the snapshots directory next to it and their name starts with the
+// name of the rules file
+//
+fn guard_files(
+    dir: &str,
+    rules_selector: &FileSelector,
+    data_selector: &FileSelector,
+    layout: &TestLayout,
+) -> Result<BTreeMap<String, Vec<GuardFile>>> {
+    let walk = walkdir::WalkDir::new(dir);
+    let mut non_guard: Vec<DirEntry> = vec![];
+    let mut ordered_guard_files: BTreeMap<String, Vec<GuardFile>> = BTreeMap::new();
+    for file in walk
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| !rules_selector.is_excluded(entry.path(), Path::new(dir)))
+        .flatten()
+    {
+        if file.path().is_file() {
+            let name = file
+                .file_name()
+                .to_str()
+                .map_or("".to_string(), |s| s.to_string());
+            if let Some(prefix) = rules_selector.strip_extension(&name) {
+                let prefix = prefix.to_string();
+                let test_files = layout
+                    .mapped(file.path())
+                    .map_or_else(Vec::new, |test_files| test_files.to_vec());
+                ordered_guard_files
+                    .entry(
+                        file.path()
+                            .parent()
+                            .map_or("".to_string(), |p| format!("{}", p.display())),
+                    )
+                    .or_insert(vec![])
+                    .push(GuardFile {
+                        prefix,
+                        file,
+                        test_files,
+                        snapshot_files: vec![],
+                    });
+                continue;
+            } else {
+                non_guard.push(file);
+            }
+        }
+    }
+    layout.check_mapped(
+        ordered_guard_files
+            .values()
+            .flatten()
+            .map(|guard_file| guard_file.file.path()),
+    )?;
+    for file in non_guard {
+        let name = file
+            .file_name()
+            .to_str()
+            .map_or("".to_string(), |s| s.to_string());
+        if !data_selector.has_extension(&name) {
+            continue;
+        }
+        let parent = file.path().parent();
+        let snapshot = parent.is_some_and(|p| p.ends_with("snapshots"));
+        let rules_dir = if snapshot {
+            parent.and_then(Path::parent)
+        } else {
+            parent.and_then(|p| layout.rules_dir(p))
+        };
+        if let Some(candidates) = rules_dir.and_then(|rules_dir| {
+            let rules_dir = format!("{}", rules_dir.display());
+            ordered_guard_files.get_mut(&rules_dir)
+        }) {
+            for guard_file in candidates {
+                if snapshot {
+                    if name.starts_with(&guard_file.prefix) {
+                        guard_file.snapshot_files.push(file);
+                        break;
+                    }
+                } else if layout.mapped(guard_file.file.path()).is_none()
+                    && layout.is_test_of(&name, &guard_file.prefix)
+                {
+                    guard_file.test_files.push(file.into_path());
+                    break;
+                }
+            }
+        }
+    }
+    Ok(ordered_guard_files)
+}
+
+//
+// Files for test --watch, each rules file is paired with its test files. The status of a pair
+// is whether each rule expectation of each test case was met, PASS or FAIL
+//
+struct TestWatch<'a> {
+    dir: Option<&'a str>,
+    rules_file: Option<&'a str>,
+    test_data: Option<&'a str>,
+    rules_selector: FileSelector,
+    data_selector: FileSelector,
+    layout: TestLayout,
+    cmp: FileOrdering,
+    new_engine: bool,
+    rules_cache: Option<RulesCache>,
+}
+
+impl Watched for TestWatch<'_> {
+    fn discover(&self) -> Result<WatchedFiles> {
+        let pairs = match self.dir {
+            Some(dir) => {
+                validate_path(dir)?;
+                guard_files(dir, &self.rules_selector, &self.data_selector, &self.layout)?
+                    .into_values()
+                    .flatten()
+                    .filter(|guard_file| !guard_file.test_files.is_empty())
+                    .map(|guard_file| (guard_file.file.into_path(), guard_file.test_files))
+                    .collect()
+            }
+            None => {
+                let (file, data) = (self.rules_file.unwrap(), self.test_data.unwrap());
+                validate_path(file)?;
+                validate_path(FileSelector::base(data))?;
+                vec![(
+                    PathBuf::from(file),
+                    self.data_selector.select(data, self.cmp)?,
+                )]
+            }
+        };
+        Ok(WatchedFiles {
+            pairs,
+            shared: vec![],
+        })
+    }
+
+    fn evaluate(&self, rules_file: &Path, data_files: &[PathBuf]) -> Evaluated {
+        let content = std::fs::read_to_string(rules_file)
+            .map_err(|e| format!("Unable to read rule file content {e}"))?;
+        let name = match self.dir {
+            Some(_) => rules_file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| self.rules_selector.strip_extension(name))
+                .map_or_else(|| rules_file.display().to_string(), String::from),
+            None => rules_file.display().to_string(),
+        };
+        let rules = parse_rules(&content, &name, self.rules_cache.as_ref())
+            .map_err(|e| format!("Parse Error on ruleset file {e}"))?;
+
+        Ok(data_files
+            .iter()
+            .map(|data_file| {
+                let mut report = TestReport::Json(vec![]);
+                let mut console = Writer::new(WriteBuffer::Vec(vec![]), WriteBuffer::Vec(vec![]));
+                let exit_code = test_with_data(
+                    std::slice::from_ref(data_file),
+                    &rules,
+                    &name,
+                    false,
+                    self.new_engine,
+                    Some(&mut report),
+                    None,
+                    &mut TestRun::default(),
+                    &mut console,
+                )
+                .map_err(|e| format!("Error processing {}, {e}", data_file.display()))?;
+                if exit_code == 1 {
+                    return Err(console
+                        .into_string()
+                        .map_or_else(|e| e.to_string(), |output| output.trim_end().to_string()));
+                }
+                let TestReport::Json(files) = report else {
+                    unreachable!()
+                };
+                Ok(files
+                    .into_iter()
+                    .flat_map(|file| file.test_cases)
+                    .flat_map(|test_case| {
+                        let number = test_case.number;
+                        let test_case_name = test_case
+                            .name
+                            .unwrap_or_else(|| format!("Test Case #{number}"));
+                        test_case.rules.into_iter().map(move |rule| {
+                            (format!("{test_case_name}/{}", rule.name), rule.result)
+                        })
+                    })
+                    .collect::<Statuses>())
+            })
+            .collect())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct TestExpectations {
-    rules: HashMap<String, String>,
+    #[serde(default)]
+    rules: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    failures: Vec<FailureExpectation>,
 }
 
+//
+// A clause failure expected for a rule. The path is the JSON pointer of the value the clause
+// failed on and the message is matched when the failure message contains it, which includes the
+// custom message of the clause or the rule. Once a rule has failure expectations every clause
+// failure of the rule must be expected, so a rule cannot fail for another reason unnoticed
+//
 #[derive(Serialize, Deserialize, Debug)]
-struct TestSpec {
+#[serde(deny_unknown_fields)]
+struct FailureExpectation {
+    rule: String,
+    path: Option<String>,
+    message: Option<String>,
+}
+
+impl FailureExpectation {
+    #[allow(clippy::unnecessary_map_or)]
+    fn matches(&self, failure: &ClauseFailure<'_>) -> bool {
+        self.path.as_ref().map_or(true, |path| {
+            failure
+                .value
+                .is_some_and(|value| value.self_path().0 == *path)
+        }) && self
+            .message
+            .as_ref()
+            .map_or(true, |message| failure.message.contains(message.as_str()))
+    }
+}
+
+impl std::fmt::Display for FailureExpectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("failure")?;
+        if let Some(path) = &self.path {
+            write!(f, " at {path}")?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, " with message \"{message}\"")?;
+        }
+        Ok(())
+    }
+}
+
+//
+// Expected failures that did not happen and failures that were not expected, for one rule
+//
+fn unmet_failure_expectations(
+    expected: &[&FailureExpectation],
+    failures: &[&ClauseFailure<'_>],
+) -> Vec<String> {
+    let missing = expected
+        .iter()
+        .filter(|expectation| !failures.iter().any(|failure| expectation.matches(failure)))
+        .map(|expectation| format!("Expected {expectation}, not found"));
+    let unexpected = failures
+        .iter()
+        .filter(|failure| {
+            !expected
+                .iter()
+                .any(|expectation| expectation.matches(failure))
+        })
+        .map(|failure| {
+            let message = failure.message.replace('\n', " ");
+            match failure.value {
+                Some(value) => format!("Unexpected failure at {}: {message}", value.self_path().0),
+                None => format!("Unexpected failure: {message}"),
+            }
+        });
+    missing.chain(unexpected).collect()
+}
+
+//
+// A test case, the input is either inline or read from input_file, resolved relative to the
+// test file. The optional patch is merged on top of the input the way a JSON merge patch is,
+// a null removes the key, mappings are merged and any other value replaces the one in input
+//
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct TestSpec {
     name: Option<String>,
-    input: serde_yaml::Value,
+    #[serde(default, deserialize_with = "present")]
+    input: Option<serde_yaml::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    patch: Option<serde_yaml::Value>,
     expectations: TestExpectations,
 }
 
-#[allow(clippy::never_loop)]
+//
+// input: null is still an input, only a missing key is None
+//
+fn present<'de, D>(deserializer: D) -> std::result::Result<Option<serde_yaml::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    serde_yaml::Value::deserialize(deserializer).map(Some)
+}
+
+impl TestSpec {
+    pub(crate) fn new(
+        name: String,
+        input: serde_yaml::Value,
+        rules: BTreeMap<String, String>,
+    ) -> TestSpec {
+        TestSpec {
+            name: Some(name),
+            input: Some(input),
+            input_file: None,
+            patch: None,
+            expectations: TestExpectations {
+                rules,
+                failures: vec![],
+            },
+        }
+    }
+
+    fn input(&mut self, test_file: &Path) -> Result<serde_yaml::Value> {
+        let mut input = match (self.input.take(), &self.input_file) {
+            (Some(input), None) => input,
+            (None, Some(input_file)) => {
+                let path = match test_file.parent() {
+                    Some(dir) => dir.join(input_file),
+                    None => PathBuf::from(input_file),
+                };
+                let content = std::fs::read_to_string(&path).map_err(|e| {
+                    Error::ParseError(format!("Unable to read input_file {}, {e}", path.display()))
+                })?;
+                serde_yaml::from_str(&content).map_err(|e| {
+                    Error::ParseError(format!(
+                        "Unable to parse input_file {}, {e}",
+                        path.display()
+                    ))
+                })?
+            }
+            (Some(_), Some(_)) => {
+                return Err(Error::ParseError(String::from(
+                    "Test case has both input and input_file, only one of them can be set",
+                )))
+            }
+            (None, None) => {
+                return Err(Error::ParseError(String::from(
+                    "Test case has neither input nor input_file",
+                )))
+            }
+        };
+        if let Some(patch) = self.patch.take() {
+            merge_patch(&mut input, patch);
+        }
+        Ok(input)
+    }
+}
+
+fn merge_patch(target: &mut serde_yaml::Value, patch: serde_yaml::Value) {
+    match patch {
+        serde_yaml::Value::Mapping(patch) => {
+            if !target.is_mapping() {
+                *target = serde_yaml::Value::Mapping(serde_yaml::Mapping::new());
+            }
+            let target = target.as_mapping_mut().unwrap();
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(&key);
+                } else {
+                    merge_patch(target.entry(key).or_insert(serde_yaml::Value::Null), value);
+                }
+            }
+        }
+        patch => *target = patch,
+    }
+}
+
+//
+// Results of a single test case for the report formats. Clause failures are only collected in
+// verbose mode, in the same form validate reports them
+//
+#[derive(Debug, Serialize)]
+struct TestCaseResult {
+    name: Option<String>,
+    number: usize,
+    rules: Vec<RuleResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleResult {
+    name: String,
+    expected: Status,
+    evaluated: Vec<Status>,
+    result: Status,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failures: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unmet_failure_expectations: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TestFileResult {
+    rules_file: String,
+    test_file: String,
+    test_cases: Vec<TestCaseResult>,
+}
+
+#[derive(Debug)]
+enum TestReport {
+    JUnit(JunitReport),
+    Json(Vec<TestFileResult>),
+    Yaml(Vec<TestFileResult>),
+}
+
+impl TestReport {
+    fn new(output_format: &str) -> Option<TestReport> {
+        match output_format {
+            "junit" => Some(TestReport::JUnit(JunitReport::new())),
+            "json" => Some(TestReport::Json(vec![])),
+            "yaml" => Some(TestReport::Yaml(vec![])),
+            _ => None,
+        }
+    }
+
+    fn add_test_case(&mut self, rules_file: &str, test_file: &str, test_case: TestCaseResult) {
+        match self {
+            TestReport::JUnit(junit) => {
+                let number = test_case.number;
+                let class_name = test_case
+                    .name
+                    .unwrap_or_else(|| format!("Test Case #{number}"));
+                for rule in test_case.rules {
+                    let outcome = match rule.result {
+                        Status::FAIL => TestOutcome::Failed {
+                            message: format!(
+                                "Expected = {}, Evaluated = {:?}",
+                                rule.expected, rule.evaluated
+                            ),
+                            details: rule.unmet_failure_expectations.join("\n"),
+                        },
+                        _ => TestOutcome::Passed,
+                    };
+                    junit.add_test_case(
+                        rules_file,
+                        JunitTestCase {
+                            name: rule.name,
+                            class_name: class_name.clone(),
+                            outcome,
+                        },
+                    );
+                }
+            }
+
+            TestReport::Json(files) | TestReport::Yaml(files) => match files.last_mut() {
+                Some(file) if file.rules_file == rules_file && file.test_file == test_file => {
+                    file.test_cases.push(test_case)
+                }
+                _ => files.push(TestFileResult {
+                    rules_file: rules_file.to_string(),
+                    test_file: test_file.to_string(),
+                    test_cases: vec![test_case],
+                }),
+            },
+        }
+    }
+
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match self {
+            TestReport::JUnit(junit) => junit.write(writer)?,
+            TestReport::Json(files) => {
+                serde_json::to_writer_pretty(&mut *writer, files)?;
+                writeln!(writer)?;
+            }
+            TestReport::Yaml(files) => serde_yaml::to_writer(&mut *writer, files)?,
+        }
+        Ok(())
+    }
+}
+
+//
+// Selection of the test cases of a run and their results. With a filter a test case runs when
+// its name matches, otherwise only the expectations of the rules whose name matches are checked
+// and a test case without any is left out. A pattern without glob characters matches anywhere
+// in the name
+//
+#[derive(Debug, Default)]
+struct TestRun {
+    filter: Option<(String, GlobMatcher)>,
+    fail_fast: bool,
+    timing: bool,
+    passed: usize,
+    failed: usize,
+    filtered: usize,
+    elapsed: Duration,
+    stopped: bool,
+}
+
+impl TestRun {
+    fn new(filter: Option<&str>, fail_fast: bool, timing: bool) -> Result<TestRun> {
+        let filter = match filter {
+            Some(pattern) => {
+                let glob = if is_pattern(pattern) {
+                    pattern.to_string()
+                } else {
+                    format!("*{pattern}*")
+                };
+                let matcher = Glob::new(&glob)
+                    .map_err(|e| Error::ParseError(format!("Invalid filter `{pattern}`, {e}")))?
+                    .compile_matcher();
+                Some((pattern.to_string(), matcher))
+            }
+            None => None,
+        };
+        Ok(TestRun {
+            filter,
+            fail_fast,
+            timing,
+            ..Default::default()
+        })
+    }
+
+    //
+    // The filter for the rules of a test case, None when all of its rules are checked
+    //
+    fn rule_filter(&self, name: Option<&str>) -> Option<GlobMatcher> {
+        match &self.filter {
+            Some((_, filter)) if !name.is_some_and(|name| filter.is_match(name)) => {
+                Some(filter.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn add_test_case(&mut self, met: usize, unmet: usize, elapsed: Duration) -> Option<String> {
+        self.elapsed += elapsed;
+        let result = if unmet == 0 {
+            self.passed += 1;
+            Status::PASS
+        } else {
+            self.failed += 1;
+            Status::FAIL
+        };
+        self.timing.then(|| {
+            format!(
+                "{result}, {met} of {} rule expectations met in {:.3}ms",
+                met + unmet,
+                milliseconds(elapsed)
+            )
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        if self.stopped {
+            writeln!(
+                writer,
+                "Stopped at the first failing test case, --{} is set",
+                FAIL_FAST.0
+            )?;
+        }
+        if let Some((pattern, _)) = &self.filter {
+            if self.passed + self.failed == 0 {
+                writeln!(
+                    writer,
+                    "No test case or rule matched --{} {pattern}",
+                    FILTER.0
+                )?;
+            }
+        }
+        if self.timing {
+            writeln!(
+                writer,
+                "Ran {} test cases in {:.3}ms, {} passed, {} failed, {} filtered out",
+                self.passed + self.failed,
+                milliseconds(self.elapsed),
+                self.passed,
+                self.failed,
+                self.filtered
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(clippy::never_loop, clippy::too_many_arguments)]
 fn test_with_data(
     test_data_files: &[PathBuf],
     rules: &RulesFile<'_>,
+    rules_file_name: &str,
     verbose: bool,
     new_engine: bool,
+    mut report: Option<&mut TestReport>,
+    mut coverage: Option<&mut Coverage>,
+    run: &mut TestRun,
     writer: &mut Writer,
 ) -> Result<i32> {
+    if let Some(coverage) = coverage.as_deref_mut() {
+        coverage.add_rules_file(rules_file_name, rules);
+    }
     let mut exit_code = 0;
     let mut test_counter = 1;
     for specs in iterate_over(test_data_files, |data, path| {
         match serde_yaml::from_str::<Vec<TestSpec>>(&data) {
-            Ok(spec) => Ok(spec),
+            Ok(spec) => Ok((spec, path.display().to_string())),
             Err(_) => match serde_json::from_str::<Vec<TestSpec>>(&data) {
-                Ok(specs) => Ok(specs),
+                Ok(specs) => Ok((specs, path.display().to_string())),
                 Err(e) => Err(Error::ParseError(format!(
                     "Unable to process data in file {}, Error {},",
                     path.display(),
@@ -320,33 +1051,123 @@ fn test_with_data(
             Err(e) => {
                 writeln!(writer, "Error processing {e}")?;
                 exit_code = 1;
+                if run.fail_fast {
+                    run.stopped = true;
+                    return Ok(exit_code);
+                }
             }
-            Ok(specs) => {
-                for each in specs {
+            Ok((specs, test_file)) => {
+                for mut each in specs {
+                    let rule_filter = run.rule_filter(each.name.as_deref());
+                    if rule_filter.as_ref().is_some_and(|filter| {
+                        !each
+                            .expectations
+                            .rules
+                            .keys()
+                            .any(|rule| filter.is_match(rule))
+                            && !each
+                                .expectations
+                                .failures
+                                .iter()
+                                .any(|expectation| filter.is_match(&expectation.rule))
+                    }) {
+                        run.filtered += 1;
+                        test_counter += 1;
+                        continue;
+                    }
+                    let started = Instant::now();
                     writeln!(writer, "Test Case #{test_counter}")?;
-                    if each.name.is_some() {
-                        writeln!(writer, "Name: {}", each.name.unwrap())?;
+                    if let Some(name) = &each.name {
+                        writeln!(writer, "Name: {name}")?;
                     }
+                    let input = match each.input(Path::new(&test_file)) {
+                        Ok(input) => input,
+                        Err(e) => {
+                            writeln!(writer, "  Error processing {e}\n")?;
+                            exit_code = 1;
+                            run.failed += 1;
+                            test_counter += 1;
+                            if run.fail_fast {
+                                run.stopped = true;
+                                return Ok(exit_code);
+                            }
+                            continue;
+                        }
+                    };
+                    let mut test_case = TestCaseResult {
+                        name: each.name.clone(),
+                        number: test_counter,
+                        rules: vec![],
+                        duration_ms: None,
+                    };
 
                     let by_result = if new_engine {
                         let mut by_result = HashMap::new();
-                        let root = PathAwareValue::try_from(each.input)?;
+                        let root = PathAwareValue::try_from(input)?;
                         let mut root_scope = crate::rules::eval_context::root_scope(rules, &root)?;
                         eval_rules_file(rules, &mut root_scope)?;
                         let top = root_scope.reset_recorder().extract();
+                        if let Some(coverage) = coverage.as_deref_mut() {
+                            coverage.add_test_case(rules_file_name, &top);
+                        }
+                        let expected_failures = &each.expectations.failures;
+                        let file_report = match report {
+                            Some(_) if verbose => Some(simplifed_json_from_root(&top)?),
+                            _ if !expected_failures.is_empty() => {
+                                Some(simplifed_json_from_root(&top)?)
+                            }
+                            _ => None,
+                        };
+                        let clause_failures = match &file_report {
+                            Some(file_report) if !expected_failures.is_empty() => {
+                                collect_clause_failures(file_report)
+                            }
+                            _ => vec![],
+                        };
+
+                        let by_rules =
+                            top.children.iter().fold(IndexMap::new(), |mut acc, rule| {
+                                if let Some(RecordType::RuleCheck(NamedStatus { name, .. })) =
+                                    rule.container
+                                {
+                                    acc.entry(name).or_insert(vec![]).push(&rule.container)
+                                }
+                                acc
+                            });
 
-                        let by_rules = top.children.iter().fold(HashMap::new(), |mut acc, rule| {
-                            if let Some(RecordType::RuleCheck(NamedStatus { name, .. })) =
-                                rule.container
+                        for expectation in expected_failures {
+                            if rule_filter
+                                .as_ref()
+                                .is_some_and(|filter| !filter.is_match(&expectation.rule))
                             {
-                                acc.entry(name).or_insert(vec![]).push(&rule.container)
+                                continue;
+                            }
+                            if !by_rules.contains_key(expectation.rule.as_str()) {
+                                by_result
+                                    .entry(String::from("FAIL"))
+                                    .or_insert_with(indexmap::IndexSet::new)
+                                    .insert(format!(
+                                        "{}: Expected {expectation}, rule was not evaluated",
+                                        expectation.rule
+                                    ));
+                                exit_code = 7;
                             }
-                            acc
-                        });
+                        }
 
                         for (rule_name, rule) in by_rules {
+                            if rule_filter
+                                .as_ref()
+                                .is_some_and(|filter| !filter.is_match(rule_name))
+                            {
+                                continue;
+                            }
+                            let rule_expected_failures = expected_failures
+                                .iter()
+                                .filter(|expectation| expectation.rule == rule_name)
+                                .collect::<Vec<_>>();
                             let expected = match each.expectations.rules.get(rule_name) {
                                 Some(exp) => Status::try_from(exp.as_str())?,
+                                None if !rule_expected_failures.is_empty() => Status::FAIL,
                                 None => {
                                     writeln!(
                                         writer,
@@ -387,8 +1208,65 @@ fn test_with_data(
                                 }
                                 break 'matched None;
                             };
+                            let unmet = if rule_expected_failures.is_empty() {
+                                vec![]
+                            } else {
+                                unmet_failure_expectations(
+                                    &rule_expected_failures,
+                                    &clause_failures
+                                        .iter()
+                                        .filter(|failure| failure.top_level_rule == rule_name)
+                                        .collect::<Vec<_>>(),
+                                )
+                            };
+
+                            let failures = match &file_report {
+                                Some(file_report) if verbose => file_report
+                                    .not_compliant
+                                    .iter()
+                                    .filter(|clause| {
+                                        matches!(clause, ClauseReport::Rule(rule) if rule.name == rule_name)
+                                    })
+                                    .map(serde_json::to_value)
+                                    .collect::<std::result::Result<Vec<_>, _>>()?,
+                                _ => vec![],
+                            };
+                            test_case.rules.push(RuleResult {
+                                name: rule_name.to_string(),
+                                expected,
+                                evaluated: rule
+                                    .iter()
+                                    .copied()
+                                    .flatten()
+                                    .filter_map(|each| match each {
+                                        RecordType::RuleCheck(NamedStatus { status, .. }) => {
+                                            Some(*status)
+                                        }
+                                        _ => None,
+                                    })
+                                    .collect(),
+                                result: if matched.is_some() && unmet.is_empty() {
+                                    Status::PASS
+                                } else {
+                                    Status::FAIL
+                                },
+                                failures,
+                                unmet_failure_expectations: unmet.clone(),
+                            });
 
                             match matched {
+                                Some(status) if !unmet.is_empty() => {
+                                    let failed = by_result
+                                        .entry(String::from("FAIL"))
+                                        .or_insert_with(indexmap::IndexSet::new);
+                                    for unmet in unmet {
+                                        failed.insert(format!(
+                                            "{rule_name}: Expected = {status}, {unmet}"
+                                        ));
+                                    }
+                                    exit_code = 7;
+                                }
+
                                 Some(status) => {
                                     by_result
                                         .entry(String::from("PASS"))
@@ -413,15 +1291,27 @@ fn test_with_data(
                         }
                         by_result
                     } else {
-                        let root = PathAwareValue::try_from(each.input)?;
+                        let root = PathAwareValue::try_from(input)?;
                         let context = RootScope::new(rules, &root)?;
                         let stacker = StackTracker::new(&context);
                         rules.evaluate(&root, &stacker)?;
                         let expectations = each.expectations.rules;
                         let stack = stacker.stack();
+                        if !each.expectations.failures.is_empty() {
+                            writeln!(
+                                writer,
+                                "  Failure expectations are not checked with the previous engine"
+                            )?;
+                        }
 
                         let mut by_result = HashMap::new();
                         for each in &stack[0].children {
+                            if rule_filter
+                                .as_ref()
+                                .is_some_and(|filter| !filter.is_match(&each.context))
+                            {
+                                continue;
+                            }
                             match expectations.get(&each.context) {
                                 Some(value) => match Status::try_from(value.as_str()) {
                                     Err(e) => {
@@ -430,6 +1320,18 @@ fn test_with_data(
                                     }
                                     Ok(status) => {
                                         let got = each.status.unwrap();
+                                        test_case.rules.push(RuleResult {
+                                            name: each.context.clone(),
+                                            expected: status,
+                                            evaluated: vec![got],
+                                            result: if status != got {
+                                                Status::FAIL
+                                            } else {
+                                                Status::PASS
+                                            },
+                                            failures: vec![],
+                                            unmet_failure_expectations: vec![],
+                                        });
                                         if status != got {
                                             by_result
                                                 .entry(String::from("FAILED"))
@@ -462,8 +1364,24 @@ fn test_with_data(
                         }
                         by_result
                     };
-                    print_test_case_report(&by_result, writer);
+                    let elapsed = started.elapsed();
+                    let met = by_result.get("PASS").map_or(0, |met| met.len());
+                    let unmet = by_result
+                        .iter()
+                        .filter(|(result, _)| result.as_str() != "PASS")
+                        .map(|(_, unmet)| unmet.len())
+                        .sum::<usize>();
+                    let summary = run.add_test_case(met, unmet, elapsed);
+                    print_test_case_report(&by_result, summary.as_deref(), writer);
+                    if let Some(report) = report.as_deref_mut() {
+                        test_case.duration_ms = run.timing.then(|| milliseconds(elapsed));
+                        report.add_test_case(rules_file_name, &test_file, test_case);
+                    }
                     test_counter += 1;
+                    if run.fail_fast && exit_code != 0 {
+                        run.stopped = true;
+                        return Ok(exit_code);
+                    }
                 }
             }
         }
@@ -473,6 +1391,7 @@ fn test_with_data(
 
 pub(crate) fn print_test_case_report(
     by_result: &HashMap<String, indexmap::IndexSet<String>>,
+    summary: Option<&str>,
     writer: &mut Writer,
 ) {
     use itertools::Itertools;
@@ -486,5 +1405,12 @@ pub(crate) fn print_test_case_report(
             writeln!(writer, "    {}", *each_case).expect("Unable to write to the output");
         }
     }
+    if let Some(summary) = summary {
+        writeln!(writer, "  {summary}").expect("Unable to write to the output");
+    }
     writeln!(writer).expect("Unable to write to the output");
 }
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
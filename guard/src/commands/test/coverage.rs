@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use colored::*;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::rules::eval_context::EventRecord;
+use crate::rules::exprs::{
+    Block, Conjunctions, FileLocation, GuardAccessClause, GuardClause, GuardNamedRuleClause, Rule,
+    RuleClause, RulesFile, WhenGuardClause,
+};
+use crate::rules::{BlockCheck, ClauseCheck, NamedStatus, RecordType, Result, Status};
+
+//
+// Coverage of the rules files by the test cases of a test run. A rule is tested once it was PASS
+// or FAIL for a test case, a clause once it was evaluated, and a when branch once a clause of its
+// block was evaluated. Clauses are the access clauses and named rule clauses of the rules, they
+// are told apart by the rule they are in and their line and column, which the evaluation records
+// them with
+//
+#[derive(Debug, Default)]
+pub(crate) struct Coverage {
+    files: IndexMap<String, FileCoverage>,
+}
+
+#[derive(Debug, Default)]
+struct FileCoverage {
+    rules: IndexMap<String, RuleCounts>,
+    clauses: Vec<ClauseCoverage>,
+    branches: Vec<BranchCoverage>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+struct RuleCounts {
+    pass: usize,
+    fail: usize,
+    skip: usize,
+}
+
+#[derive(Debug)]
+struct ClauseCoverage {
+    rule: String,
+    line: u32,
+    column: u32,
+    clause: String,
+    evaluated: usize,
+    failed: usize,
+}
+
+//
+// The clauses of the when conditions are covered like any other clause, a branch refers to the
+// range of clauses of the block that is only evaluated when the conditions pass
+//
+#[derive(Debug)]
+struct BranchCoverage {
+    rule: String,
+    line: u32,
+    column: u32,
+    block: std::ops::Range<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct CoverageDocument<'a> {
+    coverage: Summary<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    percentage: f64,
+    rules_files: Vec<FileSummary<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct FileSummary<'a> {
+    rules_file: &'a str,
+    rules: Counted<RuleSummary<'a>>,
+    clauses: Counted<ClauseSummary<'a>>,
+    when_branches: Counted<BranchSummary<'a>>,
+    never_failing_clauses: Vec<ClauseSummary<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Counted<T> {
+    covered: usize,
+    total: usize,
+    uncovered: Vec<T>,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleSummary<'a> {
+    rule: &'a str,
+    #[serde(flatten)]
+    counts: RuleCounts,
+}
+
+#[derive(Debug, Serialize)]
+struct ClauseSummary<'a> {
+    rule: &'a str,
+    line: u32,
+    column: u32,
+    clause: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct BranchSummary<'a> {
+    rule: &'a str,
+    line: u32,
+    column: u32,
+}
+
+impl Coverage {
+    pub(crate) fn new() -> Self {
+        Coverage::default()
+    }
+
+    pub(crate) fn add_rules_file(&mut self, rules_file_name: &str, rules: &RulesFile<'_>) {
+        if self.files.contains_key(rules_file_name) {
+            return;
+        }
+        let mut file = FileCoverage::default();
+        for rule in &rules.guard_rules {
+            file.rules
+                .insert(rule.rule_name.clone(), RuleCounts::default());
+            file.add_rule(rule);
+        }
+        for parameterized in &rules.parameterized_rules {
+            file.add_rule(&parameterized.rule);
+        }
+        self.files.insert(rules_file_name.to_string(), file);
+    }
+
+    pub(crate) fn add_test_case(&mut self, rules_file_name: &str, root_record: &EventRecord<'_>) {
+        let file = match self.files.get_mut(rules_file_name) {
+            Some(file) => file,
+            None => return,
+        };
+        for each in &root_record.children {
+            if let Some(RecordType::RuleCheck(NamedStatus { name, status, .. })) = &each.container {
+                if let Some(counts) = file.rules.get_mut(*name) {
+                    match status {
+                        Status::PASS => counts.pass += 1,
+                        Status::FAIL => counts.fail += 1,
+                        Status::SKIP => counts.skip += 1,
+                    }
+                }
+            }
+        }
+
+        let mut statuses = HashMap::new();
+        clause_statuses(root_record, "", &mut statuses);
+        for clause in &mut file.clauses {
+            if let Some(statuses) =
+                statuses.get(&(clause.rule.as_str(), clause.line, clause.column))
+            {
+                clause.evaluated += statuses.len();
+                clause.failed += statuses
+                    .iter()
+                    .filter(|status| **status == Status::FAIL)
+                    .count();
+            }
+        }
+    }
+
+    pub(crate) fn write(&self, output_format: &str, writer: &mut dyn Write) -> Result<()> {
+        let summary = self.summary();
+        match output_format {
+            "json" => {
+                writeln!(writer)?;
+                serde_json::to_writer_pretty(
+                    &mut *writer,
+                    &CoverageDocument { coverage: summary },
+                )?;
+                writeln!(writer)?;
+            }
+            "yaml" => {
+                writeln!(writer, "---")?;
+                serde_yaml::to_writer(writer, &CoverageDocument { coverage: summary })?;
+            }
+            _ => write_text(&summary, writer)?,
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> Summary<'_> {
+        let rules_files = self
+            .files
+            .iter()
+            .map(|(rules_file, file)| file.summary(rules_file))
+            .collect::<Vec<_>>();
+        let (covered, total) = rules_files.iter().fold((0, 0), |(covered, total), file| {
+            (
+                covered + file.rules.covered + file.clauses.covered + file.when_branches.covered,
+                total + file.rules.total + file.clauses.total + file.when_branches.total,
+            )
+        });
+        Summary {
+            percentage: percentage(covered, total),
+            rules_files,
+        }
+    }
+}
+
+impl FileCoverage {
+    fn add_rule(&mut self, rule: &Rule<'_>) {
+        let name = rule.rule_name.as_str();
+        match &rule.conditions {
+            Some(conditions) => {
+                self.add_conditions(name, conditions);
+                let start = self.clauses.len();
+                self.add_rule_block(name, &rule.block);
+                self.add_branch(name, conditions, start);
+            }
+            None => self.add_rule_block(name, &rule.block),
+        }
+    }
+
+    fn add_rule_block(&mut self, rule: &str, block: &Block<'_, RuleClause<'_>>) {
+        for clause in block.conjunctions.iter().flatten() {
+            match clause {
+                RuleClause::Clause(clause) => self.add_clause(rule, clause),
+                RuleClause::WhenBlock(conditions, block) => {
+                    self.add_when_block(rule, conditions, block)
+                }
+                RuleClause::TypeBlock(type_block) => match &type_block.conditions {
+                    Some(conditions) => self.add_when_block(rule, conditions, &type_block.block),
+                    None => self.add_block(rule, &type_block.block),
+                },
+            }
+        }
+    }
+
+    fn add_when_block(
+        &mut self,
+        rule: &str,
+        conditions: &Conjunctions<WhenGuardClause<'_>>,
+        block: &Block<'_, GuardClause<'_>>,
+    ) {
+        self.add_conditions(rule, conditions);
+        let start = self.clauses.len();
+        self.add_block(rule, block);
+        self.add_branch(rule, conditions, start);
+    }
+
+    fn add_block(&mut self, rule: &str, block: &Block<'_, GuardClause<'_>>) {
+        for clause in block.conjunctions.iter().flatten() {
+            self.add_clause(rule, clause);
+        }
+    }
+
+    fn add_clause(&mut self, rule: &str, clause: &GuardClause<'_>) {
+        match clause {
+            GuardClause::Clause(gac) => self.add_access_clause(rule, gac),
+            GuardClause::NamedRule(gnc) => self.add_named_rule_clause(rule, gnc),
+            GuardClause::BlockClause(block_clause) => self.add_block(rule, &block_clause.block),
+            GuardClause::WhenBlock(conditions, block) => {
+                self.add_when_block(rule, conditions, block)
+            }
+            GuardClause::ParameterizedNamedRule(_) => {}
+        }
+    }
+
+    fn add_conditions(&mut self, rule: &str, conditions: &Conjunctions<WhenGuardClause<'_>>) {
+        for clause in conditions.iter().flatten() {
+            match clause {
+                WhenGuardClause::Clause(gac) => self.add_access_clause(rule, gac),
+                WhenGuardClause::NamedRule(gnc) => self.add_named_rule_clause(rule, gnc),
+                WhenGuardClause::ParameterizedNamedRule(_) => {}
+            }
+        }
+    }
+
+    fn add_access_clause(&mut self, rule: &str, gac: &GuardAccessClause<'_>) {
+        self.clauses.push(ClauseCoverage::new(
+            rule,
+            &gac.access_clause.location,
+            format!("{gac}"),
+        ))
+    }
+
+    fn add_named_rule_clause(&mut self, rule: &str, gnc: &GuardNamedRuleClause<'_>) {
+        self.clauses
+            .push(ClauseCoverage::new(rule, &gnc.location, format!("{gnc}")))
+    }
+
+    //
+    // Branches are located at their first condition, when blocks with no clauses of their own
+    // are left out as they cannot tell whether they were taken
+    //
+    fn add_branch(
+        &mut self,
+        rule: &str,
+        conditions: &Conjunctions<WhenGuardClause<'_>>,
+        start: usize,
+    ) {
+        if start == self.clauses.len() {
+            return;
+        }
+        let (line, column) = conditions
+            .iter()
+            .flatten()
+            .map(|clause| match clause {
+                WhenGuardClause::Clause(gac) => (
+                    gac.access_clause.location.line,
+                    gac.access_clause.location.column,
+                ),
+                WhenGuardClause::NamedRule(gnc) => (gnc.location.line, gnc.location.column),
+                WhenGuardClause::ParameterizedNamedRule(prc) => {
+                    (prc.named_rule.location.line, prc.named_rule.location.column)
+                }
+            })
+            .next()
+            .unwrap_or_else(|| (self.clauses[start].line, self.clauses[start].column));
+        self.branches.push(BranchCoverage {
+            rule: rule.to_string(),
+            line,
+            column,
+            block: start..self.clauses.len(),
+        });
+    }
+
+    fn summary<'a>(&'a self, rules_file: &'a str) -> FileSummary<'a> {
+        let untested_rules = self
+            .rules
+            .iter()
+            .filter(|(_, counts)| counts.pass + counts.fail == 0)
+            .map(|(rule, counts)| RuleSummary {
+                rule,
+                counts: *counts,
+            })
+            .collect::<Vec<_>>();
+        let unevaluated_clauses = self
+            .clauses
+            .iter()
+            .filter(|clause| clause.evaluated == 0)
+            .map(ClauseCoverage::summary)
+            .collect::<Vec<_>>();
+        let untaken_branches = self
+            .branches
+            .iter()
+            .filter(|branch| {
+                self.clauses[branch.block.clone()]
+                    .iter()
+                    .all(|clause| clause.evaluated == 0)
+            })
+            .map(|branch| BranchSummary {
+                rule: &branch.rule,
+                line: branch.line,
+                column: branch.column,
+            })
+            .collect::<Vec<_>>();
+
+        FileSummary {
+            rules_file,
+            rules: Counted::new(self.rules.len(), untested_rules),
+            clauses: Counted::new(self.clauses.len(), unevaluated_clauses),
+            when_branches: Counted::new(self.branches.len(), untaken_branches),
+            never_failing_clauses: self
+                .clauses
+                .iter()
+                .filter(|clause| clause.evaluated > 0 && clause.failed == 0)
+                .map(ClauseCoverage::summary)
+                .collect(),
+        }
+    }
+}
+
+impl ClauseCoverage {
+    fn new(rule: &str, location: &FileLocation<'_>, clause: String) -> Self {
+        ClauseCoverage {
+            rule: rule.to_string(),
+            line: location.line,
+            column: location.column,
+            clause: clause.trim().to_string(),
+            evaluated: 0,
+            failed: 0,
+        }
+    }
+
+    fn summary(&self) -> ClauseSummary<'_> {
+        ClauseSummary {
+            rule: &self.rule,
+            line: self.line,
+            column: self.column,
+            clause: &self.clause,
+        }
+    }
+}
+
+impl<T> Counted<T> {
+    fn new(total: usize, uncovered: Vec<T>) -> Self {
+        Counted {
+            covered: total - uncovered.len(),
+            total,
+            uncovered,
+        }
+    }
+}
+
+fn percentage(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        (covered as f64 * 10000.0 / total as f64).round() / 100.0
+    }
+}
+
+//
+// Statuses of the clause records keyed by the closest rule they were evaluated in and the line
+// and column of the clause, named rules evaluated through a clause of another rule are nested in
+// its records
+//
+fn clause_statuses<'r>(
+    record: &'r EventRecord<'_>,
+    rule: &'r str,
+    statuses: &mut HashMap<(&'r str, u32, u32), Vec<Status>>,
+) {
+    let rule = match &record.container {
+        Some(RecordType::RuleCheck(NamedStatus { name, .. })) => *name,
+        _ => rule,
+    };
+    let status = match &record.container {
+        Some(RecordType::GuardClauseBlockCheck(BlockCheck { status, .. })) => Some(*status),
+        Some(RecordType::ClauseValueCheck(ClauseCheck::Success)) => Some(Status::PASS),
+        Some(RecordType::ClauseValueCheck(_)) => Some(Status::FAIL),
+        _ => None,
+    };
+    if let (Some(status), Some((line, column))) = (status, record.location) {
+        statuses
+            .entry((rule, line, column))
+            .or_default()
+            .push(status);
+    }
+    for child in &record.children {
+        clause_statuses(child, rule, statuses);
+    }
+}
+
+fn write_text(summary: &Summary<'_>, writer: &mut dyn Write) -> Result<()> {
+    writeln!(writer, "{}", "Coverage".bold())?;
+    for file in &summary.rules_files {
+        writeln!(writer, "Rules file {}", file.rules_file)?;
+        write_counted(writer, "Rules tested", &file.rules)?;
+        for each in &file.rules.uncovered {
+            writeln!(
+                writer,
+                "    Untested rule {}, SKIP {} times",
+                each.rule, each.counts.skip
+            )?;
+        }
+        write_counted(writer, "Clauses evaluated", &file.clauses)?;
+        for each in &file.clauses.uncovered {
+            writeln!(
+                writer,
+                "    Not evaluated {} at line {}, column {}: {}",
+                each.rule, each.line, each.column, each.clause
+            )?;
+        }
+        for each in &file.never_failing_clauses {
+            writeln!(
+                writer,
+                "    Never failed {} at line {}, column {}: {}",
+                each.rule, each.line, each.column, each.clause
+            )?;
+        }
+        write_counted(writer, "When branches taken", &file.when_branches)?;
+        for each in &file.when_branches.uncovered {
+            writeln!(
+                writer,
+                "    Not taken {} at line {}, column {}",
+                each.rule, each.line, each.column
+            )?;
+        }
+    }
+    writeln!(writer, "Overall coverage {:.2}%", summary.percentage)?;
+    Ok(())
+}
+
+fn write_counted<T>(writer: &mut dyn Write, label: &str, counted: &Counted<T>) -> Result<()> {
+    writeln!(
+        writer,
+        "  {label}: {} of {} ({:.2}%)",
+        counted.covered,
+        counted.total,
+        percentage(counted.covered, counted.total)
+    )?;
+    Ok(())
+}
@@ -0,0 +1,438 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use colored::*;
+use indexmap::IndexMap;
+use serde_yaml::{Mapping, Value};
+
+use crate::commands::rules_cache::{parse_rules, RulesCache};
+use crate::commands::test::TestSpec;
+use crate::commands::validate::validate_path;
+use crate::commands::value_path::{remove, value_at_mut, Step};
+use crate::rules::errors::Error;
+use crate::rules::eval::eval_rules_file;
+use crate::rules::eval_context::{root_scope, RootScope};
+use crate::rules::exprs::{FileLocation, ParameterizedRule, QueryPart, RulesFile};
+use crate::rules::path_value::PathAwareValue;
+use crate::rules::{EvalContext, QueryResult, RecordTracer, RecordType, Result, Status};
+use crate::utils::writer::Writer;
+
+//
+// Runs a rules file against random inputs made from example documents, looking for inputs the
+// rules cannot be evaluated against rather than inputs the rules fail. Each case applies a few
+// mutations to one of the documents: a value changes its type, a key or list element is removed,
+// or a list, map or string is emptied. A document with a Type at the top is a single resource and
+// is placed under Resources like in a template
+//
+// Evaluation errors and panics are grouped by the kind of error and the clause it happened in, so
+// each distinct failure is reported once. Its first input is minimized by removing whatever still fails the same way and
+// written as a test spec that can be added to the tests of the rules file
+//
+pub(crate) struct Fuzz<'a> {
+    pub(crate) rules_file: &'a str,
+    pub(crate) inputs: Vec<&'a str>,
+    pub(crate) cases: usize,
+    pub(crate) seed: u64,
+    pub(crate) rules_cache: Option<RulesCache>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Evaluated,
+    Invalid,
+    Failed { signature: String, message: String },
+}
+
+#[derive(Debug)]
+struct Found {
+    case: usize,
+    count: usize,
+    message: String,
+    input: Value,
+}
+
+//
+// Panics are expected while the rules are evaluated against a case, the default hook would print
+// each of them. The hook is muted only while this is in scope around the evaluation and restored
+// when it is dropped, panics anywhere else in fuzz are printed as usual
+//
+struct QuietPanics {
+    restore: Option<Box<dyn FnOnce()>>,
+}
+
+impl QuietPanics {
+    fn new() -> Self {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        QuietPanics {
+            restore: Some(Box::new(move || std::panic::set_hook(hook))),
+        }
+    }
+}
+
+impl Drop for QuietPanics {
+    fn drop(&mut self) {
+        if let Some(restore) = self.restore.take() {
+            restore();
+        }
+    }
+}
+
+const MUTATIONS_PER_CASE: usize = 3;
+const MINIMIZE_BUDGET: usize = 1000;
+
+impl Fuzz<'_> {
+    pub(crate) fn run(&self, writer: &mut Writer) -> Result<i32> {
+        validate_path(self.rules_file)?;
+        let content = std::fs::read_to_string(self.rules_file)?;
+        let rules = parse_rules(&content, self.rules_file, self.rules_cache.as_ref())?;
+        let mut documents = Vec::with_capacity(self.inputs.len());
+        for input in &self.inputs {
+            validate_path(input)?;
+            let document = serde_yaml::from_str::<Value>(&std::fs::read_to_string(input)?)
+                .map_err(|e| {
+                    Error::ParseError(format!("Unable to parse fuzz input {input}, {e}"))
+                })?;
+            documents.push(as_template(document));
+        }
+
+        writeln!(
+            writer,
+            "Fuzzing {} with {} cases from {} documents, seed {}",
+            self.rules_file,
+            self.cases,
+            documents.len(),
+            self.seed
+        )?;
+
+        let (found, skipped) = self.run_cases(&rules, &documents);
+        let minimized = found
+            .iter()
+            .map(|(signature, found)| minimize(&rules, found.input.clone(), signature))
+            .collect::<Vec<_>>();
+
+        let failed = found.values().map(|found| found.count).sum::<usize>();
+        let distinct = found.len();
+        for (index, ((signature, found), input)) in found.iter().zip(minimized).enumerate() {
+            writeln!(
+                writer,
+                "{} {} of {}, {signature}, found by {} of {} cases",
+                "Failure".red().bold(),
+                index + 1,
+                distinct,
+                found.count,
+                self.cases
+            )?;
+            writeln!(writer, "  {}", found.message)?;
+            writeln!(writer, "  Minimized reproducer:")?;
+            let spec = TestSpec::new(
+                format!("fuzz seed {} case {} {signature}", self.seed, found.case),
+                input,
+                BTreeMap::new(),
+            );
+            write!(writer, "{}", serde_yaml::to_string(&vec![spec])?)?;
+        }
+
+        let mut summary = format!(
+            "Ran {} fuzz cases with seed {}, {failed} failed, {} distinct failures",
+            self.cases, self.seed, distinct
+        );
+        if skipped > 0 {
+            summary.push_str(&format!(", {skipped} skipped as invalid input"));
+        }
+        writeln!(writer, "{summary}")?;
+        Ok(if found.is_empty() { 0 } else { 7 })
+    }
+
+    fn run_cases(
+        &self,
+        rules: &RulesFile<'_>,
+        documents: &[Value],
+    ) -> (IndexMap<String, Found>, usize) {
+        let mut rng = Rng::new(self.seed);
+        let mut found = IndexMap::<String, Found>::new();
+        let mut skipped = 0;
+        for case in 1..=self.cases {
+            let mut input = documents[rng.below(documents.len())].clone();
+            for _ in 0..=rng.below(MUTATIONS_PER_CASE) {
+                mutate(&mut input, &mut rng);
+            }
+            match evaluate(rules, &input) {
+                Outcome::Evaluated => {}
+                Outcome::Invalid => skipped += 1,
+                Outcome::Failed { signature, message } => {
+                    found
+                        .entry(signature)
+                        .or_insert(Found {
+                            case,
+                            count: 0,
+                            message,
+                            input,
+                        })
+                        .count += 1;
+                }
+            }
+        }
+        (found, skipped)
+    }
+}
+
+//
+// splitmix64, enough to mutate inputs the same way for the same seed on every platform
+//
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+fn as_template(document: Value) -> Value {
+    let resource = matches!(&document,
+        Value::Mapping(map) if map.contains_key("Type") && !map.contains_key("Resources"));
+    if !resource {
+        return document;
+    }
+    let mut resources = Mapping::new();
+    resources.insert(Value::from("FuzzResource"), document);
+    let mut template = Mapping::new();
+    template.insert(Value::from("Resources"), Value::Mapping(resources));
+    Value::Mapping(template)
+}
+
+fn evaluate(rules: &RulesFile<'_>, input: &Value) -> Outcome {
+    let root = match PathAwareValue::try_from(input.clone()) {
+        Ok(root) => root,
+        Err(_) => return Outcome::Invalid,
+    };
+    let mut clause = None;
+    let result = {
+        let _quiet = QuietPanics::new();
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut scope = ClauseTracker {
+                scope: root_scope(rules, &root)?,
+                clause: &mut clause,
+            };
+            eval_rules_file(rules, &mut scope)
+        }))
+    };
+    let clause = clause
+        .map(|(line, column)| format!(" at line {line}, column {column}"))
+        .unwrap_or_default();
+    match result {
+        Ok(Ok(_)) => Outcome::Evaluated,
+        Ok(Err(e)) => {
+            let kind = format!("{e:?}");
+            Outcome::Failed {
+                signature: format!("{}{clause}", kind.split('(').next().unwrap_or_default()),
+                message: e.to_string(),
+            }
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Outcome::Failed {
+                signature: format!("panic {message}{clause}"),
+                message: format!("Evaluation panicked, {message}"),
+            }
+        }
+    }
+}
+
+//
+// Remembers where the clause evaluated last is in the rules file. Evaluation stops at the first
+// error or panic, so that is the clause it failed in. The records of the scope cannot tell, they
+// are closed as the error returns through each rule
+//
+struct ClauseTracker<'value, 'loc: 'value, 'c> {
+    scope: RootScope<'value, 'loc>,
+    clause: &'c mut Option<(u32, u32)>,
+}
+
+impl<'value, 'loc: 'value> EvalContext<'value, 'loc> for ClauseTracker<'value, 'loc, '_> {
+    fn query(&mut self, query: &'value [QueryPart<'loc>]) -> Result<Vec<QueryResult<'value>>> {
+        self.scope.query(query)
+    }
+
+    fn find_parameterized_rule(
+        &mut self,
+        rule_name: &str,
+    ) -> Result<&'value ParameterizedRule<'loc>> {
+        self.scope.find_parameterized_rule(rule_name)
+    }
+
+    fn root(&mut self) -> &'value PathAwareValue {
+        self.scope.root()
+    }
+
+    fn rule_status(&mut self, rule_name: &'value str) -> Result<Status> {
+        self.scope.rule_status(rule_name)
+    }
+
+    fn resolve_variable(&mut self, variable_name: &'value str) -> Result<Vec<QueryResult<'value>>> {
+        self.scope.resolve_variable(variable_name)
+    }
+
+    fn add_variable_capture_key(
+        &mut self,
+        variable_name: &'value str,
+        key: &'value PathAwareValue,
+    ) -> Result<()> {
+        self.scope.add_variable_capture_key(variable_name, key)
+    }
+
+    fn add_variable_capture_index(
+        &mut self,
+        variable_name: &str,
+        index: &'value PathAwareValue,
+    ) -> Result<()> {
+        self.scope.add_variable_capture_index(variable_name, index)
+    }
+}
+
+impl<'value> RecordTracer<'value> for ClauseTracker<'value, '_, '_> {
+    fn start_record(&mut self, context: &str) -> Result<()> {
+        self.scope.start_record(context)
+    }
+
+    fn start_record_at(&mut self, context: &str, location: &FileLocation<'_>) -> Result<()> {
+        *self.clause = Some((location.line, location.column));
+        self.scope.start_record_at(context, location)
+    }
+
+    fn end_record(&mut self, context: &str, record: RecordType<'value>) -> Result<()> {
+        self.scope.end_record(context, record)
+    }
+}
+
+//
+// Paths to every value below the root, parents before their children. Tagged values such as
+// !Ref are left whole
+//
+fn paths(value: &Value, prefix: &mut Vec<Step>, all: &mut Vec<Vec<Step>>) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, each) in map {
+                prefix.push(Step::Key(key.clone()));
+                all.push(prefix.clone());
+                paths(each, prefix, all);
+                prefix.pop();
+            }
+        }
+        Value::Sequence(list) => {
+            for (index, each) in list.iter().enumerate() {
+                prefix.push(Step::Index(index));
+                all.push(prefix.clone());
+                paths(each, prefix, all);
+                prefix.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn replacements() -> Vec<Value> {
+    vec![
+        Value::Null,
+        Value::Bool(true),
+        Value::from(0),
+        Value::from(-1),
+        Value::from(1.5),
+        Value::from(""),
+        Value::from("fuzz"),
+        Value::Sequence(vec![]),
+        Value::Sequence(vec![Value::from("fuzz")]),
+        Value::Mapping(Mapping::new()),
+    ]
+}
+
+fn mutate(input: &mut Value, rng: &mut Rng) {
+    let mut all = vec![];
+    paths(input, &mut vec![], &mut all);
+    if all.is_empty() {
+        return;
+    }
+    let path = &all[rng.below(all.len())];
+    match rng.below(3) {
+        0 => remove(input, path),
+        choice => {
+            if let Some(value) = value_at_mut(input, path) {
+                match value {
+                    Value::Sequence(list) if choice == 1 => list.clear(),
+                    Value::Mapping(map) if choice == 1 => *map = Mapping::new(),
+                    Value::String(string) if choice == 1 => string.clear(),
+                    _ => {
+                        let kind = std::mem::discriminant(&*value);
+                        let others = replacements()
+                            .into_iter()
+                            .filter(|each| std::mem::discriminant(each) != kind)
+                            .collect::<Vec<_>>();
+                        *value = others[rng.below(others.len())].clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+//
+// Removes keys and list elements, and empties lists and maps, for as long as the input still
+// fails with the same signature
+//
+fn minimize(rules: &RulesFile<'_>, mut input: Value, signature: &str) -> Value {
+    let mut budget = MINIMIZE_BUDGET;
+    'reduced: loop {
+        let mut all = vec![];
+        paths(&input, &mut vec![], &mut all);
+        for path in all {
+            let mut removed = input.clone();
+            remove(&mut removed, &path);
+            let mut emptied = input.clone();
+            match value_at_mut(&mut emptied, &path) {
+                Some(Value::Sequence(list)) if !list.is_empty() => list.clear(),
+                Some(Value::Mapping(map)) if !map.is_empty() => *map = Mapping::new(),
+                _ => {}
+            }
+            for candidate in [removed, emptied].iter() {
+                if *candidate == input {
+                    continue;
+                }
+                if budget == 0 {
+                    break 'reduced;
+                }
+                budget -= 1;
+                if matches!(evaluate(rules, candidate),
+                    Outcome::Failed { signature: found, .. } if found == signature)
+                {
+                    input = candidate.clone();
+                    continue 'reduced;
+                }
+            }
+        }
+        break;
+    }
+    input
+}
+
+#[cfg(test)]
+#[path = "fuzz_tests.rs"]
+mod fuzz_tests;
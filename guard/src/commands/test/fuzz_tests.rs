@@ -0,0 +1,71 @@
+use super::*;
+use crate::rules::parser::{rules_file, Span};
+
+const RULES: &str = "Resources.*.Properties.Tags !empty";
+
+#[test]
+fn test_same_seed_same_mutations() {
+    let document = serde_yaml::from_str::<Value>(
+        "Resources: { Bucket: { Type: AWS::S3::Bucket, Properties: { Tags: [a, b], Name: b } } }",
+    )
+    .unwrap();
+    let mutated = |seed| {
+        let mut rng = Rng::new(seed);
+        (0..5)
+            .map(|_| {
+                let mut input = document.clone();
+                mutate(&mut input, &mut rng);
+                input
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(mutated(7), mutated(7));
+    assert_ne!(mutated(7), mutated(8));
+}
+
+#[test]
+fn test_resource_is_placed_in_template() {
+    let resource = serde_yaml::from_str::<Value>("{ Type: AWS::S3::Bucket }").unwrap();
+    let expected =
+        serde_yaml::from_str::<Value>("Resources: { FuzzResource: { Type: AWS::S3::Bucket } }")
+            .unwrap();
+    assert_eq!(as_template(resource), expected);
+    assert_eq!(as_template(expected.clone()), expected);
+}
+
+#[test]
+fn test_minimize_keeps_what_fails() -> Result<()> {
+    let rules = rules_file(Span::new_extra(RULES, "fuzz.guard"))?;
+    let input = serde_yaml::from_str::<Value>(
+        "Resources: { Bucket: { Type: AWS::S3::Bucket, Properties: { Tags: 1, Name: b } }, Queue: { Type: AWS::SQS::Queue } }",
+    )?;
+    let signature = match evaluate(&rules, &input) {
+        Outcome::Failed { signature, .. } => signature,
+        outcome => panic!("expected a failure, got {:?}", outcome),
+    };
+    assert_eq!(signature, "IncompatibleError at line 1, column 1");
+    let expected =
+        serde_yaml::from_str::<Value>("Resources: { Bucket: { Properties: { Tags: 1 } } }")?;
+    assert_eq!(minimize(&rules, input, &signature), expected);
+    Ok(())
+}
+
+#[test]
+fn test_failures_in_different_clauses_differ() -> Result<()> {
+    let rules = rules_file(Span::new_extra(
+        "rule tags_present { Resources.*.Properties.Tags !empty }\n\
+         rule name_present { Resources.*.Properties.Name !empty }\n",
+        "fuzz.guard",
+    ))?;
+    let signature = |input: &str| -> Result<String> {
+        match evaluate(&rules, &serde_yaml::from_str::<Value>(input)?) {
+            Outcome::Failed { signature, .. } => Ok(signature),
+            outcome => panic!("expected a failure, got {:?}", outcome),
+        }
+    };
+    let tags = signature("Resources: { Bucket: { Properties: { Tags: 1, Name: [b] } } }")?;
+    let name = signature("Resources: { Bucket: { Properties: { Tags: [a], Name: 1 } } }")?;
+    assert_eq!(tags, "IncompatibleError at line 1, column 21");
+    assert_eq!(name, "IncompatibleError at line 2, column 21");
+    Ok(())
+}
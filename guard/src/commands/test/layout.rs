@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::commands::files::{is_pattern, regular_ordering, FileSelector};
+use crate::commands::validate::validate_path;
+use crate::rules::errors::Error;
+use crate::rules::Result;
+
+//
+// Where test --dir looks for the test files of a rules file. By default they are in the tests
+// directory next to the rules file and their name starts with the name of the rules file. The
+// tests directory can be another one relative to the rules file, "." for the directory of the
+// rules file itself, and with suffixes a test file is named after the rules file followed by
+// one of them, s3.guard is tested by s3.test.yaml with the suffix .test.yaml
+//
+// Rules files listed in a mapping file get their test files from it instead. The mapping is a
+// YAML or JSON map from rules files to test files, directories or glob patterns, both relative
+// to the mapping file
+//
+//   s3/bucket.guard:
+//     - tests/bucket_tests.yaml
+//     - tests/bucket/*.yaml
+//
+#[derive(Debug)]
+pub(crate) struct TestLayout {
+    tests_dir: PathBuf,
+    suffixes: Vec<String>,
+    mapping: Vec<(PathBuf, Vec<PathBuf>)>,
+}
+
+impl Default for TestLayout {
+    fn default() -> Self {
+        TestLayout {
+            tests_dir: PathBuf::from("tests"),
+            suffixes: vec![],
+            mapping: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MappedTests {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TestLayout {
+    pub(crate) fn new<'a, S>(
+        tests_dir: &str,
+        suffixes: S,
+        mapping: Option<&str>,
+        data_selector: &FileSelector,
+    ) -> Result<TestLayout>
+    where
+        S: IntoIterator<Item = &'a str>,
+    {
+        let mapping = match mapping {
+            Some(file) => Self::load_mapping(file, data_selector)?,
+            None => vec![],
+        };
+        Ok(TestLayout {
+            tests_dir: PathBuf::from(tests_dir),
+            suffixes: suffixes.into_iter().map(String::from).collect(),
+            mapping,
+        })
+    }
+
+    fn load_mapping(
+        file: &str,
+        data_selector: &FileSelector,
+    ) -> Result<Vec<(PathBuf, Vec<PathBuf>)>> {
+        validate_path(file)?;
+        let content = std::fs::read_to_string(file)?;
+        let entries = serde_yaml::from_str::<IndexMap<String, MappedTests>>(&content)
+            .map_err(|e| Error::ParseError(format!("Unable to read test mapping {file}, {e}")))?;
+        let base = Path::new(file).parent().unwrap_or_else(|| Path::new(""));
+        let mut mapping = Vec::with_capacity(entries.len());
+        for (rules_file, tests) in entries {
+            let tests = match tests {
+                MappedTests::One(test) => vec![test],
+                MappedTests::Many(tests) => tests,
+            };
+            let mut test_files = vec![];
+            for test in tests {
+                let test = format!("{}", base.join(test).display());
+                if !is_pattern(&test) {
+                    validate_path(&test)?;
+                }
+                test_files.extend(data_selector.select(&test, regular_ordering)?);
+            }
+            mapping.push((base.join(rules_file), test_files));
+        }
+        Ok(mapping)
+    }
+
+    //
+    // The directory of the rules files tested by the test files in this directory
+    //
+    pub(crate) fn rules_dir<'p>(&self, test_dir: &'p Path) -> Option<&'p Path> {
+        if self.tests_dir == Path::new(".") {
+            return Some(test_dir);
+        }
+        if !test_dir.ends_with(&self.tests_dir) {
+            return None;
+        }
+        test_dir
+            .ancestors()
+            .nth(self.tests_dir.components().count())
+    }
+
+    pub(crate) fn is_test_of(&self, name: &str, prefix: &str) -> bool {
+        if self.suffixes.is_empty() {
+            return name.starts_with(prefix);
+        }
+        name.strip_prefix(prefix)
+            .is_some_and(|suffix| self.suffixes.iter().any(|each| each == suffix))
+    }
+
+    pub(crate) fn mapped(&self, rules_file: &Path) -> Option<&[PathBuf]> {
+        self.mapping
+            .iter()
+            .find(|(mapped, _)| same_file(mapped, rules_file))
+            .map(|(_, test_files)| test_files.as_slice())
+    }
+
+    //
+    // Rules files of the mapping that are not among the rules files found, most likely a typo
+    //
+    pub(crate) fn check_mapped<'f, F>(&self, rules_files: F) -> Result<()>
+    where
+        F: IntoIterator<Item = &'f Path>,
+    {
+        let rules_files = rules_files.into_iter().collect::<Vec<_>>();
+        match self.mapping.iter().find(|(mapped, _)| {
+            !rules_files
+                .iter()
+                .any(|rules_file| same_file(mapped, rules_file))
+        }) {
+            Some((mapped, _)) => Err(Error::ParseError(format!(
+                "Rules file {} of the test mapping was not found in the directory",
+                mapped.display()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn same_file(first: &Path, second: &Path) -> bool {
+    match (first.canonicalize(), second.canonicalize()) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => first == second,
+    }
+}
@@ -0,0 +1,384 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use colored::*;
+
+use crate::commands::files::FileSelector;
+use crate::commands::rules_cache::{parse_rules, RulesCache};
+use crate::commands::test::guard_files;
+use crate::commands::test::layout::TestLayout;
+use crate::commands::validate::rule_selection::SelectedRules;
+use crate::commands::validate::{report_pair, OutputFormatType};
+use crate::rules::errors::Error;
+use crate::rules::Result;
+use crate::utils::writer::{WriteBuffer, Writer};
+
+//
+// Snapshots of the validate output for the data files in the snapshots directory next to each
+// rules file. The single-line-summary and json output of a (rules file, data file) pair are
+// stored together in a .snap file next to the data file. Names in the output are relative to the
+// directory, so the snapshots do not depend on where the directory is checked out
+//
+pub(crate) struct Snapshots<'a> {
+    pub(crate) dir: &'a str,
+    pub(crate) rules_selector: FileSelector,
+    pub(crate) data_selector: FileSelector,
+    pub(crate) layout: TestLayout,
+    pub(crate) rules_cache: Option<RulesCache>,
+    pub(crate) update: bool,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    matched: usize,
+    mismatched: usize,
+    missing: usize,
+    updated: usize,
+    written: usize,
+    errors: usize,
+}
+
+impl Counts {
+    fn total(&self) -> usize {
+        self.matched + self.mismatched + self.missing + self.updated + self.written + self.errors
+    }
+
+    fn exit_code(&self) -> i32 {
+        if self.mismatched + self.missing > 0 {
+            7
+        } else if self.errors > 0 {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+const OUTPUTS: [(&str, OutputFormatType); 2] = [
+    ("single-line-summary", OutputFormatType::SingleLineSummary),
+    ("json", OutputFormatType::JSON),
+];
+
+const DIFF_CONTEXT: usize = 3;
+
+impl Snapshots<'_> {
+    pub(crate) fn run(&self, writer: &mut Writer) -> Result<i32> {
+        let mut counts = Counts::default();
+        for guard_files in guard_files(
+            self.dir,
+            &self.rules_selector,
+            &self.data_selector,
+            &self.layout,
+        )?
+        .values()
+        {
+            for guard_file in guard_files {
+                if guard_file.snapshot_files.is_empty() {
+                    continue;
+                }
+                let rules_file_name = self.relative(guard_file.file.path());
+                let content = match std::fs::read_to_string(guard_file.file.path()) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        writer.write_err(format!("Unable read content from file {e}"))?;
+                        counts.errors += guard_file.snapshot_files.len();
+                        continue;
+                    }
+                };
+                let rules = match parse_rules(&content, &rules_file_name, self.rules_cache.as_ref())
+                {
+                    Ok(rules) => SelectedRules::all(rules),
+                    Err(e) => {
+                        writer.write_err(format!(
+                            "Parsing error handling rule file = {rules_file_name}, \
+                             Error = {e}\n---"
+                        ))?;
+                        counts.errors += guard_file.snapshot_files.len();
+                        continue;
+                    }
+                };
+                for data_file in &guard_file.snapshot_files {
+                    self.check(
+                        &rules,
+                        &rules_file_name,
+                        data_file.path(),
+                        &mut counts,
+                        writer,
+                    )?;
+                }
+            }
+        }
+
+        if counts.total() == 0 {
+            writeln!(
+                writer,
+                "No snapshots found, data files are taken from the snapshots directory next to \
+                 a rules file when their name starts with the name of the rules file"
+            )?;
+        } else if self.update {
+            writeln!(
+                writer,
+                "{} snapshots, {} unchanged, {} updated, {} written, {} errors",
+                counts.total(),
+                counts.matched,
+                counts.updated,
+                counts.written,
+                counts.errors
+            )?;
+        } else {
+            writeln!(
+                writer,
+                "{} snapshots, {} matched, {} mismatched, {} missing, {} errors",
+                counts.total(),
+                counts.matched,
+                counts.mismatched,
+                counts.missing,
+                counts.errors
+            )?;
+        }
+        Ok(counts.exit_code())
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(self.dir).unwrap_or(path);
+        format!("{}", relative.display())
+    }
+
+    fn check(
+        &self,
+        rules: &SelectedRules<'_>,
+        rules_file_name: &str,
+        data_file: &Path,
+        counts: &mut Counts,
+        writer: &mut Writer,
+    ) -> Result<()> {
+        let data_file_name = self.relative(data_file);
+        let snapshot_file = PathBuf::from(format!("{}.snap", data_file.display()));
+        let snapshot_name = self.relative(&snapshot_file);
+        let actual = match render(rules, rules_file_name, &data_file_name, data_file) {
+            Ok(actual) => actual,
+            Err(e) => {
+                writer.write_err(format!(
+                    "Unable to evaluate {data_file_name} with {rules_file_name}, {e}"
+                ))?;
+                counts.errors += 1;
+                return Ok(());
+            }
+        };
+
+        match std::fs::read_to_string(&snapshot_file).ok() {
+            Some(expected) if expected == actual => counts.matched += 1,
+            Some(_) if self.update => {
+                std::fs::write(&snapshot_file, actual)?;
+                writeln!(writer, "Updated {snapshot_name}")?;
+                counts.updated += 1;
+            }
+            None if self.update => {
+                std::fs::write(&snapshot_file, actual)?;
+                writeln!(writer, "Written {snapshot_name}")?;
+                counts.written += 1;
+            }
+            Some(expected) => {
+                writeln!(writer, "{} {snapshot_name}", "Mismatch".red().bold())?;
+                writeln!(writer, "--- {snapshot_name}")?;
+                writeln!(writer, "+++ {snapshot_name} (actual)")?;
+                for line in unified_diff(&expected, &actual) {
+                    match line.chars().next() {
+                        Some('-') => writeln!(writer, "{}", line.red())?,
+                        Some('+') => writeln!(writer, "{}", line.green())?,
+                        Some('@') => writeln!(writer, "{}", line.cyan())?,
+                        _ => writeln!(writer, "{line}")?,
+                    }
+                }
+                counts.mismatched += 1;
+            }
+            None => {
+                writeln!(
+                    writer,
+                    "{} {snapshot_name}, run with --update-snapshots to write it",
+                    "Missing".red().bold()
+                )?;
+                counts.missing += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn render(
+    rules: &SelectedRules<'_>,
+    rules_file_name: &str,
+    data_file_name: &str,
+    data_file: &Path,
+) -> Result<String> {
+    let content = std::fs::read_to_string(data_file)?;
+    let mut snapshot = String::new();
+    for (name, output) in OUTPUTS.iter() {
+        let mut writer = Writer::new(WriteBuffer::Vec(vec![]), WriteBuffer::Vec(vec![]));
+        report_pair(
+            rules,
+            rules_file_name,
+            data_file_name,
+            content.clone(),
+            *output,
+            &mut writer,
+        )?;
+        let rendered = writer
+            .stripped()
+            .map_err(|e| Error::ParseError(e.to_string()))?;
+        snapshot.push_str(&format!("--- {name}\n{rendered}"));
+    }
+    Ok(snapshot)
+}
+
+//
+// Myers' diff in linear space. Lines in common at either end are kept as they are, the rest is
+// split at the middle snake of a shortest edit script and each side is diffed on its own
+//
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str], edits: &mut Vec<(char, &'a str)>) {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    edits.extend(old[..prefix].iter().map(|line| (' ', *line)));
+    let (old_middle, new_middle) = (&old[prefix..old_end], &new[prefix..new_end]);
+    if old_middle.is_empty() || new_middle.is_empty() {
+        edits.extend(old_middle.iter().map(|line| ('-', *line)));
+        edits.extend(new_middle.iter().map(|line| ('+', *line)));
+    } else {
+        let (x, y, u, v) = middle_snake(old_middle, new_middle);
+        diff_lines(&old_middle[..x], &new_middle[..y], edits);
+        edits.extend(old_middle[x..u].iter().map(|line| (' ', *line)));
+        diff_lines(&old_middle[u..], &new_middle[v..], edits);
+    }
+    edits.extend(old[old_end..].iter().map(|line| (' ', *line)));
+}
+
+//
+// Start and end of the middle snake, found by searching from both ends at once and keeping only
+// the furthest point reached on each diagonal
+//
+fn middle_snake(old: &[&str], new: &[&str]) -> (usize, usize, usize, usize) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m + 1) / 2;
+    let delta = n - m;
+    let offset = max + 1;
+    let mut forward = vec![0isize; (2 * offset + 1) as usize];
+    let mut backward = vec![0isize; (2 * offset + 1) as usize];
+    let at = |k: isize| (k + offset) as usize;
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            if delta % 2 != 0
+                && (delta - k).abs() < d
+                && forward[at(k)] + backward[at(delta - k)] >= n
+            {
+                return (start_x as usize, start_y as usize, x as usize, y as usize);
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            if delta % 2 == 0
+                && (delta - k).abs() <= d
+                && backward[at(k)] + forward[at(delta - k)] >= n
+            {
+                return (
+                    (n - x) as usize,
+                    (m - y) as usize,
+                    (n - start_x) as usize,
+                    (m - start_y) as usize,
+                );
+            }
+        }
+    }
+    unreachable!("the forward and backward searches meet within (n + m + 1) / 2 steps")
+}
+
+//
+// Hunks of a unified diff from expected to actual, with the lines of each hunk prefixed by ' ',
+// '-' or '+'. The lines in common are a longest common subsequence of the two, removed lines are
+// listed before the added lines they sit next to
+//
+fn unified_diff(expected: &str, actual: &str) -> Vec<String> {
+    let old = expected.lines().collect::<Vec<_>>();
+    let new = actual.lines().collect::<Vec<_>>();
+    let mut edits = Vec::with_capacity(old.len() + new.len());
+    diff_lines(&old, &new, &mut edits);
+    for changes in edits.split_mut(|(kind, _)| *kind == ' ') {
+        changes.sort_by_key(|(kind, _)| *kind != '-');
+    }
+
+    let changed = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, (kind, _))| *kind != ' ')
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut hunks = Vec::new();
+    let mut index = 0;
+    while index < changed.len() {
+        let mut last = index;
+        while last + 1 < changed.len() && changed[last + 1] - changed[last] <= 2 * DIFF_CONTEXT {
+            last += 1;
+        }
+        let start = changed[index].saturating_sub(DIFF_CONTEXT);
+        let end = (changed[last] + DIFF_CONTEXT + 1).min(edits.len());
+        let count = |range: &[(char, &str)], kind: char| {
+            range
+                .iter()
+                .filter(|(each, _)| *each == ' ' || *each == kind)
+                .count()
+        };
+        let (old_before, new_before) = (count(&edits[..start], '-'), count(&edits[..start], '+'));
+        let (old_count, new_count) = (
+            count(&edits[start..end], '-'),
+            count(&edits[start..end], '+'),
+        );
+        hunks.push(format!(
+            "@@ -{},{old_count} +{},{new_count} @@",
+            old_before + usize::from(old_count > 0),
+            new_before + usize::from(new_count > 0),
+        ));
+        hunks.extend(
+            edits[start..end]
+                .iter()
+                .map(|(kind, line)| format!("{kind}{line}")),
+        );
+        index = last + 1;
+    }
+    hunks
+}
+
+#[cfg(test)]
+#[path = "snapshot_tests.rs"]
+mod snapshot_tests;
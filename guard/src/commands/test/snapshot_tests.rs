@@ -0,0 +1,101 @@
+use super::*;
+
+#[test]
+fn test_unified_diff_hunks() {
+    let expected = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+    let actual = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+    assert_eq!(
+        unified_diff(expected, actual),
+        vec![
+            "@@ -1,5 +1,5 @@",
+            " a",
+            "-b",
+            "+B",
+            " c",
+            " d",
+            " e",
+            "@@ -11,3 +11,4 @@",
+            " k",
+            " l",
+            " m",
+            "+n",
+        ]
+    );
+}
+
+#[test]
+fn test_unified_diff_merges_close_changes() {
+    let expected = "a\nb\nc\nd\ne\n";
+    let actual = "b\nc\nd\nE\n";
+    assert_eq!(
+        unified_diff(expected, actual),
+        vec!["@@ -1,5 +1,4 @@", "-a", " b", " c", " d", "-e", "+E"]
+    );
+    assert!(unified_diff(expected, expected).is_empty());
+}
+
+#[test]
+fn test_unified_diff_keeps_longest_common_lines() {
+    let common = |old: &[&str], new: &[&str]| {
+        let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lengths[i][j] = if old[i] == new[j] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        lengths[0][0]
+    };
+    let cases = [
+        ("a b c a b b a", "c b a b a c"),
+        ("a b c d e f", "f e d c b a"),
+        ("x a y b z c", "a b c"),
+        ("a a a b", "b a a a"),
+        ("", "a b"),
+        ("a b", ""),
+    ];
+    for (old, new) in cases.iter() {
+        let old = old.split_whitespace().collect::<Vec<_>>();
+        let new = new.split_whitespace().collect::<Vec<_>>();
+        let mut edits = vec![];
+        diff_lines(&old, &new, &mut edits);
+        let side = |kind: char| {
+            edits
+                .iter()
+                .filter(|(each, _)| *each == ' ' || *each == kind)
+                .map(|(_, line)| *line)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(side('-'), old);
+        assert_eq!(side('+'), new);
+        assert_eq!(
+            edits.iter().filter(|(kind, _)| *kind == ' ').count(),
+            common(&old, &new)
+        );
+    }
+}
+
+#[test]
+fn test_unified_diff_of_large_snapshots() {
+    let expected = (0..100_000)
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    let actual = expected.replace("50000\n", "fifty thousand\n");
+    assert_eq!(
+        unified_diff(&expected, &actual),
+        vec![
+            "@@ -49998,7 +49998,7 @@",
+            " 49997",
+            " 49998",
+            " 49999",
+            "-50000",
+            "+fifty thousand",
+            " 50001",
+            " 50002",
+            " 50003",
+        ]
+    );
+}
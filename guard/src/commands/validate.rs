@@ -3,50 +3,81 @@ use colored::*;
 use enumflags2::BitFlags;
 use serde::Deserialize;
 use std::cmp;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use Type::CFNTemplate;
 
 use crate::command::Command;
 use crate::commands::aws_meta_appender::MetadataAppender;
-use crate::commands::files::{alpabetical, iterate_over, last_modified};
+use crate::commands::files::{
+    alpabetical, iterate_over, last_modified, regular_ordering, FileOrdering, FileSelector,
+};
+use crate::commands::rules_cache::{parse_rules, RulesCache};
 use crate::commands::tracker::{StackTracker, StatusContext};
+use crate::commands::validate::baseline::{Baseline, BaselineRun};
+use crate::commands::validate::common::fold_status;
+use crate::commands::validate::config::ValidateConfig;
+use crate::commands::validate::data_file_error::{write_data_file_errors, DataFileError};
+use crate::commands::validate::junit::JunitReport;
+use crate::commands::validate::markup::MarkupReport;
+use crate::commands::validate::rule_selection::{RuleSelection, SelectedRules};
+use crate::commands::validate::run_summary::RunSummary;
+use crate::commands::validate::sarif::SarifLog;
 use crate::commands::validate::summary_table::SummaryType;
+use crate::commands::validate::suppression::InlineSuppressions;
 use crate::commands::validate::tf::TfAware;
+use crate::commands::validate::watch::ValidateWatch;
+use crate::commands::watch::Watcher;
 use crate::commands::{
-    ALPHABETICAL, DATA, DATA_FILE_SUPPORTED_EXTENSIONS, INPUT_PARAMETERS, LAST_MODIFIED,
-    OUTPUT_FORMAT, PAYLOAD, PREVIOUS_ENGINE, PRINT_JSON, REQUIRED_FLAGS, RULES,
-    RULE_FILE_SUPPORTED_EXTENSIONS, SHOW_CLAUSE_FAILURES, SHOW_SUMMARY, TYPE, VALIDATE, VERBOSE,
+    ALL_SKIPPED_EXIT_CODE, ALPHABETICAL, BASELINE, CACHE_DIR, CONFIG, DATA, DATA_EXTENSIONS,
+    DATA_FILE_PARSE_ERROR_EXIT_CODE, DATA_FILE_SUPPORTED_EXTENSIONS, DETAILED_EXIT_CODES,
+    DISALLOW_SUPPRESSIONS, EXCLUDE, EXCLUDE_RULE, FAIL_ON_SKIP, INPUT_PARAMETERS, JOBS,
+    LAST_MODIFIED, NO_CONFIG, OUTPUT_FORMAT, PAYLOAD, PREVIOUS_ENGINE, PRINT_JSON, REQUIRED_FLAGS,
+    RULES, RULES_EXTENSIONS, RULES_FAILED_EXIT_CODE, RULES_FILE_PARSE_ERROR_EXIT_CODE,
+    RULE_FILE_SUPPORTED_EXTENSIONS, RULE_NAME, RUN_SUMMARY, SHOW_CLAUSE_FAILURES, SHOW_SUMMARY,
+    SUCCESS_EXIT_CODE, TAGS, TYPE, VALIDATE, VERBOSE, WATCH, WRITE_BASELINE,
 };
 use crate::rules::errors::Error;
 use crate::rules::eval::eval_rules_file;
 use crate::rules::eval_context::{root_scope, simplifed_json_from_root, EventRecord};
 use crate::rules::evaluate::RootScope;
-use crate::rules::exprs::RulesFile;
 use crate::rules::path_value::traversal::Traversal;
 use crate::rules::path_value::PathAwareValue;
 use crate::rules::values::CmpOperator;
 use crate::rules::{Evaluate, EvaluationContext, EvaluationType, Result, Status};
-use crate::utils::writer::Writer;
+use crate::utils::writer::{WriteBuffer, Writer};
 
+mod baseline;
 mod cfn;
 mod cfn_reporter;
-mod common;
+pub(crate) mod common;
+mod config;
 mod console_reporter;
+mod data_file_error;
 pub(crate) mod generic_summary;
+pub(crate) mod junit;
+mod markup;
+pub(crate) mod rule_selection;
+mod run_summary;
+mod sarif;
 mod summary_table;
+mod suppression;
 mod tf;
+mod watch;
 
 #[derive(Eq, Clone, Debug, PartialEq)]
 pub(crate) struct DataFile {
     content: String,
     path_value: PathAwareValue,
     name: String,
+    path: String,
 }
 
 #[derive(Copy, Eq, Clone, Debug, PartialEq)]
@@ -128,28 +159,52 @@ rules and data files. The directory being pointed to must contain only data file
 or rules files.
 "#)
             .arg(Arg::with_name(RULES.0).long(RULES.0).short(RULES.1).takes_value(true)
-                .help("Provide a rules file or a directory of rules files. Supports passing multiple values by using this option repeatedly.\
-                          \nExample:\n --rules rule1.guard --rules ./rules-dir1 --rules rule2.guard\
-                          \nFor directory arguments such as `rules-dir1` above, scanning is only supported for files with following extensions: .guard, .ruleset")
+                .help("Provide a rules file, a directory of rules files or a glob pattern. Supports passing multiple values by using this option repeatedly.\
+                          \nExample:\n --rules rule1.guard --rules ./rules-dir1 --rules 'rules/**/s3-*.guard'\
+                          \nFor directory arguments such as `rules-dir1` above, scanning is only supported for files with the --rules-extensions extensions")
                 .multiple(true).conflicts_with("payload"))
             .arg(Arg::with_name(DATA.0).long(DATA.0).short(DATA.1).takes_value(true)
-                .help("Provide a data file or directory of data files in JSON or YAML. Supports passing multiple values by using this option repeatedly.\
-                          \nExample:\n --data template1.yaml --data ./data-dir1 --data template2.yaml\
-                          \nFor directory arguments such as `data-dir1` above, scanning is only supported for files with following extensions: .yaml, .yml, .json, .jsn, .template")
+                .help("Provide a data file, a directory of data files in JSON or YAML or a glob pattern. Supports passing multiple values by using this option repeatedly.\
+                          \nExample:\n --data template1.yaml --data ./data-dir1 --data 'stacks/**/*.template.json'\
+                          \nFor directory arguments such as `data-dir1` above, scanning is only supported for files with the --data-extensions extensions")
                 .multiple(true).conflicts_with("payload"))
             .arg(Arg::with_name(INPUT_PARAMETERS.0).long(INPUT_PARAMETERS.0).short(INPUT_PARAMETERS.1).takes_value(true)
                 .help("Provide a data file or directory of data files in JSON or YAML that specifies any additional parameters to use along with data files to be used as a combined context. \
                            All the parameter files passed as input get merged and this combined context is again merged with each file passed as an argument for `data`. Due to this, every file is \
                            expected to contain mutually exclusive properties, without any overlap. Supports passing multiple values by using this option repeatedly.\
-                          \nExample:\n --input-parameters param1.yaml --input-parameters ./param-dir1 --input-parameters param2.yaml\
-                          \nFor directory arguments such as `param-dir1` above, scanning is only supported for files with following extensions: .yaml, .yml, .json, .jsn, .template")
+                          \nExample:\n --input-parameters param1.yaml --input-parameters ./param-dir1 --input-parameters 'params/*.yaml'\
+                          \nFor directory arguments such as `param-dir1` above, scanning is only supported for files with the --data-extensions extensions")
+                .multiple(true))
+            .arg(Arg::with_name(EXCLUDE.0).long(EXCLUDE.0).short(EXCLUDE.1).takes_value(true).multiple_occurrences(true)
+                .help("Leave out files and directories matching these glob patterns when scanning --rules, --data and --input-parameters. \
+                       Patterns are matched against the path of each file, its path relative to the argument it was found under and its file name.\
+                       \nExample:\n --data stacks --exclude 'stacks/sandbox/**' --exclude '*.bak.json'"))
+            .arg(Arg::with_name(DATA_EXTENSIONS.0).long(DATA_EXTENSIONS.0).short(DATA_EXTENSIONS.1).takes_value(true).use_delimiter(true).multiple(true)
+                .default_values(&DATA_FILE_SUPPORTED_EXTENSIONS)
+                .help("Extensions of the data files selected when scanning directories for --data and --input-parameters"))
+            .arg(Arg::with_name(RULES_EXTENSIONS.0).long(RULES_EXTENSIONS.0).short(RULES_EXTENSIONS.1).takes_value(true).use_delimiter(true).multiple(true)
+                .default_values(&RULE_FILE_SUPPORTED_EXTENSIONS)
+                .help("Extensions of the rules files selected when scanning directories for --rules")
                 .multiple(true))
+            .arg(Arg::with_name(RULE_NAME.0).long(RULE_NAME.0).short(RULE_NAME.1).takes_value(true).multiple_occurrences(true)
+                .help("Only evaluate and report the rules whose names match one of these glob patterns, for example s3_*. \
+                       Rules that a selected rule depends on are still evaluated, but not reported. \
+                       The selected rules of each rules file are listed with --verbose"))
+            .arg(Arg::with_name(EXCLUDE_RULE.0).long(EXCLUDE_RULE.0).short(EXCLUDE_RULE.1).takes_value(true).multiple_occurrences(true)
+                .help("Do not evaluate or report the rules whose names match one of these glob patterns, for example legacy_*"))
+            .arg(Arg::with_name(TAGS.0).long(TAGS.0).short(TAGS.1).takes_value(true).use_delimiter(true).multiple(true)
+                .help("Only evaluate and report the rules with one of these tags. Tags are declared in a comment right above the rule, for example\
+                       \n# tags: pci, s3\nrule s3_bucket_encrypted { ... }"))
             .arg(Arg::with_name(TYPE.0).long(TYPE.0).short(TYPE.1).takes_value(true).possible_values(&["CFNTemplate"])
                 .help("Specify the type of data file used for improved messaging"))
             .arg(Arg::with_name(OUTPUT_FORMAT.0).long(OUTPUT_FORMAT.0).short(OUTPUT_FORMAT.1).takes_value(true)
-                .possible_values(&["json", "yaml", "single-line-summary"])
+                .possible_values(&["json", "yaml", "single-line-summary", "sarif", "junit", "markdown", "html"])
                 .default_value("single-line-summary")
-                .help("Specify the format in which the output should be displayed"))
+                .help("Specify the format in which the output should be displayed. \
+                       sarif writes a single SARIF 2.1.0 log for the whole run, with one result for each failing clause. \
+                       junit writes a single JUnit XML report, with a test suite for each rules file and a test case for each rule and data file. \
+                       markdown and html write a single report for the whole run, with a summary table and the failing clauses \
+                       grouped by resource for CloudFormation templates and Terraform plans"))
             .arg(Arg::with_name(PREVIOUS_ENGINE.0).long(PREVIOUS_ENGINE.0).short(PREVIOUS_ENGINE.1).takes_value(false)
                 .help("Uses the old engine for evaluation. This parameter will allow customers to evaluate old changes before migrating"))
             .arg(Arg::with_name(SHOW_SUMMARY.0).long(SHOW_SUMMARY.0).short(SHOW_SUMMARY.1).takes_value(true).use_delimiter(true).multiple(true)
@@ -165,60 +220,156 @@ or rules files.
                 .help("Verbose logging"))
             .arg(Arg::with_name(PRINT_JSON.0).long(PRINT_JSON.0).short(PRINT_JSON.1).required(false)
                 .help("Print output in json format"))
+            .arg(Arg::with_name(JOBS.0).long(JOBS.0).short(JOBS.1).takes_value(true)
+                .validator(|jobs| match jobs.parse::<usize>() {
+                    Ok(count) if count > 0 => Ok(()),
+                    _ => Err(format!("expected a positive number of jobs, got {jobs}")),
+                })
+                .help("Evaluate (rules file, data file) pairs in parallel using the given number of threads. \
+                       Output is reported in the same order as a sequential run. Defaults to 1 (sequential evaluation)"))
+            .arg(Arg::with_name(CACHE_DIR.0).long(CACHE_DIR.0).short(CACHE_DIR.1).takes_value(true)
+                .help("Cache parsed rules files in the given directory and reuse them on later runs. \
+                       Entries are keyed by the rules file content and the Guard version, changed files are parsed again. \
+                       The oldest entries are removed once the directory holds more than 1000"))
+            .arg(Arg::with_name(RUN_SUMMARY.0).long(RUN_SUMMARY.0).short(RUN_SUMMARY.1).required(false)
+                .conflicts_with(PREVIOUS_ENGINE.0)
+                .help("Print a summary of the whole run once all data files were evaluated, with PASS/FAIL/SKIP counts \
+                       for each rule, the data files with the most failed rules and the rules that were always SKIP. \
+                       Written as a final document for json and yaml output, not supported with sarif, junit, markdown and html"))
+            .arg(Arg::with_name(BASELINE.0).long(BASELINE.0).short(BASELINE.1).takes_value(true)
+                .conflicts_with(PREVIOUS_ENGINE.0)
+                .help("Provide a baseline file in JSON or YAML listing accepted violations, each with a rules_file, rule, data_file and an optional \
+                       resource or JSON pointer path. Rules and data files are named by their path relative to the --rules or --data they were selected with. Failed rules for which every failure is listed are suppressed and do not affect the exit code. \
+                       Suppressed violations and baseline entries that no longer match any failure are written as a final document for json and yaml output, \
+                       stale entries are also reported on stderr"))
+            .arg(Arg::with_name(WRITE_BASELINE.0).long(WRITE_BASELINE.0).short(WRITE_BASELINE.1).takes_value(true)
+                .conflicts_with(PREVIOUS_ENGINE.0)
+                .help("Write every violation of this run to the given baseline file, as JSON for .json files and YAML otherwise"))
+            .arg(Arg::with_name(DISALLOW_SUPPRESSIONS.0).long(DISALLOW_SUPPRESSIONS.0).short(DISALLOW_SUPPRESSIONS.1).required(false)
+                .help("Ignore rules suppressed by resources, through guard.SuppressedRules in the Metadata of CloudFormation resources \
+                       or the guard:SuppressedRules tag of Terraform resources, and report every suppression that was ignored on stderr. \
+                       By default failures of suppressed rules on the resource are left out and listed after the reports. \
+                       Rules that fail only because a suppressed rule failed, through a named rule clause, pass once the suppressed rule passes. \
+                       Failures that point at no value, such as an empty check whose query selects nothing, and rules skipped by a when \
+                       condition on a suppressed rule are not suppressed"))
+            .arg(Arg::with_name(DETAILED_EXIT_CODES.0).long(DETAILED_EXIT_CODES.0).short(DETAILED_EXIT_CODES.1).required(false)
+                .help("Use a distinct exit code for each outcome of the run, 0 when no rule failed, 5 when a rule failed, \
+                       6 when a rules file could not be parsed, 7 when a data file could not be parsed and 8 when every rule was skipped with --fail-on-skip. \
+                       Without it failed rules and rules or data files that could not be parsed all exit with 5"))
+            .arg(Arg::with_name(FAIL_ON_SKIP.0).long(FAIL_ON_SKIP.0).short(FAIL_ON_SKIP.1).required(false)
+                .help("Fail the run when every rule was skipped for every data file, with exit code 5, or 8 with --detailed-exit-codes"))
             .arg(Arg::with_name(PAYLOAD.0).long(PAYLOAD.0).short(PAYLOAD.1)
                 .help("Provide rules and data in the following JSON format via STDIN,\n{\"rules\":[\"<rules 1>\", \"<rules 2>\", ...], \"data\":[\"<data 1>\", \"<data 2>\", ...]}, where,\n- \"rules\" takes a list of string \
                 version of rules files as its value and\n- \"data\" takes a list of string version of data files as it value.\nWhen --payload is specified --rules and --data cannot be specified."))
+            .arg(Arg::with_name(WATCH.0).long(WATCH.0).short(WATCH.1).required(false)
+                .requires(DATA.0)
+                .conflicts_with_all(&[PAYLOAD.0, OUTPUT_FORMAT.0, PRINT_JSON.0, PREVIOUS_ENGINE.0, RUN_SUMMARY.0, BASELINE.0, WRITE_BASELINE.0])
+                .help("Keep running and poll the rules, data and input parameters for changes, re-evaluating only the (rules file, data file) pairs \
+                       affected by a change and printing the rules whose status changed since the previous run. Failed rules are listed on start"))
+            .arg(Arg::with_name(CONFIG.0).long(CONFIG.0).short(CONFIG.1).takes_value(true)
+                .help("Provide a configuration file in TOML with a [validate] table, whose keys are the long names of the options of this command, \
+                       for example rules = [\"rules-dir\"] or output-format = \"json\". Relative paths are resolved against the directory of the file. \
+                       Defaults to guard.toml or .guardrc in the working directory when present. Options given on the command line take precedence, \
+                       except that a flag set to true in the file cannot be turned off on the command line, use --no-config to leave the file out"))
+            .arg(Arg::with_name(NO_CONFIG.0).long(NO_CONFIG.0).short(NO_CONFIG.1).required(false)
+                .conflicts_with(CONFIG.0)
+                .help("Do not read a configuration file, not even guard.toml or .guardrc in the working directory"))
             .group(ArgGroup::with_name(REQUIRED_FLAGS)
-                .args(&[RULES.0, PAYLOAD.0])
-                .required(true))
+                .args(&[RULES.0, PAYLOAD.0]))
     }
 
     fn execute(&self, app: &ArgMatches, writer: &mut Writer) -> Result<i32> {
-        let cmp = if app.is_present(LAST_MODIFIED.0) {
+        let merged;
+        let config = if app.is_present(NO_CONFIG.0) {
+            None
+        } else {
+            ValidateConfig::load(app.value_of(CONFIG.0))?
+        };
+        let app = match config {
+            Some(config) => {
+                merged = config.merge(self.command(), app)?;
+                &merged
+            }
+            None => app,
+        };
+        if !app.is_present(RULES.0) && !app.is_present(PAYLOAD.0) {
+            writer.write_err(format!(
+                "One of --{} or --{} is required, on the command line or in the configuration file",
+                RULES.0, PAYLOAD.0
+            ))?;
+            return Ok(1);
+        }
+
+        let exit_codes = ExitCodes {
+            detailed: app.is_present(DETAILED_EXIT_CODES.0),
+            fail_on_skip: app.is_present(FAIL_ON_SKIP.0),
+        };
+        let cmp: FileOrdering = if app.is_present(LAST_MODIFIED.0) {
             last_modified
         } else {
             alpabetical
         };
 
-        let empty_path = Path::new("");
+        let excludes = app.values_of(EXCLUDE.0).into_iter().flatten();
+        let data_selector =
+            FileSelector::new(app.values_of(DATA_EXTENSIONS.0).unwrap(), excludes.clone())?;
+        let rules_selector =
+            FileSelector::new(app.values_of(RULES_EXTENSIONS.0).unwrap(), excludes)?;
+
+        let rule_selection = RuleSelection::new(
+            app.values_of(RULE_NAME.0).into_iter().flatten(),
+            app.values_of(EXCLUDE_RULE.0).into_iter().flatten(),
+            app.values_of(TAGS.0).into_iter().flatten(),
+        )?;
+
+        if app.is_present(WATCH.0) {
+            let watched = ValidateWatch {
+                rules: app.values_of(RULES.0).into_iter().flatten().collect(),
+                data: app.values_of(DATA.0).into_iter().flatten().collect(),
+                input_parameters: app
+                    .values_of(INPUT_PARAMETERS.0)
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                rules_selector,
+                data_selector,
+                rule_selection,
+                cmp,
+                rules_cache: app.value_of(CACHE_DIR.0).map(RulesCache::new).transpose()?,
+                disallow_suppressions: app.is_present(DISALLOW_SUPPRESSIONS.0),
+            };
+            return Watcher::new(&watched).run(writer);
+        }
+
+        let mut data_file_errors: Vec<DataFileError> = Vec::new();
         let mut streams: Vec<DataFile> = Vec::new();
         let data_files: Vec<DataFile> = match app.values_of(DATA.0) {
             Some(list_of_file_or_dir) => {
                 for file_or_dir in list_of_file_or_dir {
-                    validate_path(file_or_dir)?;
-                    let base = PathBuf::from_str(file_or_dir)?;
-                    for file in walkdir::WalkDir::new(base.clone()).into_iter().flatten() {
-                        if file.path().is_file() {
-                            let name = file
-                                .file_name()
-                                .to_str()
-                                .map_or("".to_string(), String::from);
-                            if has_a_supported_extension(&name, &DATA_FILE_SUPPORTED_EXTENSIONS) {
-                                let mut content = String::new();
-                                let mut reader = BufReader::new(File::open(file.path())?);
-                                reader.read_to_string(&mut content)?;
-                                let path = file.path();
-                                let relative = match path.strip_prefix(base.as_path()) {
-                                    Ok(p) => {
-                                        if p != empty_path {
-                                            format!("{}", p.display())
-                                        } else {
-                                            path.file_name().unwrap().to_str().unwrap().to_string()
-                                        }
-                                    }
-                                    Err(_) => format!("{}", path.display()),
-                                };
-                                let path_value = match get_path_aware_value_from_data(&content) {
-                                    Ok(t) => t,
-                                    Err(e) => return Err(e),
-                                };
-                                streams.push(DataFile {
-                                    name: relative,
-                                    path_value,
-                                    content,
-                                });
+                    let base = FileSelector::base(file_or_dir);
+                    validate_path(base)?;
+                    let base = PathBuf::from_str(base)?;
+                    for file in data_selector.select(file_or_dir, regular_ordering)? {
+                        let mut content = String::new();
+                        let mut reader = BufReader::new(File::open(&file)?);
+                        reader.read_to_string(&mut content)?;
+                        let relative = relative_name(&file, &base);
+                        let path = format!("{}", file.display());
+                        let path_value = match get_path_aware_value_from_data(&content) {
+                            Ok(t) => t,
+                            Err(e) => {
+                                let error = DataFileError::new(&relative, &path, &content, e);
+                                writer.write_err(format!("{error}\n---"))?;
+                                data_file_errors.push(error);
+                                continue;
                             }
-                        }
+                        };
+                        streams.push(DataFile {
+                            name: relative,
+                            path_value,
+                            content,
+                            path,
+                        });
                     }
                 }
                 streams
@@ -228,15 +379,19 @@ or rules files.
                     let mut content = String::new();
                     let mut reader = BufReader::new(std::io::stdin());
                     reader.read_to_string(&mut content)?;
-                    let path_value = match get_path_aware_value_from_data(&content) {
-                        Ok(t) => t,
-                        Err(e) => return Err(e),
-                    };
-                    streams.push(DataFile {
-                        name: "STDIN".to_string(),
-                        path_value,
-                        content,
-                    });
+                    match get_path_aware_value_from_data(&content) {
+                        Ok(path_value) => streams.push(DataFile {
+                            name: "STDIN".to_string(),
+                            path_value,
+                            content,
+                            path: "STDIN".to_string(),
+                        }),
+                        Err(e) => {
+                            let error = DataFileError::new("STDIN", "STDIN", &content, e);
+                            writer.write_err(format!("{error}\n---"))?;
+                            data_file_errors.push(error);
+                        }
+                    }
                     streams
                 } else {
                     vec![]
@@ -248,28 +403,19 @@ or rules files.
             Some(list_of_file_or_dir) => {
                 let mut primary_path_value: Option<PathAwareValue> = None;
                 for file_or_dir in list_of_file_or_dir {
-                    validate_path(file_or_dir)?;
-                    let base = PathBuf::from_str(file_or_dir)?;
-                    for file in walkdir::WalkDir::new(base.clone()).into_iter().flatten() {
-                        if file.path().is_file() {
-                            let name = file
-                                .file_name()
-                                .to_str()
-                                .map_or("".to_string(), String::from);
-                            if has_a_supported_extension(&name, &DATA_FILE_SUPPORTED_EXTENSIONS) {
-                                let mut content = String::new();
-                                let mut reader = BufReader::new(File::open(file.path())?);
-                                reader.read_to_string(&mut content)?;
-                                let path_value = match get_path_aware_value_from_data(&content) {
-                                    Ok(t) => t,
-                                    Err(e) => return Err(e),
-                                };
-                                primary_path_value = match primary_path_value {
-                                    Some(current) => Some(current.merge(path_value)?),
-                                    None => Some(path_value),
-                                };
-                            }
-                        }
+                    validate_path(FileSelector::base(file_or_dir))?;
+                    for file in data_selector.select(file_or_dir, regular_ordering)? {
+                        let mut content = String::new();
+                        let mut reader = BufReader::new(File::open(&file)?);
+                        reader.read_to_string(&mut content)?;
+                        let path_value = match get_path_aware_value_from_data(&content) {
+                            Ok(t) => t,
+                            Err(e) => return exit_codes.input_parameters_error(e, writer),
+                        };
+                        primary_path_value = match primary_path_value {
+                            Some(current) => Some(current.merge(path_value)?),
+                            None => Some(path_value),
+                        };
                     }
                 }
                 primary_path_value
@@ -290,17 +436,15 @@ or rules files.
             None => Type::Generic,
         };
 
+        //
+        // sarif, junit, markdown and html are a single document for the whole run, written by the
+        // run log instead of the reporters of each pair
+        //
+        let mut run_log = app.value_of(OUTPUT_FORMAT.0).and_then(RunLog::new);
         let output_type = match app.value_of(OUTPUT_FORMAT.0) {
-            Some(o) => {
-                if o == "single-line-summary" {
-                    OutputFormatType::SingleLineSummary
-                } else if o == "json" {
-                    OutputFormatType::JSON
-                } else {
-                    OutputFormatType::YAML
-                }
-            }
-            None => OutputFormatType::SingleLineSummary,
+            Some("json") => OutputFormatType::JSON,
+            Some("yaml") => OutputFormatType::YAML,
+            _ => OutputFormatType::SingleLineSummary,
         };
 
         let summary_type: BitFlags<SummaryType> =
@@ -324,91 +468,71 @@ or rules files.
         let print_json = app.is_present(PRINT_JSON.0);
         let show_clause_failures = app.is_present(SHOW_CLAUSE_FAILURES.0);
         let new_version_eval_engine = !app.is_present(PREVIOUS_ENGINE.0);
-
-        let mut exit_code = 0;
-        if app.is_present(RULES.0) {
-            let list_of_file_or_dir = app.values_of(RULES.0).unwrap();
+        let jobs = app
+            .value_of(JOBS.0)
+            .map_or(1, |jobs| jobs.parse::<usize>().unwrap());
+        let rules_cache = app.value_of(CACHE_DIR.0).map(RulesCache::new).transpose()?;
+        if run_log.is_some() && !new_version_eval_engine {
+            writer.write_err(format!(
+                "--{} {} is not supported with --{}",
+                OUTPUT_FORMAT.0,
+                app.value_of(OUTPUT_FORMAT.0).unwrap(),
+                PREVIOUS_ENGINE.0
+            ))?;
+            return Ok(1);
+        }
+        let mut run_summary = app.is_present(RUN_SUMMARY.0).then(RunSummary::new);
+        //
+        // The run log is the only document on stdout, the evaluation tree and the json of each
+        // pair would make it invalid
+        //
+        for flag in [RUN_SUMMARY.0, VERBOSE.0, PRINT_JSON.0].iter() {
+            if run_log.is_some() && app.is_present(flag) {
+                writer.write_err(format!(
+                    "--{} is not supported with --{} {}",
+                    flag,
+                    OUTPUT_FORMAT.0,
+                    app.value_of(OUTPUT_FORMAT.0).unwrap()
+                ))?;
+                return Ok(1);
+            }
+        }
+        let baseline = match app.value_of(BASELINE.0) {
+            Some(file) => {
+                validate_path(file)?;
+                Some(Baseline::load(file)?)
+            }
+            None => None,
+        };
+        let write_baseline = app.value_of(WRITE_BASELINE.0);
+        let mut baseline_run =
+            (baseline.is_some() || write_baseline.is_some()).then(BaselineRun::new);
+        let mut suppressions = InlineSuppressions::new(app.is_present(DISALLOW_SUPPRESSIONS.0));
+
+        let mut outcome = RunOutcome {
+            data_file_errors: !data_file_errors.is_empty(),
+            ..Default::default()
+        };
+        let (rules_sources, data_files, extra_data, source_kind) = if app.is_present(RULES.0) {
             let mut rules = Vec::new();
-            for file_or_dir in list_of_file_or_dir {
-                validate_path(file_or_dir)?;
-                let base = PathBuf::from_str(file_or_dir)?;
-                if base.is_file() {
-                    rules.push(base.clone())
-                } else {
-                    for entry in walkdir::WalkDir::new(base.clone())
-                        .sort_by(cmp)
-                        .into_iter()
-                        .flatten()
-                    {
-                        if entry.path().is_file()
-                            && entry
-                                .path()
-                                .file_name()
-                                .and_then(|s| s.to_str())
-                                .map_or(false, |s| {
-                                    has_a_supported_extension(s, &RULE_FILE_SUPPORTED_EXTENSIONS)
-                                })
-                        {
-                            rules.push(entry.path().to_path_buf());
-                        }
-                    }
+            let mut names = HashMap::new();
+            for file_or_dir in app.values_of(RULES.0).unwrap() {
+                let base = PathBuf::from_str(FileSelector::base(file_or_dir))?;
+                for file in rules_files(std::iter::once(file_or_dir), &rules_selector, cmp)? {
+                    names.insert(file.clone(), relative_name(&file, &base));
+                    rules.push(file);
                 }
             }
-            for each_file_content in iterate_over(&rules, |content, file| {
-                Ok((
-                    content,
-                    match file.strip_prefix(file) {
-                        Ok(path) => {
-                            if path == empty_path {
-                                file.file_name().unwrap().to_str().unwrap().to_string()
-                            } else {
-                                format!("{}", path.display())
-                            }
-                        }
-                        Err(_) => format!("{}", file.display()),
-                    },
-                ))
-            }) {
+            let read_rules_file =
+                |content: String, file: &PathBuf| Ok((content, names[file].clone()));
+            let mut rules_sources = Vec::with_capacity(rules.len());
+            for each_file_content in iterate_over(&rules, &read_rules_file) {
                 match each_file_content {
                     Err(e) => writer.write_err(format!("Unable read content from file {e}"))?,
-                    Ok((file_content, rule_file_name)) => {
-                        let span =
-                            crate::rules::parser::Span::new_extra(&file_content, &rule_file_name);
-                        match crate::rules::parser::rules_file(span) {
-                            Err(e) => {
-                                writer.write_err(format!(
-                                    "Parsing error handling rule file = {}, Error = {e}\n---",
-                                    rule_file_name.underline(),
-                                ))?;
-                                exit_code = 5;
-                                continue;
-                            }
-
-                            Ok(rules) => {
-                                match evaluate_against_data_input(
-                                    data_type,
-                                    output_type,
-                                    extra_data.clone(),
-                                    &data_files,
-                                    &rules,
-                                    &rule_file_name,
-                                    verbose,
-                                    print_json,
-                                    show_clause_failures,
-                                    new_version_eval_engine,
-                                    summary_type,
-                                    writer,
-                                )? {
-                                    Status::SKIP | Status::PASS => continue,
-                                    Status::FAIL => {
-                                        exit_code = 5;
-                                    }
-                                }
-                            }
-                        }
-                    }
+                    Ok(source) => rules_sources.push(source),
                 }
             }
+            (rules_sources, data_files, extra_data, "rule file")
         } else {
             let mut context = String::new();
             let mut reader = BufReader::new(std::io::stdin());
@@ -417,14 +541,22 @@ or rules files.
             let mut data_collection: Vec<DataFile> = Vec::new();
             for (i, data) in payload.list_of_data.iter().enumerate() {
                 let content = data.to_string();
+                let name = format!("DATA_STDIN[{}]", i + 1);
                 let path_value = match get_path_aware_value_from_data(&content) {
                     Ok(t) => t,
-                    Err(e) => return Err(e),
+                    Err(e) => {
+                        let error = DataFileError::new(&name, &name, &content, e);
+                        writer.write_err(format!("{error}\n---"))?;
+                        data_file_errors.push(error);
+                        outcome.data_file_errors = true;
+                        continue;
+                    }
                 };
                 data_collection.push(DataFile {
-                    name: format!("DATA_STDIN[{}]", i + 1),
+                    name: name.clone(),
                     path_value,
                     content,
+                    path: name,
                 });
             }
             let rules_collection: Vec<(String, String)> = payload
@@ -433,43 +565,273 @@ or rules files.
                 .enumerate()
                 .map(|(i, rules)| (rules.to_string(), format!("RULES_STDIN[{}]", i + 1)))
                 .collect();
+            (rules_collection, data_collection, None, "rules")
+        };
 
-            for (each_rules, location) in rules_collection {
-                match parse_rules(&each_rules, &location) {
-                    Err(e) => {
-                        writer.write_err(format!(
-                            "Parsing error handling rules  = {}, Error = {e}\n---",
-                            location.underline(),
-                        ))?;
-                        exit_code = 5;
-                        continue;
-                    }
+        let parsed_rules = parse_rules_sources(
+            &rules_sources,
+            source_kind,
+            &rule_selection,
+            rules_cache.as_ref(),
+            verbose,
+            &mut outcome,
+            writer,
+        )?;
+        outcome.add(evaluate_pairs(
+            jobs,
+            data_type,
+            output_type,
+            extra_data,
+            &data_files,
+            &parsed_rules,
+            verbose,
+            print_json,
+            show_clause_failures,
+            new_version_eval_engine,
+            summary_type,
+            &mut run_log,
+            &mut run_summary,
+            baseline.as_ref(),
+            &mut baseline_run,
+            &mut suppressions,
+            writer,
+        )?);
+        write_run_reports(
+            &mut run_log,
+            &run_summary,
+            baseline.as_ref(),
+            &baseline_run,
+            write_baseline,
+            &suppressions,
+            &data_file_errors,
+            output_type,
+            writer,
+        )?;
+        Ok(exit_codes.of(&outcome))
+    }
+}
 
-                    Ok(rules) => {
-                        match evaluate_against_data_input(
-                            data_type,
-                            output_type,
-                            None,
-                            &data_collection,
-                            &rules,
-                            &location,
-                            verbose,
-                            print_json,
-                            show_clause_failures,
-                            new_version_eval_engine,
-                            summary_type,
-                            writer,
-                        )? {
-                            Status::SKIP | Status::PASS => continue,
-                            Status::FAIL => {
-                                exit_code = 5;
-                            }
-                        }
-                    }
-                }
+#[derive(Debug, Clone, Copy)]
+struct ExitCodes {
+    detailed: bool,
+    fail_on_skip: bool,
+}
+
+#[derive(Debug, Default)]
+struct RunOutcome {
+    rules_file_errors: bool,
+    data_file_errors: bool,
+    status: Option<Status>,
+}
+
+impl RunOutcome {
+    fn add(&mut self, status: Status) {
+        self.status = Some(match self.status {
+            Some(folded) => fold_status(folded, status),
+            None => status,
+        });
+    }
+}
+
+impl ExitCodes {
+    //
+    // Rules files that could not be parsed take precedence over data files that could not be
+    // parsed, both over failed rules. A run without any evaluated rule counts as skipped
+    //
+    fn of(&self, outcome: &RunOutcome) -> i32 {
+        if outcome.rules_file_errors {
+            return match self.detailed {
+                true => RULES_FILE_PARSE_ERROR_EXIT_CODE,
+                false => RULES_FAILED_EXIT_CODE,
+            };
+        }
+        if outcome.data_file_errors {
+            return match self.detailed {
+                true => DATA_FILE_PARSE_ERROR_EXIT_CODE,
+                false => RULES_FAILED_EXIT_CODE,
+            };
+        }
+        match outcome.status {
+            Some(Status::FAIL) => RULES_FAILED_EXIT_CODE,
+            Some(Status::SKIP) | None if self.fail_on_skip => match self.detailed {
+                true => ALL_SKIPPED_EXIT_CODE,
+                false => RULES_FAILED_EXIT_CODE,
+            },
+            _ => SUCCESS_EXIT_CODE,
+        }
+    }
+
+    //
+    // Input parameters are merged into every data file, the run stops when one of them could
+    // not be parsed
+    //
+    fn input_parameters_error(&self, error: Error, writer: &mut Writer) -> Result<i32> {
+        if !self.detailed {
+            return Err(error);
+        }
+        writer.write_err(format!("Error occurred {error}"))?;
+        Ok(DATA_FILE_PARSE_ERROR_EXIT_CODE)
+    }
+}
+
+//
+// Output formats that describe the whole run in a single document. Reports for every (rules
+// file, data file) pair are collected here and written out once all pairs have been evaluated
+//
+#[derive(Debug)]
+enum RunLog {
+    Sarif(SarifLog),
+    JUnit(JunitReport),
+    Markdown(MarkupReport),
+    Html(MarkupReport),
+}
+
+impl RunLog {
+    fn new(output_format: &str) -> Option<RunLog> {
+        match output_format {
+            "sarif" => Some(RunLog::Sarif(SarifLog::new())),
+            "junit" => Some(RunLog::JUnit(JunitReport::new())),
+            "markdown" => Some(RunLog::Markdown(MarkupReport::new())),
+            "html" => Some(RunLog::Html(MarkupReport::new())),
+            _ => None,
+        }
+    }
+
+    fn new_like(&self) -> RunLog {
+        match self {
+            RunLog::Sarif(_) => RunLog::Sarif(SarifLog::new()),
+            RunLog::JUnit(_) => RunLog::JUnit(JunitReport::new()),
+            RunLog::Markdown(_) => RunLog::Markdown(MarkupReport::new()),
+            RunLog::Html(_) => RunLog::Html(MarkupReport::new()),
+        }
+    }
+
+    fn add_report(
+        &mut self,
+        root_record: &EventRecord<'_>,
+        rules_file_name: &str,
+        data_file: &DataFile,
+        data: &Traversal<'_>,
+    ) -> Result<()> {
+        match self {
+            RunLog::Sarif(log) => {
+                log.add_report(&simplifed_json_from_root(root_record)?, &data_file.path)
+            }
+            RunLog::JUnit(report) => {
+                report.add_report(rules_file_name, &data_file.name, root_record)?
             }
+            RunLog::Markdown(report) | RunLog::Html(report) => {
+                report.add_report(rules_file_name, &data_file.name, root_record, data)?
+            }
+        }
+        Ok(())
+    }
+
+    fn add_data_file_error(&mut self, error: &DataFileError) {
+        match self {
+            RunLog::Sarif(log) => log.add_data_file_error(error),
+            RunLog::JUnit(report) => report.add_data_file_error(error),
+            RunLog::Markdown(report) | RunLog::Html(report) => report.add_data_file_error(error),
+        }
+    }
+
+    fn append(&mut self, other: RunLog) {
+        match (self, other) {
+            (RunLog::Sarif(log), RunLog::Sarif(other)) => log.append(other),
+            (RunLog::JUnit(report), RunLog::JUnit(other)) => report.append(other),
+            (RunLog::Markdown(report), RunLog::Markdown(other))
+            | (RunLog::Html(report), RunLog::Html(other)) => report.append(other),
+            _ => unreachable!("a run only collects a single output format"),
         }
-        Ok(exit_code)
+    }
+}
+
+//
+// The baseline document is left out for output formats with a single document for the whole
+// run, stale baseline entries are still reported on stderr. Data files that could not be parsed
+// are part of that document
+//
+#[allow(clippy::too_many_arguments)]
+fn write_run_reports(
+    run_log: &mut Option<RunLog>,
+    run_summary: &Option<RunSummary>,
+    baseline: Option<&Baseline>,
+    baseline_run: &Option<BaselineRun>,
+    write_baseline: Option<&str>,
+    suppressions: &InlineSuppressions,
+    data_file_errors: &[DataFileError],
+    output: OutputFormatType,
+    writer: &mut Writer,
+) -> Result<()> {
+    if let Some(run_summary) = run_summary {
+        run_summary.write(output, writer)?;
+    }
+    if let Some(baseline_run) = baseline_run {
+        if let Some(baseline) = baseline {
+            if run_log.is_none() {
+                baseline_run.write(baseline, output, writer)?;
+            }
+            for each in baseline_run.stale(baseline) {
+                writer.write_err(format!("Stale baseline entry, no failure matched {each}"))?;
+            }
+        }
+        if let Some(file) = write_baseline {
+            baseline_run.write_baseline(file)?;
+        }
+    }
+    match run_log.as_mut() {
+        Some(run_log) => data_file_errors
+            .iter()
+            .for_each(|each| run_log.add_data_file_error(each)),
+        None => {
+            suppressions.write(output, writer)?;
+            write_data_file_errors(data_file_errors, output, writer)?;
+        }
+    }
+    match run_log {
+        Some(RunLog::Sarif(log)) => log.write(writer),
+        Some(RunLog::JUnit(report)) => report.write(writer),
+        Some(RunLog::Markdown(report)) => report.write_markdown(writer),
+        Some(RunLog::Html(report)) => report.write_html(writer),
+        None => Ok(()),
+    }
+}
+
+//
+// Rules files given explicitly are used as is unless excluded, directories and patterns are
+// scanned with the rules selector
+//
+pub(crate) fn rules_files<'a>(
+    list_of_file_or_dir: impl Iterator<Item = &'a str>,
+    rules_selector: &FileSelector,
+    cmp: FileOrdering,
+) -> Result<Vec<PathBuf>> {
+    let mut rules = Vec::new();
+    for file_or_dir in list_of_file_or_dir {
+        validate_path(FileSelector::base(file_or_dir))?;
+        let base = PathBuf::from_str(file_or_dir)?;
+        if base.is_file() {
+            if !rules_selector.is_excluded(&base, &base) {
+                rules.push(base)
+            }
+        } else {
+            rules.extend(rules_selector.select(file_or_dir, cmp)?);
+        }
+    }
+    Ok(rules)
+}
+
+//
+// Files are named by their path relative to the file, directory or pattern they were selected
+// with, a file given by itself is named by its file name
+//
+fn relative_name(file: &Path, base: &Path) -> String {
+    match file.strip_prefix(base) {
+        Ok(path) if path == Path::new("") => {
+            file.file_name().unwrap().to_str().unwrap().to_string()
+        }
+        Ok(path) => format!("{}", path.display()),
+        Err(_) => format!("{}", file.display()),
     }
 }
 
@@ -511,11 +873,6 @@ fn deserialize_payload(payload: &str) -> Result<Payload> {
     }
 }
 
-fn parse_rules<'r>(rules_file_content: &'r str, rules_file_name: &'r str) -> Result<RulesFile<'r>> {
-    let span = crate::rules::parser::Span::new_extra(rules_file_content, rules_file_name);
-    crate::rules::parser::rules_file(span)
-}
-
 // #[derive(Debug)]
 pub(crate) struct ConsoleReporter<'r> {
     root_context: StackTracker<'r>,
@@ -834,22 +1191,64 @@ impl<'r> EvaluationContext for ConsoleReporter<'r> {
     }
 }
 
+//
+// Parses the rules files, or the rules of the payload, and selects the rules to evaluate from
+// each. Rules that do not parse are reported and the run goes on with the others
+//
+fn parse_rules_sources<'r>(
+    sources: &'r [(String, String)],
+    source_kind: &str,
+    rule_selection: &RuleSelection,
+    rules_cache: Option<&RulesCache>,
+    verbose: bool,
+    outcome: &mut RunOutcome,
+    writer: &mut Writer,
+) -> Result<Vec<(SelectedRules<'r>, &'r str)>> {
+    let mut parsed_rules = Vec::with_capacity(sources.len());
+    for (content, name) in sources {
+        match parse_rules(content, name, rules_cache) {
+            Err(e) => {
+                writer.write_err(format!(
+                    "Parsing error handling {source_kind} = {}, Error = {e}\n---",
+                    name.underline(),
+                ))?;
+                outcome.rules_file_errors = true;
+            }
+            Ok(rules) => {
+                let rules = rule_selection.select(rules, content);
+                if verbose {
+                    rule_selection.write_effective(&rules, name, writer)?;
+                }
+                if !rules.nothing_selected() {
+                    parsed_rules.push((rules, name.as_str()))
+                }
+            }
+        }
+    }
+    Ok(parsed_rules)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn evaluate_against_data_input<'r>(
     _data_type: Type,
     output: OutputFormatType,
     extra_data: Option<PathAwareValue>,
-    data_files: &'r Vec<DataFile>,
-    rules: &RulesFile<'_>,
+    data_files: &'r [DataFile],
+    rules: &SelectedRules<'_>,
     rules_file_name: &'r str,
     verbose: bool,
     print_json: bool,
     show_clause_failures: bool,
     new_engine_version: bool,
     summary_table: BitFlags<SummaryType>,
+    run_log: &mut Option<RunLog>,
+    run_summary: &mut Option<RunSummary>,
+    baseline: Option<&Baseline>,
+    baseline_run: &mut Option<BaselineRun>,
+    suppressions: &mut InlineSuppressions,
     mut write_output: &mut Writer,
 ) -> Result<Status> {
-    let mut overall = Status::PASS;
+    let mut overall = Status::SKIP;
     let generic: Box<dyn Reporter> =
         Box::new(generic_summary::GenericSummary::new()) as Box<dyn Reporter>;
     let tf: Box<dyn Reporter> = Box::new(TfAware::new_with(generic.as_ref())) as Box<dyn Reporter>;
@@ -872,20 +1271,47 @@ fn evaluate_against_data_input<'r>(
                 None => file.path_value.clone(),
             };
             let traversal = Traversal::from(&each);
-            let mut root_scope = root_scope(rules, &each)?;
-            let status = eval_rules_file(rules, &mut root_scope)?;
+            let mut root_scope = root_scope(&rules.rules_file, &each)?;
+            let status = eval_rules_file(rules.evaluated(), &mut root_scope)?;
             let root_record = root_scope.reset_recorder().extract();
+            let unsuppressed =
+                suppressions.apply(rules_file_name, &file.name, &each, &root_record)?;
+            let (status, unsuppressed) = match &unsuppressed {
+                Some((status, unsuppressed)) => (*status, unsuppressed),
+                None => (status, &root_record),
+            };
+            let remaining = match baseline_run {
+                Some(baseline_run) => baseline_run.apply(
+                    baseline,
+                    rules_file_name,
+                    &file.name,
+                    &each,
+                    unsuppressed,
+                )?,
+                None => None,
+            };
+            let (status, reported) = match &remaining {
+                Some((status, remaining)) => (*status, remaining),
+                None => (status, unsuppressed),
+            };
 
-            reporter.report_eval(
-                &mut write_output,
-                status,
-                &root_record,
-                rules_file_name,
-                &file.name,
-                &file.content,
-                &traversal,
-                output,
-            )?;
+            if let Some(run_summary) = run_summary {
+                run_summary.add_report(rules_file_name, &file.name, reported);
+            }
+            if let Some(run_log) = run_log {
+                run_log.add_report(reported, rules_file_name, file, &traversal)?;
+            } else {
+                reporter.report_eval(
+                    &mut write_output,
+                    status,
+                    reported,
+                    rules_file_name,
+                    &file.name,
+                    &file.content,
+                    &traversal,
+                    output,
+                )?;
+            }
 
             if verbose {
                 print_verbose_tree(&root_record, &mut write_output);
@@ -900,12 +1326,10 @@ fn evaluate_against_data_input<'r>(
                 .expect("Unable to write to the output");
             }
 
-            if status == Status::FAIL {
-                overall = Status::FAIL
-            }
+            overall = fold_status(overall, status);
         } else {
             let each = &file.path_value;
-            let root_context = RootScope::new(rules, each)?;
+            let root_context = RootScope::new(&rules.rules_file, each)?;
             let stacker = StackTracker::new(&root_context);
             let renderers = vec![reporter.as_ref()];
 
@@ -924,16 +1348,216 @@ fn evaluate_against_data_input<'r>(
                 delegate: &reporter,
                 root_context: each,
             };
-            let status = rules.evaluate(each, &appender)?;
+            let status = rules.evaluated().evaluate(each, &appender)?;
             reporter.report(each, output)?;
-            if status == Status::FAIL {
-                overall = Status::FAIL
-            }
+            overall = fold_status(overall, status);
         }
     }
     Ok(overall)
 }
 
+//
+// Writes the report validate writes for a single (rules file, data file) pair with the default
+// summary table, test --snapshot compares it with the stored snapshot
+//
+pub(crate) fn report_pair(
+    rules: &SelectedRules<'_>,
+    rules_file_name: &str,
+    data_file_name: &str,
+    content: String,
+    output: OutputFormatType,
+    writer: &mut Writer,
+) -> Result<Status> {
+    let data_file = DataFile {
+        path_value: get_path_aware_value_from_data(&content)?,
+        content,
+        name: data_file_name.to_string(),
+        path: data_file_name.to_string(),
+    };
+    let mut suppressions = InlineSuppressions::new(false);
+    let status = evaluate_against_data_input(
+        CFNTemplate,
+        output,
+        None,
+        &[data_file],
+        rules,
+        rules_file_name,
+        false,
+        false,
+        false,
+        true,
+        SummaryType::FAIL.into(),
+        &mut None,
+        &mut None,
+        None,
+        &mut None,
+        &mut suppressions,
+        writer,
+    )?;
+    suppressions.write(output, writer)?;
+    Ok(status)
+}
+
+//
+// Evaluates every (rules file, data file) pair, on a pool of `jobs` worker threads when there is
+// more than one job. Each pair then renders into its own buffers, and the buffers are written out
+// in the same order as the sequential evaluation, rules file first and then data file, to keep
+// the output deterministic
+//
+#[allow(clippy::too_many_arguments)]
+fn evaluate_pairs(
+    jobs: usize,
+    data_type: Type,
+    output: OutputFormatType,
+    extra_data: Option<PathAwareValue>,
+    data_files: &[DataFile],
+    rules: &[(SelectedRules<'_>, &str)],
+    verbose: bool,
+    print_json: bool,
+    show_clause_failures: bool,
+    new_engine_version: bool,
+    summary_table: BitFlags<SummaryType>,
+    run_log: &mut Option<RunLog>,
+    run_summary: &mut Option<RunSummary>,
+    baseline: Option<&Baseline>,
+    baseline_run: &mut Option<BaselineRun>,
+    suppressions: &mut InlineSuppressions,
+    write_output: &mut Writer,
+) -> Result<Status> {
+    if jobs <= 1 {
+        let mut overall = Status::SKIP;
+        for (rules, rules_file_name) in rules {
+            let status = evaluate_against_data_input(
+                data_type,
+                output,
+                extra_data.clone(),
+                data_files,
+                rules,
+                rules_file_name,
+                verbose,
+                print_json,
+                show_clause_failures,
+                new_engine_version,
+                summary_table,
+                run_log,
+                run_summary,
+                baseline,
+                baseline_run,
+                suppressions,
+                write_output,
+            )?;
+            overall = fold_status(overall, status);
+        }
+        return Ok(overall);
+    }
+
+    let pairs = rules
+        .iter()
+        .flat_map(|(rules, rules_file_name)| {
+            data_files
+                .iter()
+                .map(move |data_file| (rules, *rules_file_name, data_file))
+        })
+        .collect::<Vec<_>>();
+
+    let next_pair = AtomicUsize::new(0);
+    let mut evaluated = std::thread::scope(|scope| {
+        let workers = (0..cmp::min(jobs, pairs.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut evaluated = Vec::new();
+                    loop {
+                        let index = next_pair.fetch_add(1, Ordering::Relaxed);
+                        let (rules, rules_file_name, data_file) = match pairs.get(index) {
+                            Some(pair) => pair,
+                            None => break,
+                        };
+                        let mut buffer =
+                            Writer::new(WriteBuffer::Vec(vec![]), WriteBuffer::Vec(vec![]));
+                        let mut pair_run_log = run_log.as_ref().map(RunLog::new_like);
+                        let mut pair_run_summary = run_summary.as_ref().map(|_| RunSummary::new());
+                        let mut pair_baseline_run =
+                            baseline_run.as_ref().map(|_| BaselineRun::new());
+                        let mut pair_suppressions = suppressions.new_like();
+                        let status = evaluate_against_data_input(
+                            data_type,
+                            output,
+                            extra_data.clone(),
+                            std::slice::from_ref(*data_file),
+                            rules,
+                            rules_file_name,
+                            verbose,
+                            print_json,
+                            show_clause_failures,
+                            new_engine_version,
+                            summary_table,
+                            &mut pair_run_log,
+                            &mut pair_run_summary,
+                            baseline,
+                            &mut pair_baseline_run,
+                            &mut pair_suppressions,
+                            &mut buffer,
+                        );
+                        evaluated.push((
+                            index,
+                            status.map(|status| {
+                                (
+                                    status,
+                                    buffer,
+                                    pair_run_log,
+                                    pair_run_summary,
+                                    pair_baseline_run,
+                                    pair_suppressions,
+                                )
+                            }),
+                        ));
+                    }
+                    evaluated
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect::<Vec<_>>()
+    });
+    evaluated.sort_by_key(|(index, _)| *index);
+
+    let mut overall = Status::SKIP;
+    for (_index, each) in evaluated {
+        let (status, buffer, pair_run_log, pair_run_summary, pair_baseline_run, pair_suppressions) =
+            each?;
+        let (rendered, errors) = buffer
+            .into_strings()
+            .map_err(|e| Error::ParseError(e.to_string()))?;
+        write!(write_output, "{rendered}")?;
+        for line in errors.lines() {
+            write_output.write_err(line.to_string())?;
+        }
+        if let (Some(run_log), Some(pair_run_log)) = (run_log.as_mut(), pair_run_log) {
+            run_log.append(pair_run_log);
+        }
+        if let (Some(run_summary), Some(pair_run_summary)) =
+            (run_summary.as_mut(), pair_run_summary)
+        {
+            run_summary.append(pair_run_summary);
+        }
+        if let (Some(baseline_run), Some(pair_baseline_run)) =
+            (baseline_run.as_mut(), pair_baseline_run)
+        {
+            baseline_run.append(pair_baseline_run);
+        }
+        suppressions.append(pair_suppressions);
+        overall = fold_status(overall, status);
+    }
+    Ok(overall)
+}
+
 fn get_path_aware_value_from_data(content: &String) -> Result<PathAwareValue> {
     if content.trim().is_empty() {
         Err(Error::ParseError("blank data".to_string()))
@@ -952,10 +1576,6 @@ fn get_path_aware_value_from_data(content: &String) -> Result<PathAwareValue> {
     }
 }
 
-fn has_a_supported_extension(name: &str, extensions: &[&str]) -> bool {
-    extensions.iter().any(|extension| name.ends_with(extension))
-}
-
 fn partition_failed_and_rest(top: &StatusContext) -> (Vec<&StatusContext>, Vec<&StatusContext>) {
     top.children
         .iter()
@@ -0,0 +1,282 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+use colored::*;
+use indexmap::IndexSet;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::commands::validate::common::{
+    collect_clause_failures, is_within, rule_statuses, update_file_status,
+};
+use crate::commands::validate::OutputFormatType;
+use crate::commands::value_path::{keys, value_at, Step};
+use crate::rules::errors::Error;
+use crate::rules::eval_context::{simplifed_json_from_root, EventRecord};
+use crate::rules::path_value::PathAwareValue;
+use crate::rules::{NamedStatus, RecordType, Result, Status};
+
+//
+// Violations accepted for a validate run. Each entry names the rules file, the rule and the data
+// file, both by their path relative to the --rules or --data they were selected with, and optionally the path of the resource or value that failed, or the address of the
+// resource for a Terraform plan. Without a path the entry accepts every failure of the rule in
+// that data file
+//
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Baseline {
+    violations: Vec<Violation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Violation {
+    rules_file: String,
+    rule: String,
+    data_file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+impl Violation {
+    //
+    // A path accepts the value at the path as well as everything underneath it, so an entry for
+    // /Resources/MyBucket covers all failing properties of that resource
+    //
+    fn covers(&self, other: &Violation) -> bool {
+        self.rules_file == other.rules_file
+            && self.rule == other.rule
+            && self.data_file == other.data_file
+            && match (&self.path, &other.path) {
+                (None, _) => true,
+                (Some(accepted), Some(path)) => is_within(path, accepted),
+                (Some(_), None) => false,
+            }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rule {}/{} on data file {}",
+            self.rules_file, self.rule, self.data_file
+        )?;
+        if let Some(path) = &self.path {
+            write!(f, " at {path}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Baseline {
+    pub(crate) fn load(file: &str) -> Result<Baseline> {
+        let reader = BufReader::new(File::open(file)?);
+        serde_yaml::from_reader(reader)
+            .map_err(|e| Error::ParseError(format!("Unable to read baseline file {file}, {e}")))
+    }
+}
+
+//
+// What the baseline did during a run. Every failing rule is recorded as violations, which is
+// the content for --write-baseline, along with the violations that were suppressed and the
+// baseline entries that matched at least one of them
+//
+#[derive(Debug, Default)]
+pub(crate) struct BaselineRun {
+    violations: IndexSet<Violation>,
+    suppressed: Vec<Violation>,
+    matched: BTreeSet<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct BaselineDocument<'a> {
+    baseline: BaselineSummary<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct BaselineSummary<'a> {
+    suppressed: &'a [Violation],
+    stale: Vec<&'a Violation>,
+}
+
+impl BaselineRun {
+    pub(crate) fn new() -> Self {
+        BaselineRun::default()
+    }
+
+    //
+    // Records the violations for the rules file and data file pair. Failed rules for which every
+    // failure is covered by the baseline are suppressed, the returned record no longer contains
+    // them and the status is derived from the remaining rules. None when nothing was suppressed
+    //
+    pub(crate) fn apply<'value>(
+        &mut self,
+        baseline: Option<&Baseline>,
+        rules_file: &str,
+        data_file: &str,
+        data: &PathAwareValue,
+        root_record: &EventRecord<'value>,
+    ) -> Result<Option<(Status, EventRecord<'value>)>> {
+        let report = simplifed_json_from_root(root_record)?;
+        let failures = collect_clause_failures(&report);
+        let mut suppressed_rules = HashSet::new();
+        for (rule, status) in rule_statuses(root_record) {
+            if status != Status::FAIL {
+                continue;
+            }
+
+            let violation = |path: Option<String>| Violation {
+                rules_file: rules_file.to_string(),
+                rule: rule.to_string(),
+                data_file: data_file.to_string(),
+                path,
+            };
+            let mut violations = failures
+                .iter()
+                .filter(|failure| failure.top_level_rule == rule)
+                .map(|failure| {
+                    violation(failure.value.map(|v| resource_path(data, &v.self_path().0)))
+                })
+                .collect::<IndexSet<_>>();
+            if violations.is_empty() {
+                violations.insert(violation(None));
+            }
+
+            if let Some(baseline) = baseline {
+                let mut all_covered = true;
+                for each in &violations {
+                    let covering = baseline
+                        .violations
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, accepted)| accepted.covers(each))
+                        .map(|(index, _)| index)
+                        .collect::<Vec<_>>();
+                    all_covered &= !covering.is_empty();
+                    self.matched.extend(covering);
+                }
+                if all_covered {
+                    suppressed_rules.insert(rule);
+                    self.suppressed.extend(violations.iter().cloned());
+                }
+            }
+            self.violations.extend(violations);
+        }
+
+        if suppressed_rules.is_empty() {
+            return Ok(None);
+        }
+
+        let mut remaining = root_record.clone();
+        remaining.children.retain(|each| match &each.container {
+            Some(RecordType::RuleCheck(NamedStatus { name, .. })) => {
+                !suppressed_rules.contains(name)
+            }
+            _ => true,
+        });
+        let status = update_file_status(&mut remaining);
+        Ok(Some((status, remaining)))
+    }
+
+    pub(crate) fn append(&mut self, other: BaselineRun) {
+        self.violations.extend(other.violations);
+        self.suppressed.extend(other.suppressed);
+        self.matched.extend(other.matched);
+    }
+
+    pub(crate) fn stale<'b>(&self, baseline: &'b Baseline) -> Vec<&'b Violation> {
+        baseline
+            .violations
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.matched.contains(index))
+            .map(|(_, each)| each)
+            .collect()
+    }
+
+    pub(crate) fn write(
+        &self,
+        baseline: &Baseline,
+        output: OutputFormatType,
+        writer: &mut dyn Write,
+    ) -> Result<()> {
+        let document = BaselineDocument {
+            baseline: BaselineSummary {
+                suppressed: &self.suppressed,
+                stale: self.stale(baseline),
+            },
+        };
+        match output {
+            OutputFormatType::JSON => {
+                writeln!(writer)?;
+                serde_json::to_writer_pretty(&mut *writer, &document)?;
+                writeln!(writer)?;
+            }
+            OutputFormatType::YAML => {
+                writeln!(writer, "---")?;
+                serde_yaml::to_writer(writer, &document)?;
+            }
+            _ => {
+                if !self.suppressed.is_empty() {
+                    writeln!(writer, "{}", "Suppressed by baseline".bold())?;
+                    for each in &self.suppressed {
+                        writeln!(writer, "{each}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //
+    // JSON for .json and .jsn files, YAML otherwise
+    //
+    pub(crate) fn write_baseline(&self, file: &str) -> Result<()> {
+        let baseline = Baseline {
+            violations: self.violations.iter().cloned().collect(),
+        };
+        let content = match Path::new(file).extension().and_then(|ext| ext.to_str()) {
+            Some("json") | Some("jsn") => serde_json::to_string_pretty(&baseline)? + "\n",
+            _ => serde_yaml::to_string(&baseline)?,
+        };
+        std::fs::write(file, content)?;
+        Ok(())
+    }
+}
+
+//
+// Failures in CloudFormation templates are recorded against the resource they were found in.
+// Failures in Terraform plans are recorded against the address of the resource change, its
+// index moves when other changes are added to or removed from the plan. Any other failure is
+// recorded against the path of the failing value
+//
+fn resource_path(data: &PathAwareValue, path: &str) -> String {
+    if let Some(name) = path
+        .strip_prefix("/Resources/")
+        .and_then(|rest| rest.split('/').next())
+    {
+        return format!("/Resources/{name}");
+    }
+
+    if let Some(index) = path
+        .strip_prefix("/resource_changes/")
+        .and_then(|rest| rest.split('/').next())
+    {
+        let change = index.parse::<usize>().ok().and_then(|index| {
+            value_at(
+                data,
+                &[
+                    Step::Key(Value::from("resource_changes")),
+                    Step::Index(index),
+                ],
+            )
+        });
+        return match change.and_then(|change| value_at(change, &keys(&["address"]))) {
+            Some(PathAwareValue::String((_, address))) => address.clone(),
+            _ => format!("/resource_changes/{index}"),
+        };
+    }
+    path.to_string()
+}
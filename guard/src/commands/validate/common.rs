@@ -6,9 +6,10 @@ use crate::commands::validate::OutputFormatType;
 use crate::rules::errors::Error;
 use crate::rules::eval_context::{
     simplifed_json_from_root, BinaryCheck, BinaryComparison, ClauseReport, EventRecord, FileReport,
-    GuardClauseReport, InComparison, UnaryCheck, UnaryComparison, ValueComparisons,
+    GuardClauseReport, InComparison, Messages, UnaryCheck, UnaryComparison, ValueComparisons,
     ValueUnResolved,
 };
+use crate::rules::path_value::PathAwareValue;
 use crate::rules::values::CmpOperator;
 use crate::rules::{
     ClauseCheck, EvaluationType, NamedStatus, QueryResult, RecordType, Status, UnResolved,
@@ -359,6 +360,7 @@ pub(crate) fn extract_event_records<'value>(
                     })),
                     children: vec![],
                     context: each_rule.context,
+                    location: each_rule.location,
                 };
                 //add_failed_children(&mut failed, each_rule.children)
             }
@@ -1275,3 +1277,147 @@ pub(super) fn pprint_clauses<'report, 'value: 'report>(
 
     Ok(())
 }
+
+//
+// A failing clause flattened out of a FileReport, for output formats that list failures one by
+// one instead of as a tree. `rule_name` is the rule the clause is written in, `top_level_rule`
+// the rule that was reported as failing for the data file
+//
+#[derive(Debug)]
+pub(crate) struct ClauseFailure<'value> {
+    pub(crate) top_level_rule: &'value str,
+    pub(crate) rule_name: &'value str,
+    pub(crate) message: String,
+    pub(crate) value: Option<&'value PathAwareValue>,
+}
+
+pub(crate) fn collect_clause_failures<'value>(
+    report: &FileReport<'value>,
+) -> Vec<ClauseFailure<'value>> {
+    let mut failures = Vec::new();
+    for each in &report.not_compliant {
+        if let ClauseReport::Rule(rule) = each {
+            collect_rule_failures(each, rule.name, rule.name, None, &mut failures);
+        }
+    }
+    failures
+}
+
+fn collect_rule_failures<'value>(
+    clause: &ClauseReport<'value>,
+    top_level_rule: &'value str,
+    rule_name: &'value str,
+    rule_message: Option<&str>,
+    failures: &mut Vec<ClauseFailure<'value>>,
+) {
+    let (messages, context) = match clause {
+        ClauseReport::Rule(rule) => {
+            for each in &rule.checks {
+                collect_rule_failures(
+                    each,
+                    top_level_rule,
+                    rule.name,
+                    rule.messages.custom_message.as_deref(),
+                    failures,
+                );
+            }
+            return;
+        }
+
+        ClauseReport::Disjunctions(ors) => {
+            for each in &ors.checks {
+                collect_rule_failures(each, top_level_rule, rule_name, rule_message, failures);
+            }
+            return;
+        }
+
+        ClauseReport::Block(block) => (&block.messages, &block.context),
+        ClauseReport::Clause(GuardClauseReport::Unary(unary)) => (&unary.messages, &unary.context),
+        ClauseReport::Clause(GuardClauseReport::Binary(binary)) => {
+            (&binary.messages, &binary.context)
+        }
+    };
+
+    failures.push(ClauseFailure {
+        top_level_rule,
+        rule_name,
+        message: failure_message(messages, context, rule_message),
+        value: clause.value_from(),
+    });
+}
+
+//
+// Custom messages keep the `;` that stand in for line breaks inside << >>, trim those along
+// with whitespace so that messages read the same as they do in the console output
+//
+fn failure_message(messages: &Messages, context: &str, rule_message: Option<&str>) -> String {
+    let custom_message = messages
+        .custom_message
+        .as_deref()
+        .or(rule_message)
+        .map(|msg| msg.trim_matches(|c: char| c == ';' || c.is_whitespace()))
+        .filter(|msg| !msg.is_empty());
+    let error_message = messages
+        .error_message
+        .as_deref()
+        .map(str::trim)
+        .filter(|msg| !msg.is_empty())
+        .map_or_else(
+            || format!("Check was not compliant: {}", context.trim()),
+            String::from,
+        );
+
+    match custom_message {
+        Some(custom_message) => format!("{custom_message}\n{error_message}"),
+        None => error_message,
+    }
+}
+
+//
+// Status of each rule for a data file, in the order rules were evaluated. A rule can be recorded
+// more than once when it is defined several times in the rules file, any FAIL makes the rule
+// fail and it is only skipped when every definition was skipped
+//
+pub(super) fn rule_statuses<'value>(
+    root_record: &EventRecord<'value>,
+) -> indexmap::IndexMap<&'value str, Status> {
+    let mut rules = indexmap::IndexMap::new();
+    for each in &root_record.children {
+        if let Some(RecordType::RuleCheck(NamedStatus { name, status, .. })) = &each.container {
+            let folded = rules.entry(*name).or_insert(*status);
+            *folded = fold_status(*folded, *status);
+        }
+    }
+    rules
+}
+
+pub(super) fn fold_status(folded: Status, each: Status) -> Status {
+    match (folded, each) {
+        (Status::FAIL, _) | (_, Status::FAIL) => Status::FAIL,
+        (Status::PASS, _) | (_, Status::PASS) => Status::PASS,
+        _ => Status::SKIP,
+    }
+}
+
+//
+// Recomputes the status of the data file once failures were removed from the record
+//
+pub(super) fn update_file_status(root_record: &mut EventRecord<'_>) -> Status {
+    let status = rule_statuses(root_record)
+        .values()
+        .copied()
+        .reduce(fold_status)
+        .unwrap_or(Status::SKIP);
+    if let Some(RecordType::FileCheck(file)) = &mut root_record.container {
+        file.status = status;
+    }
+    status
+}
+
+//
+// Whether the JSON pointer is the prefix itself or points to a value underneath it
+//
+pub(super) fn is_within(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
@@ -0,0 +1,214 @@
+use std::path::{Path, PathBuf};
+
+use clap::{App, Arg, ArgMatches};
+use toml::value::{Table, Value};
+
+use crate::commands::validate::validate_path;
+use crate::commands::{
+    BASELINE, CACHE_DIR, CONFIG, DATA, INPUT_PARAMETERS, NO_CONFIG, PAYLOAD, RULES, VALIDATE,
+    WRITE_BASELINE,
+};
+use crate::rules::errors::Error;
+use crate::rules::Result;
+
+//
+// Looked up in the working directory, in this order, when neither --config nor --no-config is
+// given
+//
+const CONFIG_FILE_NAMES: [&str; 2] = ["guard.toml", ".guardrc"];
+
+//
+// Keys whose values are files or directories, relative values are resolved against the
+// directory of the configuration file
+//
+const PATH_KEYS: [&str; 6] = [
+    RULES.0,
+    DATA.0,
+    INPUT_PARAMETERS.0,
+    CACHE_DIR.0,
+    BASELINE.0,
+    WRITE_BASELINE.0,
+];
+
+//
+// Project configuration for validate, a TOML file with a [validate] table. Keys are the long
+// names of the validate options, flags take true or false and options take a value or a list
+// of values
+//
+//   [validate]
+//   rules = ["rules"]
+//   data = ["templates"]
+//   input-parameters = ["parameters/common.yaml"]
+//   output-format = "json"
+//   show-summary = ["fail", "skip"]
+//   fail-on-skip = true
+//
+// Options given on the command line take precedence, a list given on the command line replaces
+// the list from the configuration file. Flags are the exception, there is no way to turn off on the
+// command line a flag that is set to true here, so such flags stick for every run that reads the
+// file. Use --no-config to leave the file out
+//
+#[derive(Debug)]
+pub(crate) struct ValidateConfig {
+    file: PathBuf,
+    options: Table,
+}
+
+impl ValidateConfig {
+    pub(crate) fn load(file: Option<&str>) -> Result<Option<ValidateConfig>> {
+        let file = match file {
+            Some(file) => {
+                validate_path(file)?;
+                PathBuf::from(file)
+            }
+            None => match CONFIG_FILE_NAMES
+                .iter()
+                .map(PathBuf::from)
+                .find(|each| each.is_file())
+            {
+                Some(file) => file,
+                None => return Ok(None),
+            },
+        };
+
+        let content = std::fs::read_to_string(&file).map_err(|e| {
+            Error::ParseError(format!(
+                "Unable to read configuration file {}, {e}",
+                file.display()
+            ))
+        })?;
+        let mut document = toml::from_str::<Table>(&content).map_err(|e| {
+            Error::ParseError(format!(
+                "Unable to read configuration file {}, {e}",
+                file.display()
+            ))
+        })?;
+        if let Some(key) = document.keys().find(|key| *key != VALIDATE) {
+            return Err(Error::ParseError(format!(
+                "Unknown key `{key}` in configuration file {}, expected `{VALIDATE}`",
+                file.display()
+            )));
+        }
+        let options = match document.remove(VALIDATE) {
+            Some(Value::Table(options)) => options,
+            Some(_) => {
+                return Err(Error::ParseError(format!(
+                    "`{VALIDATE}` in configuration file {} must be a table",
+                    file.display()
+                )))
+            }
+            None => Table::new(),
+        };
+        Ok(Some(ValidateConfig { file, options }))
+    }
+
+    //
+    // Options from the configuration file are added to the arguments given on the command line
+    // and parsed again, so they are checked just like command line arguments. Options that
+    // were given on the command line, or conflict with one that was, are left out
+    //
+    pub(crate) fn merge(&self, command: App<'static>, app: &ArgMatches) -> Result<ArgMatches> {
+        let base = self.file.parent().unwrap_or_else(|| Path::new(""));
+        let arguments = command
+            .get_arguments()
+            .filter(|arg| !is_generated(arg))
+            .collect::<Vec<_>>();
+        let given = |arg: &Arg<'_>| app.occurrences_of(arg.get_id()) > 0;
+
+        let mut args = vec![VALIDATE.to_string()];
+        for arg in arguments.iter().filter(|arg| given(arg)) {
+            let name = arg.get_id();
+            match app.values_of(name) {
+                Some(values) if arg.is_takes_value_set() => {
+                    args.extend(values.map(|value| format!("--{name}={value}")))
+                }
+                _ => args.extend((0..app.occurrences_of(name)).map(|_| format!("--{name}"))),
+            }
+        }
+
+        for (key, value) in &self.options {
+            let arg = match arguments
+                .iter()
+                .find(|arg| arg.get_id() == key && is_configurable(arg))
+            {
+                Some(arg) => *arg,
+                None => {
+                    let mut expected = arguments
+                        .iter()
+                        .filter(|arg| is_configurable(arg))
+                        .map(|arg| arg.get_id())
+                        .collect::<Vec<_>>();
+                    expected.sort_unstable();
+                    return Err(Error::ParseError(format!(
+                        "Unknown key `{key}` in configuration file {}, expected one of {}",
+                        self.file.display(),
+                        expected.join(", ")
+                    )));
+                }
+            };
+            let conflicts = |one: &Arg<'_>, other: &Arg<'_>| {
+                command
+                    .get_arg_conflicts_with(one)
+                    .iter()
+                    .any(|each| each.get_id() == other.get_id())
+            };
+            if given(arg)
+                || arguments
+                    .iter()
+                    .filter(|other| given(other))
+                    .any(|other| conflicts(arg, other) || conflicts(other, arg))
+            {
+                continue;
+            }
+
+            if !arg.is_takes_value_set() {
+                match value {
+                    Value::Boolean(true) => args.push(format!("--{key}")),
+                    Value::Boolean(false) => {}
+                    _ => return Err(self.invalid(key, "expects true or false")),
+                }
+                continue;
+            }
+            let values = match value {
+                Value::Array(values) => values.iter().collect::<Vec<_>>(),
+                value => vec![value],
+            };
+            for value in values {
+                let value = match value {
+                    Value::String(value) if PATH_KEYS.contains(&key.as_str()) => {
+                        format!("{}", base.join(value).display())
+                    }
+                    Value::String(value) => value.clone(),
+                    Value::Integer(value) => value.to_string(),
+                    _ => return Err(self.invalid(key, "expects a value or a list of values")),
+                };
+                args.push(format!("--{key}={value}"));
+            }
+        }
+
+        command.try_get_matches_from(args).map_err(|e| {
+            Error::ParseError(format!(
+                "Invalid options in configuration file {}, {e}",
+                self.file.display()
+            ))
+        })
+    }
+
+    fn invalid(&self, key: &str, expected: &str) -> Error {
+        Error::ParseError(format!(
+            "Key `{key}` in configuration file {} {expected}",
+            self.file.display()
+        ))
+    }
+}
+
+fn is_configurable(arg: &Arg<'_>) -> bool {
+    arg.get_id() != CONFIG.0 && arg.get_id() != NO_CONFIG.0 && arg.get_id() != PAYLOAD.0
+}
+
+//
+// Added by clap, the matches of the subcommand do not know about them
+//
+fn is_generated(arg: &Arg<'_>) -> bool {
+    arg.get_id() == "help" || arg.get_id() == "version"
+}
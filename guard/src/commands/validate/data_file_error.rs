@@ -0,0 +1,86 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::commands::validate::OutputFormatType;
+use crate::rules::errors::Error;
+use crate::rules::Result;
+
+//
+// A data file that could not be parsed. The file is left out of the run and the remaining data
+// files are still evaluated. The location is taken from the YAML/JSON parser when it reports
+// one, lines and columns are 1 based
+//
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct DataFileError {
+    pub(crate) data_file: String,
+    #[serde(skip)]
+    pub(crate) path: String,
+    pub(crate) message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) column: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct DataFileErrorsDocument<'a> {
+    data_file_errors: &'a [DataFileError],
+}
+
+impl DataFileError {
+    pub(crate) fn new(data_file: &str, path: &str, content: &str, error: Error) -> Self {
+        let (message, location) = match serde_yaml::from_str::<serde_yaml::Value>(content) {
+            Err(e) if !content.trim().is_empty() => (e.to_string(), e.location()),
+            _ => (error.to_string(), None),
+        };
+        DataFileError {
+            data_file: data_file.to_string(),
+            path: path.to_string(),
+            message,
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+        }
+    }
+}
+
+impl std::fmt::Display for DataFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Parsing error handling data file = {}, Error = {}",
+            self.data_file, self.message
+        )
+    }
+}
+
+//
+// Output formats with a single document for the whole run carry the errors in that document,
+// JSON and YAML get a separate document after the reports
+//
+pub(crate) fn write_data_file_errors(
+    errors: &[DataFileError],
+    output: OutputFormatType,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let document = DataFileErrorsDocument {
+        data_file_errors: errors,
+    };
+    match output {
+        OutputFormatType::JSON => {
+            writeln!(writer)?;
+            serde_json::to_writer_pretty(&mut *writer, &document)?;
+            writeln!(writer)?;
+        }
+        OutputFormatType::YAML => {
+            writeln!(writer, "---")?;
+            serde_yaml::to_writer(writer, &document)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
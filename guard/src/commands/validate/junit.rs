@@ -0,0 +1,233 @@
+use std::io::Write;
+
+use crate::commands::validate::common::{collect_clause_failures, rule_statuses};
+use crate::commands::validate::data_file_error::DataFileError;
+use crate::rules::eval_context::{simplifed_json_from_root, EventRecord};
+use crate::rules::{Result, Status};
+
+const DATA_FILES_SUITE: &str = "data files";
+
+//
+// JUnit XML report shared by validate and test. Each suite is written out once the whole run
+// is done, CI systems expect a single document per report file
+//
+#[derive(Debug, Default)]
+pub(crate) struct JunitReport {
+    suites: Vec<TestSuite>,
+}
+
+#[derive(Debug)]
+struct TestSuite {
+    name: String,
+    test_cases: Vec<JunitTestCase>,
+}
+
+#[derive(Debug)]
+pub(crate) struct JunitTestCase {
+    pub(crate) name: String,
+    pub(crate) class_name: String,
+    pub(crate) outcome: TestOutcome,
+}
+
+#[derive(Debug)]
+pub(crate) enum TestOutcome {
+    Passed,
+    Skipped,
+    Failed { message: String, details: String },
+    Error { message: String },
+}
+
+impl TestSuite {
+    fn count(&self, matches: fn(&TestOutcome) -> bool) -> usize {
+        self.test_cases
+            .iter()
+            .filter(|each| matches(&each.outcome))
+            .count()
+    }
+}
+
+fn is_failure(outcome: &TestOutcome) -> bool {
+    matches!(outcome, TestOutcome::Failed { .. })
+}
+
+fn is_skipped(outcome: &TestOutcome) -> bool {
+    matches!(outcome, TestOutcome::Skipped)
+}
+
+fn is_error(outcome: &TestOutcome) -> bool {
+    matches!(outcome, TestOutcome::Error { .. })
+}
+
+//
+// The errors attribute is only written when at least one test case is an error, a report
+// without errors has only the tests, failures and skipped counts
+//
+fn errors_attribute(errors: usize) -> String {
+    match errors {
+        0 => String::new(),
+        errors => format!(r#" errors="{errors}""#),
+    }
+}
+
+impl JunitReport {
+    pub(crate) fn new() -> Self {
+        JunitReport::default()
+    }
+
+    pub(crate) fn add_test_case(&mut self, suite: &str, test_case: JunitTestCase) {
+        match self.suites.iter_mut().find(|each| each.name == suite) {
+            Some(existing) => existing.test_cases.push(test_case),
+            None => self.suites.push(TestSuite {
+                name: suite.to_string(),
+                test_cases: vec![test_case],
+            }),
+        }
+    }
+
+    //
+    // One test case per rule for the data file
+    //
+    pub(crate) fn add_report(
+        &mut self,
+        rules_file: &str,
+        data_file: &str,
+        root_record: &EventRecord<'_>,
+    ) -> Result<()> {
+        let rules = rule_statuses(root_record);
+        let report = simplifed_json_from_root(root_record)?;
+        let failures = collect_clause_failures(&report);
+        for (rule_name, status) in rules {
+            let outcome = match status {
+                Status::PASS => TestOutcome::Passed,
+                Status::SKIP => TestOutcome::Skipped,
+                Status::FAIL => TestOutcome::Failed {
+                    message: format!("Rule {rule_name} is not compliant"),
+                    details: failures
+                        .iter()
+                        .filter(|failure| failure.top_level_rule == rule_name)
+                        .map(|failure| failure.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n\n"),
+                },
+            };
+            self.add_test_case(
+                rules_file,
+                JunitTestCase {
+                    name: rule_name.to_string(),
+                    class_name: data_file.to_string(),
+                    outcome,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    //
+    // Data files that could not be parsed are errored test cases of their own suite
+    //
+    pub(crate) fn add_data_file_error(&mut self, error: &DataFileError) {
+        self.add_test_case(
+            DATA_FILES_SUITE,
+            JunitTestCase {
+                name: error.data_file.clone(),
+                class_name: error.data_file.clone(),
+                outcome: TestOutcome::Error {
+                    message: error.message.clone(),
+                },
+            },
+        );
+    }
+
+    pub(crate) fn append(&mut self, other: JunitReport) {
+        for suite in other.suites {
+            for test_case in suite.test_cases {
+                self.add_test_case(&suite.name, test_case);
+            }
+        }
+    }
+
+    pub(crate) fn write(&self, writer: &mut dyn Write) -> Result<()> {
+        let tests = self.suites.iter().map(|suite| suite.test_cases.len());
+        let failures = self.suites.iter().map(|suite| suite.count(is_failure));
+        let skipped = self.suites.iter().map(|suite| suite.count(is_skipped));
+        let errors = self.suites.iter().map(|suite| suite.count(is_error));
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<testsuites tests="{}" failures="{}" skipped="{}"{}>"#,
+            tests.sum::<usize>(),
+            failures.sum::<usize>(),
+            skipped.sum::<usize>(),
+            errors_attribute(errors.sum::<usize>())
+        )?;
+        for suite in &self.suites {
+            writeln!(
+                writer,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" skipped="{}"{}>"#,
+                escape(&suite.name),
+                suite.test_cases.len(),
+                suite.count(is_failure),
+                suite.count(is_skipped),
+                errors_attribute(suite.count(is_error))
+            )?;
+            for test_case in &suite.test_cases {
+                let attributes = format!(
+                    r#"name="{}" classname="{}""#,
+                    escape(&test_case.name),
+                    escape(&test_case.class_name)
+                );
+                match &test_case.outcome {
+                    TestOutcome::Passed => writeln!(writer, "    <testcase {attributes}/>")?,
+                    TestOutcome::Skipped => {
+                        writeln!(writer, "    <testcase {attributes}>")?;
+                        writeln!(writer, "      <skipped/>")?;
+                        writeln!(writer, "    </testcase>")?;
+                    }
+                    TestOutcome::Failed { message, details } => {
+                        writeln!(writer, "    <testcase {attributes}>")?;
+                        if details.is_empty() {
+                            writeln!(writer, r#"      <failure message="{}"/>"#, escape(message))?;
+                        } else {
+                            writeln!(
+                                writer,
+                                r#"      <failure message="{}">{}</failure>"#,
+                                escape(message),
+                                escape(details)
+                            )?;
+                        }
+                        writeln!(writer, "    </testcase>")?;
+                    }
+                    TestOutcome::Error { message } => {
+                        writeln!(writer, "    <testcase {attributes}>")?;
+                        writeln!(writer, r#"      <error message="{}"/>"#, escape(message))?;
+                        writeln!(writer, "    </testcase>")?;
+                    }
+                }
+            }
+            writeln!(writer, "  </testsuite>")?;
+        }
+        writeln!(writer, "</testsuites>")?;
+        Ok(())
+    }
+}
+
+//
+// Control characters other than tab and line breaks are not allowed in XML 1.0 even when
+// escaped, they are dropped
+//
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,404 @@
+use std::io::Write;
+
+use indexmap::IndexMap;
+
+use crate::commands::validate::common::{collect_clause_failures, fold_status, rule_statuses};
+use crate::commands::validate::data_file_error::DataFileError;
+use crate::commands::APP_NAME;
+use crate::rules::display::ValueOnlyDisplay;
+use crate::rules::eval_context::{simplifed_json_from_root, EventRecord};
+use crate::rules::path_value::traversal::{Traversal, TraversalResult};
+use crate::rules::path_value::PathAwareValue;
+use crate::rules::{Result, Status};
+
+//
+// Values are shown inline in table cells, long structures are cut off
+//
+const MAX_VALUE_LENGTH: usize = 120;
+
+//
+// Markdown and HTML reports for a validate run. Both render the same content, a summary table
+// with one row per (data file, rules file) pair followed by the status of each rule and the
+// failing clauses for every pair. Failures in CloudFormation templates and Terraform plans are
+// grouped by the resource they were found in. Data files that could not be parsed are listed
+// after the summary table
+//
+#[derive(Debug, Default)]
+pub(crate) struct MarkupReport {
+    evaluations: Vec<Evaluation>,
+    data_file_errors: Vec<DataFileError>,
+}
+
+#[derive(Debug)]
+struct Evaluation {
+    rules_file: String,
+    data_file: String,
+    status: Status,
+    rules: Vec<(String, Status)>,
+    resources: IndexMap<Option<Resource>, Vec<Failure>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct Resource {
+    name: String,
+    resource_type: String,
+}
+
+#[derive(Debug)]
+struct Failure {
+    rule: String,
+    path: String,
+    line: usize,
+    value: String,
+    message: String,
+}
+
+impl Evaluation {
+    fn count(&self, status: Status) -> usize {
+        self.rules
+            .iter()
+            .filter(|(_, each)| *each == status)
+            .count()
+    }
+}
+
+impl MarkupReport {
+    pub(crate) fn new() -> Self {
+        MarkupReport::default()
+    }
+
+    pub(crate) fn add_report(
+        &mut self,
+        rules_file: &str,
+        data_file: &str,
+        root_record: &EventRecord<'_>,
+        data: &Traversal<'_>,
+    ) -> Result<()> {
+        let rules = rule_statuses(root_record)
+            .into_iter()
+            .map(|(name, status)| (name.to_string(), status))
+            .collect::<Vec<_>>();
+        let status = rules
+            .iter()
+            .map(|(_, status)| *status)
+            .reduce(fold_status)
+            .unwrap_or(Status::SKIP);
+
+        let report = simplifed_json_from_root(root_record)?;
+        let mut resources: IndexMap<Option<Resource>, Vec<Failure>> = IndexMap::new();
+        for failure in collect_clause_failures(&report) {
+            let (path, line, value) = match failure.value {
+                Some(value) => {
+                    let path = value.self_path();
+                    (path.0.clone(), path.1.line, display_value(value))
+                }
+                None => (String::new(), 0, String::new()),
+            };
+            resources
+                .entry(resource_of(&path, data))
+                .or_default()
+                .push(Failure {
+                    rule: failure.rule_name.to_string(),
+                    path,
+                    line,
+                    value,
+                    message: failure.message,
+                });
+        }
+
+        self.evaluations.push(Evaluation {
+            rules_file: rules_file.to_string(),
+            data_file: data_file.to_string(),
+            status,
+            rules,
+            resources,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn add_data_file_error(&mut self, error: &DataFileError) {
+        self.data_file_errors.push(error.clone())
+    }
+
+    pub(crate) fn append(&mut self, other: MarkupReport) {
+        self.evaluations.extend(other.evaluations);
+        self.data_file_errors.extend(other.data_file_errors)
+    }
+
+    pub(crate) fn write_markdown(&self, writer: &mut dyn Write) -> Result<()> {
+        writeln!(writer, "# {APP_NAME} validation report")?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "| Data file | Rules file | Status | PASS | FAIL | SKIP |"
+        )?;
+        writeln!(writer, "| --- | --- | --- | ---: | ---: | ---: |")?;
+        for each in &self.evaluations {
+            writeln!(
+                writer,
+                "| {} | {} | {} | {} | {} | {} |",
+                markdown_cell(&each.data_file),
+                markdown_cell(&each.rules_file),
+                each.status,
+                each.count(Status::PASS),
+                each.count(Status::FAIL),
+                each.count(Status::SKIP)
+            )?;
+        }
+
+        if !self.data_file_errors.is_empty() {
+            writeln!(writer)?;
+            writeln!(writer, "## Data files that could not be parsed")?;
+            writeln!(writer)?;
+            writeln!(writer, "| Data file | Line | Column | Error |")?;
+            writeln!(writer, "| --- | ---: | ---: | --- |")?;
+            for each in &self.data_file_errors {
+                writeln!(
+                    writer,
+                    "| {} | {} | {} | {} |",
+                    markdown_cell(&each.data_file),
+                    optional_number(each.line),
+                    optional_number(each.column),
+                    markdown_cell(&each.message)
+                )?;
+            }
+        }
+
+        for each in &self.evaluations {
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "## {} against {}: {}",
+                markdown_cell(&each.data_file),
+                markdown_cell(&each.rules_file),
+                each.status
+            )?;
+            writeln!(writer)?;
+            writeln!(writer, "| Rule | Status |")?;
+            writeln!(writer, "| --- | --- |")?;
+            for (rule, status) in &each.rules {
+                writeln!(writer, "| {} | {} |", markdown_cell(rule), status)?;
+            }
+
+            for (resource, failures) in &each.resources {
+                writeln!(writer)?;
+                match resource {
+                    Some(resource) => writeln!(
+                        writer,
+                        "### Resource {} ({})",
+                        markdown_cell(&resource.name),
+                        markdown_cell(&resource.resource_type)
+                    )?,
+                    None => writeln!(writer, "### Other failures")?,
+                }
+                writeln!(writer)?;
+                writeln!(writer, "| Rule | Property path | Line | Value | Message |")?;
+                writeln!(writer, "| --- | --- | ---: | --- | --- |")?;
+                for failure in failures {
+                    writeln!(
+                        writer,
+                        "| {} | {} | {} | {} | {} |",
+                        markdown_cell(&failure.rule),
+                        markdown_cell(&failure.path),
+                        line_of(failure),
+                        markdown_cell(&failure.value),
+                        markdown_cell(&failure.message)
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write_html(&self, writer: &mut dyn Write) -> Result<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, r#"<html lang="en">"#)?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, r#"<meta charset="utf-8">"#)?;
+        writeln!(writer, "<title>{APP_NAME} validation report</title>")?;
+        writeln!(writer, "<style>")?;
+        writeln!(writer, "body {{ font-family: sans-serif; margin: 2em; }}")?;
+        writeln!(
+            writer,
+            "table {{ border-collapse: collapse; margin-bottom: 1em; }}"
+        )?;
+        writeln!(writer, "th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }}")?;
+        writeln!(writer, "td.PASS {{ color: #1a7f37; }}")?;
+        writeln!(writer, "td.FAIL {{ color: #cf222e; font-weight: bold; }}")?;
+        writeln!(writer, "td.SKIP {{ color: #6e7781; }}")?;
+        writeln!(writer, "</style>")?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, "<h1>{APP_NAME} validation report</h1>")?;
+        writeln!(writer, "<table>")?;
+        writeln!(writer, "<tr><th>Data file</th><th>Rules file</th><th>Status</th><th>PASS</th><th>FAIL</th><th>SKIP</th></tr>")?;
+        for each in &self.evaluations {
+            writeln!(
+                writer,
+                r#"<tr><td>{}</td><td>{}</td><td class="{status}">{status}</td><td>{}</td><td>{}</td><td>{}</td></tr>"#,
+                html_text(&each.data_file),
+                html_text(&each.rules_file),
+                each.count(Status::PASS),
+                each.count(Status::FAIL),
+                each.count(Status::SKIP),
+                status = each.status
+            )?;
+        }
+        writeln!(writer, "</table>")?;
+
+        if !self.data_file_errors.is_empty() {
+            writeln!(writer, "<section>")?;
+            writeln!(writer, "<h2>Data files that could not be parsed</h2>")?;
+            writeln!(writer, "<table>")?;
+            writeln!(
+                writer,
+                "<tr><th>Data file</th><th>Line</th><th>Column</th><th>Error</th></tr>"
+            )?;
+            for each in &self.data_file_errors {
+                writeln!(
+                    writer,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    html_text(&each.data_file),
+                    optional_number(each.line),
+                    optional_number(each.column),
+                    html_text(&each.message).replace('\n', "<br>")
+                )?;
+            }
+            writeln!(writer, "</table>")?;
+            writeln!(writer, "</section>")?;
+        }
+
+        for each in &self.evaluations {
+            writeln!(writer, "<section>")?;
+            writeln!(
+                writer,
+                "<h2>{} against {}: {}</h2>",
+                html_text(&each.data_file),
+                html_text(&each.rules_file),
+                each.status
+            )?;
+            writeln!(writer, "<table>")?;
+            writeln!(writer, "<tr><th>Rule</th><th>Status</th></tr>")?;
+            for (rule, status) in &each.rules {
+                writeln!(
+                    writer,
+                    r#"<tr><td>{}</td><td class="{status}">{status}</td></tr>"#,
+                    html_text(rule),
+                )?;
+            }
+            writeln!(writer, "</table>")?;
+
+            for (resource, failures) in &each.resources {
+                match resource {
+                    Some(resource) => writeln!(
+                        writer,
+                        "<h3>Resource {} ({})</h3>",
+                        html_text(&resource.name),
+                        html_text(&resource.resource_type)
+                    )?,
+                    None => writeln!(writer, "<h3>Other failures</h3>")?,
+                }
+                writeln!(writer, "<table>")?;
+                writeln!(writer, "<tr><th>Rule</th><th>Property path</th><th>Line</th><th>Value</th><th>Message</th></tr>")?;
+                for failure in failures {
+                    writeln!(
+                        writer,
+                        "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td><code>{}</code></td><td>{}</td></tr>",
+                        html_text(&failure.rule),
+                        html_text(&failure.path),
+                        line_of(failure),
+                        html_text(&failure.value),
+                        html_text(&failure.message).replace('\n', "<br>")
+                    )?;
+                }
+                writeln!(writer, "</table>")?;
+            }
+            writeln!(writer, "</section>")?;
+        }
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")?;
+        Ok(())
+    }
+}
+
+//
+// CloudFormation failures are grouped under the logical id of the resource, Terraform plan
+// failures under the address of the resource change. Anything else is reported ungrouped
+//
+fn resource_of(path: &str, data: &Traversal<'_>) -> Option<Resource> {
+    let root = data.root()?;
+    let string_at = |pointer: &str| match data.at(pointer, root) {
+        Ok(TraversalResult::Value(node)) => match node.value() {
+            PathAwareValue::String((_, value)) => Some(value.clone()),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    if let Some(rest) = path.strip_prefix("/Resources/") {
+        let name = rest.split('/').next()?;
+        let resource_type = string_at(&format!("/Resources/{name}/Type"))?;
+        return Some(Resource {
+            name: name.to_string(),
+            resource_type,
+        });
+    }
+
+    if let Some(rest) = path.strip_prefix("/resource_changes/") {
+        let index = rest.split('/').next()?;
+        let name = string_at(&format!("/resource_changes/{index}/address"))?;
+        let resource_type = string_at(&format!("/resource_changes/{index}/type"))
+            .or_else(|| name.split('.').next().map(String::from))?;
+        return Some(Resource {
+            name,
+            resource_type,
+        });
+    }
+    None
+}
+
+fn display_value(value: &PathAwareValue) -> String {
+    let displayed = format!("{}", ValueOnlyDisplay(value));
+    match displayed.char_indices().nth(MAX_VALUE_LENGTH) {
+        Some((cut_off, _)) => format!("{}...", &displayed[..cut_off]),
+        None => displayed,
+    }
+}
+
+fn line_of(failure: &Failure) -> String {
+    match failure.line {
+        0 => String::new(),
+        line => line.to_string(),
+    }
+}
+
+fn optional_number(number: Option<usize>) -> String {
+    number.map_or(String::new(), |number| number.to_string())
+}
+
+fn html_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+//
+// Table cells can neither contain line breaks nor unescaped pipes
+//
+fn markdown_cell(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
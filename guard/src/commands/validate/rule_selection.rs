@@ -0,0 +1,314 @@
+use std::collections::{BTreeSet, HashMap};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::rules::errors::Error;
+use crate::rules::exprs::{
+    Block, Conjunctions, GuardClause, Rule, RuleClause, RulesFile, WhenGuardClause,
+};
+use crate::rules::Result;
+use crate::utils::writer::Writer;
+
+const TAGS_COMMENT: &str = "tags:";
+
+//
+// Selects the rules of each rules file that are evaluated and reported. Rules are selected by
+// name patterns with --rule-name, left out with --exclude-rule, and selected by tags with --tags.
+// Tags are declared in the comments right above a rule
+//
+//   # tags: pci, s3
+//   rule S3_BUCKET_ENCRYPTED { ... }
+//
+// Rules that a selected rule depends on are still evaluated when the selected rule refers to
+// them, they are not reported on their own
+//
+#[derive(Debug)]
+pub(crate) struct RuleSelection {
+    names: Option<GlobSet>,
+    excluded: GlobSet,
+    tags: Vec<String>,
+}
+
+//
+// A parsed rules file with the rules that were selected from it. Named rules are looked up in
+// the whole file, only the selected rules are evaluated from the top
+//
+#[derive(Debug)]
+pub(crate) struct SelectedRules<'loc> {
+    pub(crate) rules_file: RulesFile<'loc>,
+    selected: Option<RulesFile<'loc>>,
+}
+
+impl<'loc> SelectedRules<'loc> {
+    pub(crate) fn all(rules_file: RulesFile<'loc>) -> Self {
+        SelectedRules {
+            rules_file,
+            selected: None,
+        }
+    }
+
+    pub(crate) fn evaluated(&self) -> &RulesFile<'loc> {
+        self.selected.as_ref().unwrap_or(&self.rules_file)
+    }
+
+    pub(crate) fn nothing_selected(&self) -> bool {
+        self.selected
+            .as_ref()
+            .is_some_and(|selected| selected.guard_rules.is_empty())
+    }
+}
+
+impl RuleSelection {
+    pub(crate) fn new<'a, N, X, T>(names: N, excluded: X, tags: T) -> Result<RuleSelection>
+    where
+        N: IntoIterator<Item = &'a str>,
+        X: IntoIterator<Item = &'a str>,
+        T: IntoIterator<Item = &'a str>,
+    {
+        let names = names.into_iter().collect::<Vec<_>>();
+        Ok(RuleSelection {
+            names: if names.is_empty() {
+                None
+            } else {
+                Some(glob_set(names)?)
+            },
+            excluded: glob_set(excluded)?,
+            tags: tags.into_iter().map(String::from).collect(),
+        })
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.names.is_some() || !self.excluded.is_empty() || !self.tags.is_empty()
+    }
+
+    pub(crate) fn select<'loc>(
+        &self,
+        rules_file: RulesFile<'loc>,
+        content: &str,
+    ) -> SelectedRules<'loc> {
+        if !self.is_active() {
+            return SelectedRules::all(rules_file);
+        }
+
+        let tags = if self.tags.is_empty() {
+            HashMap::new()
+        } else {
+            declared_tags(content)
+        };
+        let guard_rules = rules_file
+            .guard_rules
+            .iter()
+            .filter(|rule| self.is_selected(&rule.rule_name, &tags))
+            .cloned()
+            .collect();
+        let selected = RulesFile {
+            assignments: rules_file.assignments.clone(),
+            guard_rules,
+            parameterized_rules: rules_file.parameterized_rules.clone(),
+        };
+        SelectedRules {
+            rules_file,
+            selected: Some(selected),
+        }
+    }
+
+    #[allow(clippy::unnecessary_map_or)]
+    fn is_selected(&self, rule_name: &str, tags: &HashMap<&str, Vec<&str>>) -> bool {
+        self.names
+            .as_ref()
+            .map_or(true, |names| names.is_match(rule_name))
+            && !self.excluded.is_match(rule_name)
+            && (self.tags.is_empty()
+                || tags.get(rule_name).is_some_and(|declared| {
+                    declared
+                        .iter()
+                        .any(|tag| self.tags.iter().any(|each| each == tag))
+                }))
+    }
+
+    pub(crate) fn write_effective(
+        &self,
+        rules: &SelectedRules<'_>,
+        rules_file_name: &str,
+        writer: &mut Writer,
+    ) -> Result<()> {
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        let selected = rules
+            .evaluated()
+            .guard_rules
+            .iter()
+            .map(|rule| rule.rule_name.as_str())
+            .collect::<BTreeSet<_>>();
+        let dependencies = dependencies(&rules.rules_file, &selected);
+        writer.write_err(format!(
+            "Rules selected from {rules_file_name}: {}",
+            list(selected.into_iter())
+        ))?;
+        if !dependencies.is_empty() {
+            writer.write_err(format!(
+                "Rules evaluated as dependencies from {rules_file_name}: {}",
+                list(dependencies.into_iter())
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+fn glob_set<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|e| {
+            Error::ParseError(format!("Invalid rule name pattern `{pattern}`, {e}"))
+        })?);
+    }
+    builder
+        .build()
+        .map_err(|e| Error::ParseError(format!("Invalid rule name patterns, {e}")))
+}
+
+fn list<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    let names = names.collect::<Vec<_>>();
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+//
+// Tags from the `# tags:` comments in the comment block right above each rule
+//
+fn declared_tags(content: &str) -> HashMap<&str, Vec<&str>> {
+    let mut declared = HashMap::new();
+    let mut pending = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some(tags) = comment.trim().strip_prefix(TAGS_COMMENT) {
+                pending.extend(
+                    tags.split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|tag| !tag.is_empty()),
+                );
+            }
+            continue;
+        }
+        if let Some(name) = rule_name(line) {
+            declared
+                .entry(name)
+                .or_insert_with(Vec::new)
+                .append(&mut pending);
+        }
+        pending.clear();
+    }
+    declared
+}
+
+fn rule_name(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("rule")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+//
+// Named rules that the selected rules refer to, directly or through other named rules, and
+// that were not selected themselves
+//
+fn dependencies<'r>(
+    rules_file: &'r RulesFile<'_>,
+    selected: &BTreeSet<&'r str>,
+) -> BTreeSet<&'r str> {
+    let mut rules: HashMap<&str, Vec<&Rule<'_>>> = HashMap::new();
+    for rule in &rules_file.guard_rules {
+        rules.entry(rule.rule_name.as_str()).or_default().push(rule);
+    }
+    for parameterized in &rules_file.parameterized_rules {
+        rules
+            .entry(parameterized.rule.rule_name.as_str())
+            .or_default()
+            .push(&parameterized.rule);
+    }
+
+    let mut visited = selected.clone();
+    let mut pending = selected.iter().copied().collect::<Vec<_>>();
+    let mut dependencies = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        let mut referred = Vec::new();
+        for rule in rules.get(name).into_iter().flatten() {
+            named_rules_of_rule(rule, &mut referred);
+        }
+        for each in referred {
+            if let Some((name, _)) = rules.get_key_value(each) {
+                if visited.insert(*name) {
+                    dependencies.insert(*name);
+                    pending.push(*name);
+                }
+            }
+        }
+    }
+    dependencies
+}
+
+fn named_rules_of_rule<'r>(rule: &'r Rule<'_>, names: &mut Vec<&'r str>) {
+    if let Some(conditions) = &rule.conditions {
+        named_rules_of_conditions(conditions, names);
+    }
+    for clause in rule.block.conjunctions.iter().flatten() {
+        match clause {
+            RuleClause::Clause(clause) => named_rules_of_clause(clause, names),
+            RuleClause::WhenBlock(conditions, block) => {
+                named_rules_of_conditions(conditions, names);
+                named_rules_of_block(block, names);
+            }
+            RuleClause::TypeBlock(type_block) => {
+                if let Some(conditions) = &type_block.conditions {
+                    named_rules_of_conditions(conditions, names);
+                }
+                named_rules_of_block(&type_block.block, names);
+            }
+        }
+    }
+}
+
+fn named_rules_of_block<'r>(block: &'r Block<'_, GuardClause<'_>>, names: &mut Vec<&'r str>) {
+    for clause in block.conjunctions.iter().flatten() {
+        named_rules_of_clause(clause, names);
+    }
+}
+
+fn named_rules_of_clause<'r>(clause: &'r GuardClause<'_>, names: &mut Vec<&'r str>) {
+    match clause {
+        GuardClause::Clause(_) => {}
+        GuardClause::NamedRule(named) => names.push(&named.dependent_rule),
+        GuardClause::ParameterizedNamedRule(parameterized) => {
+            names.push(&parameterized.named_rule.dependent_rule)
+        }
+        GuardClause::BlockClause(block) => named_rules_of_block(&block.block, names),
+        GuardClause::WhenBlock(conditions, block) => {
+            named_rules_of_conditions(conditions, names);
+            named_rules_of_block(block, names);
+        }
+    }
+}
+
+fn named_rules_of_conditions<'r>(
+    conditions: &'r Conjunctions<WhenGuardClause<'_>>,
+    names: &mut Vec<&'r str>,
+) {
+    for clause in conditions.iter().flatten() {
+        match clause {
+            WhenGuardClause::Clause(_) => {}
+            WhenGuardClause::NamedRule(named) => names.push(&named.dependent_rule),
+            WhenGuardClause::ParameterizedNamedRule(parameterized) => {
+                names.push(&parameterized.named_rule.dependent_rule)
+            }
+        }
+    }
+}
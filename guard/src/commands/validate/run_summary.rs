@@ -0,0 +1,224 @@
+use std::io::Write;
+
+use colored::*;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::commands::validate::common::rule_statuses;
+use crate::commands::validate::OutputFormatType;
+use crate::rules::eval_context::EventRecord;
+use crate::rules::{Result, Status};
+
+//
+// Number of data files listed as the ones with the most failed rules
+//
+const MOST_FAILED_DATA_FILES: usize = 10;
+
+//
+// Roll-up of a validate run across every (rules file, data file) pair. Rules are keyed by the
+// rules file they come from, the same rule name can appear in several rules files
+//
+#[derive(Debug, Default)]
+pub(crate) struct RunSummary {
+    rules: IndexMap<(String, String), RuleCounts>,
+    data_files: IndexMap<String, usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+struct RuleCounts {
+    pass: usize,
+    fail: usize,
+    skip: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct SummaryDocument<'a> {
+    run_summary: Summary<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    rules: Vec<RuleSummary<'a>>,
+    most_failed_data_files: Vec<DataFileSummary<'a>>,
+    always_skipped_rules: Vec<RuleName<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleSummary<'a> {
+    rules_file: &'a str,
+    rule: &'a str,
+    #[serde(flatten)]
+    counts: RuleCounts,
+}
+
+#[derive(Debug, Serialize)]
+struct DataFileSummary<'a> {
+    data_file: &'a str,
+    failed_rules: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleName<'a> {
+    rules_file: &'a str,
+    rule: &'a str,
+}
+
+impl RunSummary {
+    pub(crate) fn new() -> Self {
+        RunSummary::default()
+    }
+
+    pub(crate) fn add_report(
+        &mut self,
+        rules_file: &str,
+        data_file: &str,
+        root_record: &EventRecord<'_>,
+    ) {
+        let mut failed_rules = 0;
+        for (rule, status) in rule_statuses(root_record) {
+            let counts = self
+                .rules
+                .entry((rules_file.to_string(), rule.to_string()))
+                .or_default();
+            match status {
+                Status::PASS => counts.pass += 1,
+                Status::FAIL => {
+                    counts.fail += 1;
+                    failed_rules += 1;
+                }
+                Status::SKIP => counts.skip += 1,
+            }
+        }
+        *self.data_files.entry(data_file.to_string()).or_default() += failed_rules;
+    }
+
+    pub(crate) fn append(&mut self, other: RunSummary) {
+        for (rule, counts) in other.rules {
+            let merged = self.rules.entry(rule).or_default();
+            merged.pass += counts.pass;
+            merged.fail += counts.fail;
+            merged.skip += counts.skip;
+        }
+        for (data_file, failed_rules) in other.data_files {
+            *self.data_files.entry(data_file).or_default() += failed_rules;
+        }
+    }
+
+    pub(crate) fn write(&self, output: OutputFormatType, writer: &mut dyn Write) -> Result<()> {
+        let summary = self.summary();
+        match output {
+            OutputFormatType::JSON => {
+                writeln!(writer)?;
+                serde_json::to_writer_pretty(
+                    &mut *writer,
+                    &SummaryDocument {
+                        run_summary: summary,
+                    },
+                )?;
+                writeln!(writer)?;
+            }
+            OutputFormatType::YAML => {
+                writeln!(writer, "---")?;
+                serde_yaml::to_writer(
+                    writer,
+                    &SummaryDocument {
+                        run_summary: summary,
+                    },
+                )?;
+            }
+            _ => write_text(&summary, writer)?,
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> Summary<'_> {
+        let mut most_failed_data_files = self
+            .data_files
+            .iter()
+            .filter(|(_, failed_rules)| **failed_rules > 0)
+            .map(|(data_file, failed_rules)| DataFileSummary {
+                data_file,
+                failed_rules: *failed_rules,
+            })
+            .collect::<Vec<_>>();
+        most_failed_data_files.sort_by_key(|each| std::cmp::Reverse(each.failed_rules));
+        most_failed_data_files.truncate(MOST_FAILED_DATA_FILES);
+
+        Summary {
+            rules: self
+                .rules
+                .iter()
+                .map(|((rules_file, rule), counts)| RuleSummary {
+                    rules_file,
+                    rule,
+                    counts: *counts,
+                })
+                .collect(),
+            most_failed_data_files,
+            always_skipped_rules: self
+                .rules
+                .iter()
+                .filter(|(_, counts)| counts.pass == 0 && counts.fail == 0)
+                .map(|((rules_file, rule), _)| RuleName { rules_file, rule })
+                .collect(),
+        }
+    }
+}
+
+fn write_text(summary: &Summary<'_>, writer: &mut dyn Write) -> Result<()> {
+    let longest = summary
+        .rules
+        .iter()
+        .map(|each| each.rules_file.len() + each.rule.len() + 1)
+        .chain(
+            summary
+                .most_failed_data_files
+                .iter()
+                .map(|each| each.data_file.len()),
+        )
+        .max()
+        .unwrap_or(0);
+
+    writeln!(writer, "{}", "Run summary".bold())?;
+    writeln!(
+        writer,
+        "{rule:<width$}{pass:>6}{fail:>6}{skip:>6}",
+        rule = "Rule",
+        pass = "PASS",
+        fail = "FAIL",
+        skip = "SKIP",
+        width = longest + 4
+    )?;
+    for each in &summary.rules {
+        writeln!(
+            writer,
+            "{rule:<width$}{pass:>6}{fail:>6}{skip:>6}",
+            rule = format!("{}/{}", each.rules_file, each.rule),
+            pass = each.counts.pass,
+            fail = each.counts.fail,
+            skip = each.counts.skip,
+            width = longest + 4
+        )?;
+    }
+
+    if !summary.most_failed_data_files.is_empty() {
+        writeln!(writer, "{}", "Data files with the most failed rules".bold())?;
+        for each in &summary.most_failed_data_files {
+            writeln!(
+                writer,
+                "{data_file:<width$}{failed_rules}",
+                data_file = each.data_file,
+                failed_rules = each.failed_rules,
+                width = longest + 4
+            )?;
+        }
+    }
+
+    if !summary.always_skipped_rules.is_empty() {
+        writeln!(writer, "{}", "Rules that were always SKIP".bold())?;
+        for each in &summary.always_skipped_rules {
+            writeln!(writer, "{}/{}", each.rules_file, each.rule)?;
+        }
+    }
+    Ok(())
+}
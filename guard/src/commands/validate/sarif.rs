@@ -0,0 +1,222 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::commands::validate::common::collect_clause_failures;
+use crate::commands::validate::data_file_error::DataFileError;
+use crate::commands::{APP_NAME, APP_VERSION};
+use crate::rules::eval_context::FileReport;
+use crate::rules::path_value::PathAwareValue;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const INFORMATION_URI: &str = "https://github.com/aws-cloudformation/cloudformation-guard";
+
+//
+// Collects the failing clauses of every (rules file, data file) pair evaluated in a run. Code
+// scanning tools expect a single log per upload, so the log is only written out once all
+// pairs have been evaluated. Data files that could not be parsed are tool execution
+// notifications of the run's invocation
+//
+#[derive(Debug, Default)]
+pub(crate) struct SarifLog {
+    results: Vec<SarifResult>,
+    notifications: Vec<Notification>,
+}
+
+#[derive(Debug, Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Run<'a> {
+    tool: Tool<'a>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    invocations: Vec<Invocation<'a>>,
+    results: &'a [SarifResult],
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation<'a> {
+    execution_successful: bool,
+    tool_execution_notifications: &'a [Notification],
+}
+
+#[derive(Debug, Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message,
+    locations: Vec<ResultLocation>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool<'a> {
+    driver: Driver<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver<'a> {
+    name: &'static str,
+    semantic_version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportingDescriptor<'a> {
+    id: &'a str,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    level: &'static str,
+    message: Message,
+    locations: Vec<ResultLocation>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultLocation {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+}
+
+impl SarifLog {
+    pub(crate) fn new() -> Self {
+        SarifLog::default()
+    }
+
+    pub(crate) fn add_report(&mut self, report: &FileReport<'_>, data_file: &str) {
+        for failure in collect_clause_failures(report) {
+            let result = SarifResult {
+                rule_id: failure.rule_name.to_string(),
+                level: "error",
+                message: Message {
+                    text: failure.message,
+                },
+                locations: vec![ResultLocation {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation {
+                            uri: data_file.replace('\\', "/"),
+                        },
+                        region: failure.value.and_then(region_of),
+                    },
+                }],
+            };
+            if !self.results.contains(&result) {
+                self.results.push(result);
+            }
+        }
+    }
+
+    pub(crate) fn add_data_file_error(&mut self, error: &DataFileError) {
+        self.notifications.push(Notification {
+            level: "error",
+            message: Message {
+                text: error.message.clone(),
+            },
+            locations: vec![ResultLocation {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation {
+                        uri: error.path.replace('\\', "/"),
+                    },
+                    region: error.line.zip(error.column).map(|(line, column)| Region {
+                        start_line: line,
+                        start_column: column,
+                    }),
+                },
+            }],
+        });
+    }
+
+    pub(crate) fn append(&mut self, other: SarifLog) {
+        for each in other.results {
+            if !self.results.contains(&each) {
+                self.results.push(each);
+            }
+        }
+        self.notifications.extend(other.notifications);
+    }
+
+    pub(crate) fn write(&self, writer: &mut dyn Write) -> crate::rules::Result<()> {
+        let mut rules: Vec<ReportingDescriptor<'_>> = Vec::new();
+        for each in &self.results {
+            if !rules.iter().any(|rule| rule.id == each.rule_id) {
+                rules.push(ReportingDescriptor { id: &each.rule_id });
+            }
+        }
+
+        let log = Log {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: APP_NAME,
+                        semantic_version: APP_VERSION,
+                        information_uri: INFORMATION_URI,
+                        rules,
+                    },
+                },
+                invocations: match self.notifications.is_empty() {
+                    true => vec![],
+                    false => vec![Invocation {
+                        execution_successful: false,
+                        tool_execution_notifications: &self.notifications,
+                    }],
+                },
+                results: &self.results,
+            }],
+        };
+        serde_json::to_writer_pretty(&mut *writer, &log)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+//
+// Lines from the data file loader start at 1 and columns at 0, SARIF regions are 1 based for
+// both. Values that were not read from the data file (line 0) have no region
+//
+fn region_of(value: &PathAwareValue) -> Option<Region> {
+    let location = &value.self_path().1;
+    if location.line == 0 {
+        return None;
+    }
+    Some(Region {
+        start_line: location.line,
+        start_column: location.col + 1,
+    })
+}
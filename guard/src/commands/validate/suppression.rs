@@ -0,0 +1,409 @@
+use std::io::Write;
+
+use colored::*;
+use serde::Serialize;
+
+use crate::commands::validate::common::{is_within, update_file_status};
+use crate::commands::validate::OutputFormatType;
+use crate::commands::value_path::{keys, value_at};
+use crate::rules::eval_context::EventRecord;
+use crate::rules::path_value::PathAwareValue;
+use crate::rules::{ClauseCheck, NamedStatus, QueryResult, RecordType, Result, Status};
+use crate::utils::writer::Writer;
+
+const SUPPRESSED_RULES_TAG: &str = "guard:SuppressedRules";
+const SUPPRESSION_REASON_TAG: &str = "guard:SuppressionReason";
+
+//
+// Resources can opt out of rules in their own definition. CloudFormation resources list the
+// rules in their Metadata, each with an optional reason
+//
+//   Metadata:
+//     guard:
+//       SuppressedRules:
+//         - id: S3_BUCKET_LOGGING_ENABLED
+//           reason: Access logs are collected by the proxy in front of the bucket
+//
+// Terraform resources use a guard:SuppressedRules tag with the rule names separated by spaces,
+// and an optional guard:SuppressionReason tag. Failures of a suppressed rule on the resource
+// are removed before the rule status is reported, the rule still fails for other resources.
+// Failures of rules that depend on a suppressed rule, through a named rule clause, are removed
+// when the suppressed rule no longer fails. Failures that point at no value, such as an empty
+// check whose query selects nothing, cannot be tied to a resource and are kept
+//
+#[derive(Debug, Default)]
+pub(crate) struct InlineSuppressions {
+    disallowed: bool,
+    suppressed: Vec<Suppressed>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Suppressed {
+    rules_file: String,
+    data_file: String,
+    rule: String,
+    resource: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Debug)]
+struct ResourceSuppression<'value> {
+    path: String,
+    resource: &'value str,
+    rules: Vec<(&'value str, Option<&'value str>)>,
+}
+
+#[derive(Debug, Serialize)]
+struct SuppressionsDocument<'a> {
+    suppressed_by_resources: &'a [Suppressed],
+}
+
+impl InlineSuppressions {
+    pub(crate) fn new(disallowed: bool) -> Self {
+        InlineSuppressions {
+            disallowed,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn new_like(&self) -> Self {
+        InlineSuppressions::new(self.disallowed)
+    }
+
+    //
+    // Removes the failures suppressed by resources from the record of the rules file and data
+    // file pair and returns the status derived from what is left. None when no failure was
+    // suppressed, or when suppressions are disallowed, which only records them
+    //
+    pub(crate) fn apply<'value>(
+        &mut self,
+        rules_file: &str,
+        data_file: &str,
+        data: &PathAwareValue,
+        root_record: &EventRecord<'value>,
+    ) -> Result<Option<(Status, EventRecord<'value>)>> {
+        let resources = resource_suppressions(data);
+        if resources.is_empty() {
+            return Ok(None);
+        }
+
+        let mut remaining = root_record.clone();
+        let mut suppressed = Vec::new();
+        let mut cleared = Vec::new();
+        for rule in remaining.children.iter_mut() {
+            let name = match failed_rule(rule) {
+                Some(name) => name,
+                None => continue,
+            };
+            let suppressing = resources
+                .iter()
+                .flat_map(|resource| {
+                    resource
+                        .rules
+                        .iter()
+                        .filter(|(id, _)| *id == name)
+                        .map(move |(_, reason)| (resource, *reason))
+                })
+                .collect::<Vec<_>>();
+            if suppressing.is_empty() {
+                continue;
+            }
+
+            let mut used = vec![false; suppressing.len()];
+            prune(
+                rule,
+                &mut |check| match failed_path(check).and_then(|path| {
+                    suppressing
+                        .iter()
+                        .position(|(resource, _)| is_within(path, &resource.path))
+                }) {
+                    Some(index) => {
+                        used[index] = true;
+                        true
+                    }
+                    None => false,
+                },
+            );
+            if status_of(rule) == Some(Status::PASS) {
+                cleared.push(name);
+            }
+            for ((resource, reason), _) in suppressing.iter().zip(used).filter(|(_, used)| *used) {
+                suppressed.push(Suppressed {
+                    rules_file: rules_file.to_string(),
+                    data_file: data_file.to_string(),
+                    rule: name.to_string(),
+                    resource: resource.resource.to_string(),
+                    reason: reason.map(String::from),
+                });
+            }
+        }
+
+        //
+        // Rules that no longer fail stop failing the rules that depend on them, which can clear
+        // those in turn
+        //
+        while !cleared.is_empty() {
+            let mut next = Vec::new();
+            for rule in remaining.children.iter_mut() {
+                let name = match failed_rule(rule) {
+                    Some(name) => name,
+                    None => continue,
+                };
+                prune(rule, &mut |check| {
+                    matches!(check, ClauseCheck::DependentRule(dependent)
+                        if dependent.status == Status::FAIL && cleared.contains(&dependent.rule))
+                });
+                if status_of(rule) == Some(Status::PASS) {
+                    next.push(name);
+                }
+            }
+            cleared = next;
+        }
+
+        if suppressed.is_empty() {
+            return Ok(None);
+        }
+        self.suppressed.extend(suppressed);
+        if self.disallowed {
+            return Ok(None);
+        }
+        let status = update_file_status(&mut remaining);
+        Ok(Some((status, remaining)))
+    }
+
+    pub(crate) fn append(&mut self, other: InlineSuppressions) {
+        self.suppressed.extend(other.suppressed)
+    }
+
+    //
+    // Suppressions are listed after the reports, or on stderr when they were disallowed
+    //
+    pub(crate) fn write(&self, output: OutputFormatType, writer: &mut Writer) -> Result<()> {
+        if self.suppressed.is_empty() {
+            return Ok(());
+        }
+
+        if self.disallowed {
+            for each in &self.suppressed {
+                writer.write_err(format!(
+                    "Suppression of rule {}/{} by resource {} in data file {} is not allowed",
+                    each.rules_file, each.rule, each.resource, each.data_file
+                ))?;
+            }
+            return Ok(());
+        }
+
+        let document = SuppressionsDocument {
+            suppressed_by_resources: &self.suppressed,
+        };
+        match output {
+            OutputFormatType::JSON => {
+                writeln!(writer)?;
+                serde_json::to_writer_pretty(&mut *writer, &document)?;
+                writeln!(writer)?;
+            }
+            OutputFormatType::YAML => {
+                writeln!(writer, "---")?;
+                serde_yaml::to_writer(writer, &document)?;
+            }
+            _ => {
+                writeln!(writer, "{}", "Suppressed by resources".bold())?;
+                for each in &self.suppressed {
+                    write!(
+                        writer,
+                        "rule {}/{} on resource {} in data file {}",
+                        each.rules_file, each.rule, each.resource, each.data_file
+                    )?;
+                    match &each.reason {
+                        Some(reason) => writeln!(writer, ", {reason}")?,
+                        None => writeln!(writer)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn failed_rule<'value>(record: &EventRecord<'value>) -> Option<&'value str> {
+    match &record.container {
+        Some(RecordType::RuleCheck(NamedStatus {
+            name,
+            status: Status::FAIL,
+            ..
+        })) => Some(*name),
+        _ => None,
+    }
+}
+
+//
+// Drops the failed clause checks the predicate matches, checks that failed only because of them
+// are marked as passed. Returns whether anything was dropped underneath the record
+//
+fn prune<'value>(
+    record: &mut EventRecord<'value>,
+    suppressed: &mut dyn FnMut(&ClauseCheck<'value>) -> bool,
+) -> bool {
+    let mut pruned = false;
+    record.children.retain_mut(|child| match &child.container {
+        Some(RecordType::ClauseValueCheck(check)) if suppressed(check) => {
+            pruned = true;
+            false
+        }
+        Some(RecordType::ClauseValueCheck(_)) => true,
+        _ => {
+            pruned |= prune(child, suppressed);
+            true
+        }
+    });
+
+    if pruned
+        && status_of(record) == Some(Status::FAIL)
+        && !record
+            .children
+            .iter()
+            .any(|child| status_of(child) == Some(Status::FAIL))
+    {
+        set_status(record, Status::PASS);
+    }
+    pruned
+}
+
+fn failed_path<'value>(check: &ClauseCheck<'value>) -> Option<&'value str> {
+    let (status, from, to) = match check {
+        ClauseCheck::Comparison(check) => (check.status, &check.from, check.to.as_ref()),
+        ClauseCheck::InComparison(check) => (check.status, &check.from, check.to.first()),
+        ClauseCheck::Unary(check) => (check.value.status, &check.value.from, None),
+        ClauseCheck::MissingBlockValue(check) => (check.status, &check.from, None),
+        _ => return None,
+    };
+    if status != Status::FAIL {
+        return None;
+    }
+    std::iter::once(from)
+        .chain(to)
+        .find_map(|each| match each {
+            QueryResult::Resolved(value) => Some(*value),
+            QueryResult::UnResolved(unresolved) => Some(unresolved.traversed_to),
+            QueryResult::Literal(_) => None,
+        })
+        .map(|value| value.self_path().0.as_str())
+}
+
+fn status_of(record: &EventRecord<'_>) -> Option<Status> {
+    Some(match record.container.as_ref()? {
+        RecordType::FileCheck(named) | RecordType::RuleCheck(named) => named.status,
+        RecordType::RuleCondition(status)
+        | RecordType::TypeCondition(status)
+        | RecordType::TypeBlock(status)
+        | RecordType::Filter(status)
+        | RecordType::WhenCondition(status) => *status,
+        RecordType::TypeCheck(check) => check.block.status,
+        RecordType::WhenCheck(check)
+        | RecordType::Disjunction(check)
+        | RecordType::BlockGuardCheck(check)
+        | RecordType::GuardClauseBlockCheck(check) => check.status,
+        RecordType::ClauseValueCheck(check) => match check {
+            ClauseCheck::Success => Status::PASS,
+            ClauseCheck::NoValueForEmptyCheck(_) => Status::FAIL,
+            ClauseCheck::Comparison(check) => check.status,
+            ClauseCheck::InComparison(check) => check.status,
+            ClauseCheck::Unary(check) => check.value.status,
+            ClauseCheck::DependentRule(check) => check.status,
+            ClauseCheck::MissingBlockValue(check) => check.status,
+        },
+    })
+}
+
+fn set_status(record: &mut EventRecord<'_>, to: Status) {
+    match &mut record.container {
+        Some(RecordType::FileCheck(named) | RecordType::RuleCheck(named)) => named.status = to,
+        Some(
+            RecordType::RuleCondition(status)
+            | RecordType::TypeCondition(status)
+            | RecordType::TypeBlock(status)
+            | RecordType::Filter(status)
+            | RecordType::WhenCondition(status),
+        ) => *status = to,
+        Some(RecordType::TypeCheck(check)) => check.block.status = to,
+        Some(
+            RecordType::WhenCheck(check)
+            | RecordType::Disjunction(check)
+            | RecordType::BlockGuardCheck(check)
+            | RecordType::GuardClauseBlockCheck(check),
+        ) => check.status = to,
+        Some(RecordType::ClauseValueCheck(_)) | None => {}
+    }
+}
+
+fn resource_suppressions(data: &PathAwareValue) -> Vec<ResourceSuppression<'_>> {
+    let mut suppressions = Vec::new();
+    if let Some(resources) = map_at(data, &["Resources"]) {
+        for (name, resource) in resources {
+            let rules = match value_at(resource, &keys(&["Metadata", "guard", "SuppressedRules"])) {
+                Some(PathAwareValue::List((_, rules))) => rules
+                    .iter()
+                    .filter_map(|each| match each {
+                        PathAwareValue::String((_, id)) => Some((id.as_str(), None)),
+                        _ => match value_at(each, &keys(&["id"])) {
+                            Some(PathAwareValue::String((_, id))) => {
+                                Some((id.as_str(), string_at(each, &["reason"])))
+                            }
+                            _ => None,
+                        },
+                    })
+                    .collect::<Vec<_>>(),
+                _ => continue,
+            };
+            suppressions.push(ResourceSuppression {
+                path: format!("/Resources/{name}"),
+                resource: name,
+                rules,
+            });
+        }
+    }
+
+    if let Some(PathAwareValue::List((_, changes))) = value_at(data, &keys(&["resource_changes"])) {
+        for (index, change) in changes.iter().enumerate() {
+            let (address, tags) = match (
+                string_at(change, &["address"]),
+                map_at(change, &["change", "after", "tags"]),
+            ) {
+                (Some(address), Some(tags)) => (address, tags),
+                _ => continue,
+            };
+            let rules = match tags.get(SUPPRESSED_RULES_TAG) {
+                Some(PathAwareValue::String((_, rules))) => rules,
+                _ => continue,
+            };
+            let reason = match tags.get(SUPPRESSION_REASON_TAG) {
+                Some(PathAwareValue::String((_, reason))) => Some(reason.as_str()),
+                _ => None,
+            };
+            suppressions.push(ResourceSuppression {
+                path: format!("/resource_changes/{index}"),
+                resource: address,
+                rules: rules.split_whitespace().map(|id| (id, reason)).collect(),
+            });
+        }
+    }
+    suppressions
+}
+
+fn map_at<'value>(
+    value: &'value PathAwareValue,
+    path: &[&str],
+) -> Option<&'value indexmap::IndexMap<String, PathAwareValue>> {
+    match value_at(value, &keys(path))? {
+        PathAwareValue::Map((_, map)) => Some(&map.values),
+        _ => None,
+    }
+}
+
+fn string_at<'value>(value: &'value PathAwareValue, path: &[&str]) -> Option<&'value str> {
+    match value_at(value, &keys(path))? {
+        PathAwareValue::String((_, string)) => Some(string),
+        _ => None,
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+
+use crate::commands::files::{regular_ordering, FileOrdering, FileSelector};
+use crate::commands::rules_cache::{parse_rules, RulesCache};
+use crate::commands::validate::common::rule_statuses;
+use crate::commands::validate::data_file_error::DataFileError;
+use crate::commands::validate::rule_selection::RuleSelection;
+use crate::commands::validate::suppression::InlineSuppressions;
+use crate::commands::validate::{get_path_aware_value_from_data, rules_files, validate_path};
+use crate::commands::watch::{Evaluated, Statuses, Watched, WatchedFiles};
+use crate::rules::eval::eval_rules_file;
+use crate::rules::eval_context::root_scope;
+use crate::rules::path_value::PathAwareValue;
+use crate::rules::Result;
+
+//
+// Files for validate --watch, every rules file is evaluated against every data file, input
+// parameters are merged into each data file and shared by all pairs. Rules suppressed by
+// resources are left out of the statuses like in a single run
+//
+pub(crate) struct ValidateWatch<'a> {
+    pub(crate) rules: Vec<&'a str>,
+    pub(crate) data: Vec<&'a str>,
+    pub(crate) input_parameters: Vec<&'a str>,
+    pub(crate) rules_selector: FileSelector,
+    pub(crate) data_selector: FileSelector,
+    pub(crate) rule_selection: RuleSelection,
+    pub(crate) cmp: FileOrdering,
+    pub(crate) rules_cache: Option<RulesCache>,
+    pub(crate) disallow_suppressions: bool,
+}
+
+impl ValidateWatch<'_> {
+    fn data_files(&self, list_of_file_or_dir: &[&str]) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for file_or_dir in list_of_file_or_dir {
+            validate_path(FileSelector::base(file_or_dir))?;
+            files.extend(self.data_selector.select(file_or_dir, regular_ordering)?);
+        }
+        Ok(files)
+    }
+
+    fn input_parameters(&self) -> std::result::Result<Option<PathAwareValue>, String> {
+        let mut merged: Option<PathAwareValue> = None;
+        for file in self
+            .data_files(&self.input_parameters)
+            .map_err(|e| e.to_string())?
+        {
+            let path_value = std::fs::read_to_string(&file)
+                .map_err(crate::rules::errors::Error::from)
+                .and_then(|content| get_path_aware_value_from_data(&content))
+                .map_err(|e| format!("Error occurred {e}"))?;
+            merged = match merged {
+                Some(current) => Some(current.merge(path_value).map_err(|e| e.to_string())?),
+                None => Some(path_value),
+            };
+        }
+        Ok(merged)
+    }
+}
+
+impl Watched for ValidateWatch<'_> {
+    fn discover(&self) -> Result<WatchedFiles> {
+        let data_files = self.data_files(&self.data)?;
+        Ok(WatchedFiles {
+            pairs: rules_files(self.rules.iter().copied(), &self.rules_selector, self.cmp)?
+                .into_iter()
+                .map(|rules_file| (rules_file, data_files.clone()))
+                .collect(),
+            shared: self.data_files(&self.input_parameters)?,
+        })
+    }
+
+    fn evaluate(&self, rules_file: &Path, data_files: &[PathBuf]) -> Evaluated {
+        let content = std::fs::read_to_string(rules_file)
+            .map_err(|e| format!("Unable read content from file {e}"))?;
+        let rules_file_name = rules_file
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+        let rules =
+            parse_rules(&content, &rules_file_name, self.rules_cache.as_ref()).map_err(|e| {
+                format!("Parsing error handling rule file = {rules_file_name}, Error = {e}\n---")
+            })?;
+        let rules = self.rule_selection.select(rules, &content);
+        let extra_data = self.input_parameters()?;
+
+        Ok(data_files
+            .iter()
+            .map(|data_file| {
+                let path = format!("{}", data_file.display());
+                let content = std::fs::read_to_string(data_file).map_err(|e| e.to_string())?;
+                let path_value = get_path_aware_value_from_data(&content).map_err(|e| {
+                    format!("{}\n---", DataFileError::new(&path, &path, &content, e))
+                })?;
+                let each = match &extra_data {
+                    Some(data) => data.clone().merge(path_value),
+                    None => Ok(path_value),
+                }
+                .map_err(|e| e.to_string())?;
+                let evaluated = || -> Result<Statuses> {
+                    let mut root_scope = root_scope(&rules.rules_file, &each)?;
+                    eval_rules_file(rules.evaluated(), &mut root_scope)?;
+                    let root_record = root_scope.reset_recorder().extract();
+                    let root_record = match InlineSuppressions::new(self.disallow_suppressions)
+                        .apply(&rules_file_name, &path, &each, &root_record)?
+                    {
+                        Some((_, unsuppressed)) => unsuppressed,
+                        None => root_record,
+                    };
+                    Ok(rule_statuses(&root_record)
+                        .into_iter()
+                        .map(|(rule, status)| (rule.to_string(), status))
+                        .collect())
+                };
+                evaluated().map_err(|e| format!("Error evaluating {path}, {e}"))
+            })
+            .collect())
+    }
+}
@@ -1,5 +1,6 @@
 use super::super::*;
 use super::*;
+use crate::commands::watch::Watched;
 
 #[test]
 fn test_deserialize_payload_success() {
@@ -39,52 +40,132 @@ fn test_deserialize_payload_empty_input() {
 #[test]
 fn test_supported_extensions() {
     // Data extensions
-    assert!(has_a_supported_extension(
-        &"blah.json".to_string(),
-        &DATA_FILE_SUPPORTED_EXTENSIONS
-    ));
-    assert!(has_a_supported_extension(
-        &"blah.jsn".to_string(),
-        &DATA_FILE_SUPPORTED_EXTENSIONS
-    ));
-    assert!(has_a_supported_extension(
-        &"blah.yaml".to_string(),
-        &DATA_FILE_SUPPORTED_EXTENSIONS
-    ));
-    assert!(has_a_supported_extension(
-        &"blah.template".to_string(),
-        &DATA_FILE_SUPPORTED_EXTENSIONS
-    ));
-    assert!(has_a_supported_extension(
-        &"blah.yml".to_string(),
-        &DATA_FILE_SUPPORTED_EXTENSIONS
-    ));
+    let data = FileSelector::new(DATA_FILE_SUPPORTED_EXTENSIONS, []).unwrap();
+    assert!(data.has_extension("blah.json"));
+    assert!(data.has_extension("blah.jsn"));
+    assert!(data.has_extension("blah.yaml"));
+    assert!(data.has_extension("blah.template"));
+    assert!(data.has_extension("blah.yml"));
     // unsupported
-    assert!(!has_a_supported_extension(
-        &"blah.txt".to_string(),
-        &DATA_FILE_SUPPORTED_EXTENSIONS
-    ));
-    assert!(!has_a_supported_extension(
-        &"blah".to_string(),
-        &DATA_FILE_SUPPORTED_EXTENSIONS
-    ));
+    assert!(!data.has_extension("blah.txt"));
+    assert!(!data.has_extension("blah"));
 
     // Rules extensions
-    assert!(has_a_supported_extension(
-        &"blah.guard".to_string(),
-        &RULE_FILE_SUPPORTED_EXTENSIONS
-    ));
-    assert!(has_a_supported_extension(
-        &"blah.ruleset".to_string(),
-        &RULE_FILE_SUPPORTED_EXTENSIONS
-    ));
+    let rules = FileSelector::new(RULE_FILE_SUPPORTED_EXTENSIONS, []).unwrap();
+    assert!(rules.has_extension("blah.guard"));
+    assert!(rules.has_extension("blah.ruleset"));
     // unsupported
-    assert!(!has_a_supported_extension(
-        &"blah.txt".to_string(),
-        &RULE_FILE_SUPPORTED_EXTENSIONS
-    ));
-    assert!(!has_a_supported_extension(
-        &"blah".to_string(),
-        &RULE_FILE_SUPPORTED_EXTENSIONS
-    ));
+    assert!(!rules.has_extension("blah.txt"));
+    assert!(!rules.has_extension("blah"));
+
+    // Custom extensions, with or without the leading dot
+    let custom = FileSelector::new(["template.json", ".cfn"], []).unwrap();
+    assert!(custom.has_extension("stack.template.json"));
+    assert!(custom.has_extension("stack.cfn"));
+    assert!(!custom.has_extension("stack.json"));
+}
+
+#[test]
+fn test_file_selector_base() {
+    assert_eq!(FileSelector::base("stacks"), "stacks");
+    assert_eq!(FileSelector::base("stacks/**/*.template.json"), "stacks");
+    assert_eq!(FileSelector::base("./stacks/prod-*/*.json"), "./stacks");
+    assert_eq!(FileSelector::base("*.json"), ".");
+    assert_eq!(FileSelector::base("/*.json"), "/");
+}
+
+#[test]
+fn test_file_selector_excludes() {
+    let selector = FileSelector::new(
+        DATA_FILE_SUPPORTED_EXTENSIONS,
+        ["stacks/sandbox/**", "*.bak.json", "generated"],
+    )
+    .unwrap();
+    let base = Path::new("stacks");
+    assert!(selector.is_excluded(Path::new("stacks/sandbox/a.json"), base));
+    assert!(selector.is_excluded(Path::new("./stacks/sandbox/a.json"), base));
+    assert!(selector.is_excluded(Path::new("stacks/prod/a.bak.json"), base));
+    assert!(selector.is_excluded(Path::new("stacks/generated"), base));
+    assert!(!selector.is_excluded(Path::new("stacks/prod/a.json"), base));
+    assert!(!selector.is_excluded(Path::new("stacks/sandbox.json"), base));
+
+    assert!(FileSelector::new(DATA_FILE_SUPPORTED_EXTENSIONS, ["stacks/[a"]).is_err());
+}
+
+#[test]
+fn test_validate_watch_pairs_and_statuses() {
+    let rules_selector = FileSelector::new(RULE_FILE_SUPPORTED_EXTENSIONS, [""; 0]).unwrap();
+    let data_selector =
+        FileSelector::new(DATA_FILE_SUPPORTED_EXTENSIONS, ["*-non-compliant.yaml"]).unwrap();
+    let watched = ValidateWatch {
+        rules: vec!["resources/validate/rule-selection"],
+        data: vec!["resources/validate/data-dir/s3-server-side-encryption-*"],
+        input_parameters: vec![],
+        rules_selector,
+        data_selector,
+        rule_selection: RuleSelection::new([], ["legacy_*"], []).unwrap(),
+        cmp: alpabetical,
+        rules_cache: None,
+        disallow_suppressions: false,
+    };
+
+    let files = watched.discover().unwrap();
+    let rules_file = PathBuf::from("resources/validate/rule-selection/s3_bucket_rules.guard");
+    let data_file = PathBuf::from(
+        "resources/validate/data-dir/s3-server-side-encryption-template-compliant.yaml",
+    );
+    assert_eq!(
+        files.pairs,
+        vec![(rules_file.clone(), vec![data_file.clone()])]
+    );
+    assert!(files.shared.is_empty());
+
+    let evaluated = watched.evaluate(&rules_file, &[data_file]).unwrap();
+    assert_eq!(
+        evaluated,
+        vec![Ok(vec![
+            ("s3_bucket_encryption_configured".to_string(), Status::PASS),
+            ("s3_bucket_logging_enabled".to_string(), Status::FAIL),
+            ("s3_bucket_sse_enabled".to_string(), Status::PASS),
+        ]
+        .into_iter()
+        .collect())]
+    );
+}
+
+#[test]
+fn test_validate_watch_leaves_out_suppressed_rules() {
+    let rules_file =
+        PathBuf::from("resources/validate/suppressions/s3_bucket_encryption_dependent.guard");
+    let data_file =
+        PathBuf::from("resources/validate/suppressions/s3-buckets-with-suppressed-encryption.yaml");
+    let statuses = |disallow_suppressions, status| {
+        let watched = ValidateWatch {
+            rules: vec![],
+            data: vec![],
+            input_parameters: vec![],
+            rules_selector: FileSelector::new(RULE_FILE_SUPPORTED_EXTENSIONS, [""; 0]).unwrap(),
+            data_selector: FileSelector::new(DATA_FILE_SUPPORTED_EXTENSIONS, [""; 0]).unwrap(),
+            rule_selection: RuleSelection::new([""; 0], [""; 0], [""; 0]).unwrap(),
+            cmp: alpabetical,
+            rules_cache: None,
+            disallow_suppressions,
+        };
+        let evaluated = watched
+            .evaluate(&rules_file, std::slice::from_ref(&data_file))
+            .unwrap();
+        let expected = vec![
+            ("S3_BUCKET_COMPLIANT".to_string(), status),
+            (
+                "S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED_2".to_string(),
+                status,
+            ),
+        ];
+        (evaluated, vec![Ok(expected.into_iter().collect())])
+    };
+
+    let (evaluated, expected) = statuses(false, Status::PASS);
+    assert_eq!(evaluated, expected);
+    let (evaluated, expected) = statuses(true, Status::FAIL);
+    assert_eq!(evaluated, expected);
 }
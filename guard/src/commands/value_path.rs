@@ -0,0 +1,68 @@
+use serde_yaml::Value;
+
+use crate::rules::path_value::PathAwareValue;
+
+//
+// A path to a value inside a document, one step for each map key or list index from the root.
+// Documents read by serde_yaml can also be changed at a path, evaluated documents are only looked
+// into
+//
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Step {
+    Key(Value),
+    Index(usize),
+}
+
+pub(crate) trait Document: Sized {
+    fn child(&self, step: &Step) -> Option<&Self>;
+}
+
+impl Document for Value {
+    fn child(&self, step: &Step) -> Option<&Self> {
+        match step {
+            Step::Key(key) => self.as_mapping()?.get(key),
+            Step::Index(index) => self.as_sequence()?.get(*index),
+        }
+    }
+}
+
+impl Document for PathAwareValue {
+    fn child(&self, step: &Step) -> Option<&Self> {
+        match (self, step) {
+            (PathAwareValue::Map((_, map)), Step::Key(key)) => map.values.get(key.as_str()?),
+            (PathAwareValue::List((_, list)), Step::Index(index)) => list.get(*index),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn keys(keys: &[&str]) -> Vec<Step> {
+    keys.iter()
+        .map(|key| Step::Key(Value::from(*key)))
+        .collect()
+}
+
+pub(crate) fn value_at<'v, D: Document>(value: &'v D, path: &[Step]) -> Option<&'v D> {
+    path.iter().try_fold(value, |value, step| value.child(step))
+}
+
+pub(crate) fn value_at_mut<'v>(value: &'v mut Value, path: &[Step]) -> Option<&'v mut Value> {
+    path.iter().try_fold(value, |value, step| match step {
+        Step::Key(key) => value.as_mapping_mut()?.get_mut(key),
+        Step::Index(index) => value.as_sequence_mut()?.get_mut(*index),
+    })
+}
+
+pub(crate) fn remove(value: &mut Value, path: &[Step]) {
+    if let Some((last, parent)) = path.split_last() {
+        match (value_at_mut(value, parent), last) {
+            (Some(Value::Mapping(map)), Step::Key(key)) => {
+                map.remove(key);
+            }
+            (Some(Value::Sequence(list)), Step::Index(index)) if *index < list.len() => {
+                list.remove(*index);
+            }
+            _ => {}
+        }
+    }
+}
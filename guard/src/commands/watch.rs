@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::rules::{Result, Status};
+use crate::utils::writer::Writer;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+//
+// Files a watch run looks at. Each rules file is paired with the data files it is evaluated
+// against. Changing a rules file re-runs all of its pairs, changing a data file re-runs the pairs
+// it is part of, and changing a shared file, like input parameters, re-runs every pair
+//
+#[derive(Debug, Default)]
+pub(crate) struct WatchedFiles {
+    pub(crate) pairs: Vec<(PathBuf, Vec<PathBuf>)>,
+    pub(crate) shared: Vec<PathBuf>,
+}
+
+//
+// Status of each rule of a pair, keyed by the rule name for validate and by the test case and
+// rule name for test
+//
+pub(crate) type Statuses = BTreeMap<String, Status>;
+
+//
+// Error messages for a rules file, or for a single data file, that could not be evaluated. The
+// statuses of the affected pairs are kept from the previous run, so fixing the file shows the
+// changes against the last good run
+//
+pub(crate) type Evaluated = std::result::Result<Vec<std::result::Result<Statuses, String>>, String>;
+
+pub(crate) trait Watched {
+    fn discover(&self) -> Result<WatchedFiles>;
+    fn evaluate(&self, rules_file: &Path, data_files: &[PathBuf]) -> Evaluated;
+}
+
+type Stamp = Option<(SystemTime, u64)>;
+
+//
+// Polls the watched files for changes and re-runs only the affected (rules file, data file)
+// pairs, printing the rules whose status changed since the previous run
+//
+pub(crate) struct Watcher<'w> {
+    watched: &'w dyn Watched,
+    stamps: BTreeMap<PathBuf, Stamp>,
+    statuses: BTreeMap<(PathBuf, PathBuf), Statuses>,
+}
+
+impl<'w> Watcher<'w> {
+    pub(crate) fn new(watched: &'w dyn Watched) -> Self {
+        Watcher {
+            watched,
+            stamps: BTreeMap::new(),
+            statuses: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn run(mut self, writer: &mut Writer) -> Result<i32> {
+        self.start(writer)?;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            self.poll(writer)?;
+        }
+    }
+
+    //
+    // Evaluates every pair and lists the failed rules
+    //
+    pub(crate) fn start(&mut self, writer: &mut Writer) -> Result<()> {
+        let files = self.watched.discover()?;
+        self.stamps = stamps(&files);
+        for (rules_file, data_files) in &files.pairs {
+            for (data_file, statuses) in self.evaluate(rules_file, data_files, writer)? {
+                for (rule, status) in &statuses {
+                    if *status == Status::FAIL {
+                        writeln!(
+                            writer,
+                            "{} on {}: {status}",
+                            rule_display(rules_file, rule),
+                            data_file.display()
+                        )?;
+                    }
+                }
+                self.statuses
+                    .insert((rules_file.clone(), data_file), statuses);
+            }
+        }
+        writeln!(
+            writer,
+            "Watching {} rules files and {} data files, {}, waiting for changes",
+            files.pairs.len(),
+            files
+                .pairs
+                .iter()
+                .flat_map(|(_, data_files)| data_files)
+                .collect::<BTreeSet<_>>()
+                .len(),
+            self.failing()
+        )?;
+        Ok(())
+    }
+
+    //
+    // Re-runs the pairs affected by the files that changed since the last poll, returns whether
+    // anything changed
+    //
+    pub(crate) fn poll(&mut self, writer: &mut Writer) -> Result<bool> {
+        let files = match self.watched.discover() {
+            Ok(files) => files,
+            Err(e) => {
+                writeln!(writer, "Unable to find the watched files, {e}")?;
+                return Ok(false);
+            }
+        };
+        let stamps = stamps(&files);
+        let changed = stamps
+            .iter()
+            .filter(|(path, stamp)| self.stamps.get(*path) != Some(*stamp))
+            .map(|(path, _)| path.clone())
+            .chain(
+                self.stamps
+                    .keys()
+                    .filter(|path| !stamps.contains_key(*path))
+                    .cloned(),
+            )
+            .collect::<BTreeSet<_>>();
+        if changed.is_empty() {
+            return Ok(false);
+        }
+        self.stamps = stamps;
+
+        writeln!(
+            writer,
+            "Changed: {}",
+            changed
+                .iter()
+                .map(|path| format!("{}", path.display()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+
+        let current = files
+            .pairs
+            .iter()
+            .flat_map(|(rules_file, data_files)| {
+                data_files
+                    .iter()
+                    .map(move |data_file| (rules_file.clone(), data_file.clone()))
+            })
+            .collect::<BTreeSet<_>>();
+        let mut changes = 0;
+        let removed = self
+            .statuses
+            .keys()
+            .filter(|pair| !current.contains(*pair))
+            .cloned()
+            .collect::<Vec<_>>();
+        for (rules_file, data_file) in removed {
+            self.statuses
+                .remove(&(rules_file.clone(), data_file.clone()));
+            writeln!(
+                writer,
+                "{} on {}: removed",
+                rules_file.display(),
+                data_file.display()
+            )?;
+            changes += 1;
+        }
+
+        let shared_changed = files.shared.iter().any(|path| changed.contains(path));
+        let mut evaluated = 0;
+        for (rules_file, data_files) in &files.pairs {
+            let affected = if shared_changed || changed.contains(rules_file) {
+                data_files.clone()
+            } else {
+                data_files
+                    .iter()
+                    .filter(|data_file| changed.contains(*data_file))
+                    .cloned()
+                    .collect()
+            };
+            if affected.is_empty() {
+                continue;
+            }
+            evaluated += affected.len();
+            for (data_file, statuses) in self.evaluate(rules_file, &affected, writer)? {
+                let pair = (rules_file.clone(), data_file);
+                changes += write_changes(
+                    &pair,
+                    self.statuses.get(&pair).unwrap_or(&Statuses::new()),
+                    &statuses,
+                    writer,
+                )?;
+                self.statuses.insert(pair, statuses);
+            }
+        }
+
+        writeln!(
+            writer,
+            "Re-evaluated {evaluated} pairs, {}, {}",
+            match changes {
+                0 => "no status changes".to_string(),
+                1 => "1 status change".to_string(),
+                changes => format!("{changes} status changes"),
+            },
+            self.failing()
+        )?;
+        Ok(true)
+    }
+
+    fn evaluate(
+        &self,
+        rules_file: &Path,
+        data_files: &[PathBuf],
+        writer: &mut Writer,
+    ) -> Result<Vec<(PathBuf, Statuses)>> {
+        let mut evaluated = Vec::with_capacity(data_files.len());
+        match self.watched.evaluate(rules_file, data_files) {
+            Err(e) => writeln!(writer, "{e}")?,
+            Ok(results) => {
+                for (data_file, result) in data_files.iter().zip(results) {
+                    match result {
+                        Ok(statuses) => evaluated.push((data_file.clone(), statuses)),
+                        Err(e) => writeln!(writer, "{e}")?,
+                    }
+                }
+            }
+        }
+        Ok(evaluated)
+    }
+
+    fn failing(&self) -> String {
+        let failing = self
+            .statuses
+            .values()
+            .filter(|statuses| statuses.values().any(|status| *status == Status::FAIL))
+            .count();
+        format!("{failing} of {} pairs failing", self.statuses.len())
+    }
+}
+
+fn stamps(files: &WatchedFiles) -> BTreeMap<PathBuf, Stamp> {
+    files
+        .pairs
+        .iter()
+        .flat_map(|(rules_file, data_files)| std::iter::once(rules_file).chain(data_files))
+        .chain(&files.shared)
+        .map(|path| {
+            let stamp = std::fs::metadata(path)
+                .ok()
+                .and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+            (path.clone(), stamp)
+        })
+        .collect()
+}
+
+fn rule_display(rules_file: &Path, rule: &str) -> String {
+    format!("{}/{rule}", rules_file.display())
+}
+
+fn write_changes(
+    (rules_file, data_file): &(PathBuf, PathBuf),
+    previous: &Statuses,
+    current: &Statuses,
+    writer: &mut Writer,
+) -> Result<usize> {
+    let mut changes = 0;
+    let rules = previous
+        .keys()
+        .chain(current.keys())
+        .collect::<BTreeSet<_>>();
+    for rule in rules {
+        let change = match (previous.get(rule), current.get(rule)) {
+            (Some(before), Some(after)) if before != after => format!("{before} -> {after}"),
+            (None, Some(after)) => format!("{after} (new)"),
+            (Some(before), None) => format!("{before} -> removed"),
+            _ => continue,
+        };
+        writeln!(
+            writer,
+            "{} on {}: {change}",
+            rule_display(rules_file, rule),
+            data_file.display()
+        )?;
+        changes += 1;
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+#[path = "watch_tests.rs"]
+mod watch_tests;
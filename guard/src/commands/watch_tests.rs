@@ -0,0 +1,116 @@
+use super::*;
+use crate::utils::writer::WriteBuffer;
+use std::convert::TryFrom;
+
+//
+// Rules files list `rule status` lines, data files are evaluated with every rule of the rules
+// file unless they contain `error`
+//
+struct FakeWatched {
+    dir: PathBuf,
+}
+
+impl Watched for FakeWatched {
+    fn discover(&self) -> Result<WatchedFiles> {
+        let mut data_files = std::fs::read_dir(&self.dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "data"))
+            .collect::<Vec<_>>();
+        data_files.sort();
+        Ok(WatchedFiles {
+            pairs: vec![(self.dir.join("a.rules"), data_files)],
+            shared: vec![],
+        })
+    }
+
+    fn evaluate(&self, rules_file: &Path, data_files: &[PathBuf]) -> Evaluated {
+        let rules = std::fs::read_to_string(rules_file).map_err(|e| e.to_string())?;
+        let statuses = rules
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(rule, status)| (rule.to_string(), Status::try_from(status).unwrap()))
+            .collect::<Statuses>();
+        Ok(data_files
+            .iter()
+            .map(|data_file| match std::fs::read_to_string(data_file) {
+                Ok(content) if content.contains("error") => {
+                    Err(format!("Unable to evaluate {}", data_file.display()))
+                }
+                Ok(_) => Ok(statuses.clone()),
+                Err(e) => Err(e.to_string()),
+            })
+            .collect())
+    }
+}
+
+fn output(watcher: &mut Watcher<'_>, poll: bool) -> (bool, String) {
+    let mut writer = Writer::new(WriteBuffer::Vec(vec![]), WriteBuffer::Vec(vec![]));
+    let polled = if poll {
+        watcher.poll(&mut writer).unwrap()
+    } else {
+        watcher.start(&mut writer).unwrap();
+        true
+    };
+    (polled, writer.stripped().unwrap())
+}
+
+#[test]
+fn test_watch_re_evaluates_affected_pairs() {
+    let dir = std::env::temp_dir().join(format!("guard-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.rules"), "r1 PASS\nr2 FAIL\n").unwrap();
+    std::fs::write(dir.join("x.data"), "x").unwrap();
+    std::fs::write(dir.join("y.data"), "y").unwrap();
+    let rules = dir.join("a.rules").display().to_string();
+    let x = dir.join("x.data").display().to_string();
+    let y = dir.join("y.data").display().to_string();
+
+    let watched = FakeWatched { dir: dir.clone() };
+    let mut watcher = Watcher::new(&watched);
+    let (_, out) = output(&mut watcher, false);
+    assert_eq!(
+        out,
+        format!(
+            "{rules}/r2 on {x}: FAIL\n{rules}/r2 on {y}: FAIL\n\
+             Watching 1 rules files and 2 data files, 2 of 2 pairs failing, waiting for changes\n"
+        )
+    );
+
+    assert_eq!(output(&mut watcher, true), (false, String::new()));
+
+    std::fs::write(dir.join("a.rules"), "r1 PASS\nr2 PASS\nr3 SKIP\n").unwrap();
+    let (polled, out) = output(&mut watcher, true);
+    assert!(polled);
+    assert_eq!(
+        out,
+        format!(
+            "Changed: {rules}\n\
+             {rules}/r2 on {x}: FAIL -> PASS\n{rules}/r3 on {x}: SKIP (new)\n\
+             {rules}/r2 on {y}: FAIL -> PASS\n{rules}/r3 on {y}: SKIP (new)\n\
+             Re-evaluated 2 pairs, 4 status changes, 0 of 2 pairs failing\n"
+        )
+    );
+
+    std::fs::write(dir.join("y.data"), "error").unwrap();
+    let (_, out) = output(&mut watcher, true);
+    assert_eq!(
+        out,
+        format!(
+            "Changed: {y}\nUnable to evaluate {y}\n\
+             Re-evaluated 1 pairs, no status changes, 0 of 2 pairs failing\n"
+        )
+    );
+
+    std::fs::remove_file(dir.join("x.data")).unwrap();
+    let (_, out) = output(&mut watcher, true);
+    assert_eq!(
+        out,
+        format!(
+            "Changed: {x}\n{rules} on {x}: removed\n\
+             Re-evaluated 0 pairs, 1 status change, 0 of 1 pairs failing\n"
+        )
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
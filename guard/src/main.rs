@@ -7,7 +7,7 @@ mod migrate;
 mod rules;
 mod utils;
 
-use crate::commands::{MIGRATE, OUTPUT, PARSE_TREE, RULEGEN};
+use crate::commands::{INTERNAL_ERROR_EXIT_CODE, MIGRATE, OUTPUT, PARSE_TREE, RULEGEN};
 use crate::utils::writer::WriteBuffer::Stderr;
 use crate::utils::writer::{WriteBuffer::File as WBFile, WriteBuffer::Stdout, Writer};
 use command::Command;
@@ -73,7 +73,7 @@ fn main() -> Result<(), Error> {
                             .write_err(format!("Error occurred {e}"))
                             .expect("failed to write to stderr");
 
-                        exit(-1);
+                        exit(INTERNAL_ERROR_EXIT_CODE);
                     }
                     Ok(code) => exit(code),
                 }
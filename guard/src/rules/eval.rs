@@ -1107,7 +1107,7 @@ pub(in crate::rules) fn eval_guard_access_clause<'value, 'loc: 'value>(
 ) -> Result<Status> {
     let all = gac.access_clause.query.match_all;
     let blk_context = format!("GuardAccessClause#block{}", gac);
-    resolver.start_record(&blk_context)?;
+    resolver.start_record_at(&blk_context, &gac.access_clause.location)?;
 
     let statues = if gac.access_clause.comparator.0.is_unary() {
         unary_operation(
@@ -1237,7 +1237,7 @@ pub(in crate::rules) fn eval_guard_named_clause<'value, 'loc: 'value>(
     resolver: &mut dyn EvalContext<'value, 'loc>,
 ) -> Result<Status> {
     let context = format!("{}", gnc);
-    resolver.start_record(&context)?;
+    resolver.start_record_at(&context, &gnc.location)?;
 
     match resolver.rule_status(&gnc.dependent_rule) {
         Ok(status) => {
@@ -1562,6 +1562,10 @@ impl<'eval, 'value, 'loc: 'value> RecordTracer<'value>
         self.parent.start_record(context)
     }
 
+    fn start_record_at(&mut self, context: &str, location: &FileLocation<'_>) -> Result<()> {
+        self.parent.start_record_at(context, location)
+    }
+
     fn end_record(&mut self, context: &str, record: RecordType<'value>) -> Result<()> {
         let record = match record {
             RecordType::RuleCheck(ns) => {
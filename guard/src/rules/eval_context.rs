@@ -1,7 +1,7 @@
 use crate::rules::errors::Error;
 use crate::rules::exprs::{
-    AccessQuery, Block, Conjunctions, GuardClause, LetExpr, LetValue, ParameterizedRule, QueryPart,
-    Rule, RulesFile, SliceDisplay,
+    AccessQuery, Block, Conjunctions, FileLocation, GuardClause, LetExpr, LetValue,
+    ParameterizedRule, QueryPart, Rule, RulesFile, SliceDisplay,
 };
 use crate::rules::path_value::{MapValue, PathAwareValue};
 use crate::rules::values::CmpOperator;
@@ -30,6 +30,11 @@ pub(crate) struct EventRecord<'value> {
     pub(crate) context: String,
     pub(crate) container: Option<RecordType<'value>>,
     pub(crate) children: Vec<EventRecord<'value>>,
+    //
+    // line and column of the clause for records started with start_record_at
+    //
+    #[serde(skip)]
+    pub(crate) location: Option<(u32, u32)>,
 }
 
 pub(crate) struct RootScope<'value, 'loc: 'value> {
@@ -998,6 +1003,17 @@ impl<'value> RecordTracer<'value> for RecordTracker<'value> {
             context: context.to_string(),
             container: None,
             children: vec![],
+            location: None,
+        });
+        Ok(())
+    }
+
+    fn start_record_at(&mut self, context: &str, location: &FileLocation<'_>) -> Result<()> {
+        self.events.push(EventRecord {
+            context: context.to_string(),
+            container: None,
+            children: vec![],
+            location: Some((location.line, location.column)),
         });
         Ok(())
     }
@@ -1154,6 +1170,10 @@ impl<'value, 'loc: 'value> RecordTracer<'value> for RootScope<'value, 'loc> {
         self.recorder.start_record(context)
     }
 
+    fn start_record_at(&mut self, context: &str, location: &FileLocation<'_>) -> Result<()> {
+        self.recorder.start_record_at(context, location)
+    }
+
     fn end_record(&mut self, context: &str, record: RecordType<'value>) -> Result<()> {
         self.recorder.end_record(context, record)
     }
@@ -1197,6 +1217,10 @@ impl<'value, 'loc: 'value, 'eval> RecordTracer<'value> for ValueScope<'value, 'e
         self.parent.start_record(context)
     }
 
+    fn start_record_at(&mut self, context: &str, location: &FileLocation<'_>) -> Result<()> {
+        self.parent.start_record_at(context, location)
+    }
+
     fn end_record(&mut self, context: &str, record: RecordType<'value>) -> Result<()> {
         self.parent.end_record(context, record)
     }
@@ -1267,6 +1291,10 @@ impl<'value, 'loc: 'value, 'eval> RecordTracer<'value> for BlockScope<'value, 'l
         self.parent.start_record(context)
     }
 
+    fn start_record_at(&mut self, context: &str, location: &FileLocation<'_>) -> Result<()> {
+        self.parent.start_record_at(context, location)
+    }
+
     fn end_record(&mut self, context: &str, record: RecordType<'value>) -> Result<()> {
         self.parent.end_record(context, record)
     }
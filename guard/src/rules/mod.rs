@@ -12,7 +12,7 @@ pub(crate) mod values;
 
 use errors::Error;
 
-use crate::rules::exprs::{ParameterizedRule, QueryPart};
+use crate::rules::exprs::{FileLocation, ParameterizedRule, QueryPart};
 use crate::rules::path_value::PathAwareValue;
 use crate::rules::values::CmpOperator;
 use colored::*;
@@ -352,6 +352,14 @@ struct ParameterRuleResult<'value, 'loc> {
 pub(crate) trait RecordTracer<'value> {
     fn start_record(&mut self, context: &str) -> Result<()>;
     fn end_record(&mut self, context: &str, record: RecordType<'value>) -> Result<()>;
+
+    //
+    // Starts the record of a clause along with where the clause is in its rules file, clauses
+    // with the same text are told apart by it
+    //
+    fn start_record_at(&mut self, context: &str, _location: &FileLocation<'_>) -> Result<()> {
+        self.start_record(context)
+    }
 }
 
 pub(crate) trait EvalContext<'value, 'loc: 'value>: RecordTracer<'value> {
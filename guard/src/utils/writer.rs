@@ -38,6 +38,10 @@ impl Writer {
         self.buffer.into_string()
     }
 
+    pub fn into_strings(self) -> Result<(String, String), FromUtf8Error> {
+        Ok((self.buffer.into_string()?, self.err.into_string()?))
+    }
+
     pub fn stripped(self) -> Result<String, FromUtf8Error> {
         match self.buffer {
             WriteBuffer::Vec(vec) => String::from_utf8(strip_ansi_escapes::strip(vec).unwrap()),
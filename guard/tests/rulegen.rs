@@ -10,7 +10,10 @@ mod rulegen_tests {
     use rstest::rstest;
 
     use crate::assert_output_from_file_eq;
-    use cfn_guard::commands::{OUTPUT, RULEGEN, TEMPLATE};
+    use cfn_guard::commands::{
+        ENUM_THRESHOLD, INFER, OUTPUT, RULEGEN, RULES_FILE, TEMPLATE, TEST, TEST_DATA, TYPE,
+        WITH_TESTS,
+    };
     use cfn_guard::utils::writer::WriteBuffer::Stderr;
     use cfn_guard::utils::writer::{WriteBuffer::Stdout, WriteBuffer::Vec as WBVec, Writer};
     use cfn_guard::Error;
@@ -20,7 +23,12 @@ mod rulegen_tests {
     #[derive(Default)]
     struct RulegenTestRunner<'args> {
         template: Option<&'args str>,
+        templates: Vec<&'args str>,
         output: Option<&'args str>,
+        infer: bool,
+        enum_threshold: Option<&'args str>,
+        input_type: Option<&'args str>,
+        with_tests: Option<&'args str>,
     }
 
     impl<'args> RulegenTestRunner<'args> {
@@ -29,10 +37,38 @@ mod rulegen_tests {
             self
         }
 
+        fn templates(&'args mut self, args: Vec<&'args str>) -> &'args mut RulegenTestRunner {
+            self.templates = args;
+            self
+        }
+
         fn output(&'args mut self, arg: Option<&'args str>) -> &'args mut RulegenTestRunner {
             self.output = arg;
             self
         }
+
+        fn infer(&'args mut self, arg: bool) -> &'args mut RulegenTestRunner {
+            self.infer = arg;
+            self
+        }
+
+        fn enum_threshold(
+            &'args mut self,
+            arg: Option<&'args str>,
+        ) -> &'args mut RulegenTestRunner {
+            self.enum_threshold = arg;
+            self
+        }
+
+        fn input_type(&'args mut self, arg: Option<&'args str>) -> &'args mut RulegenTestRunner {
+            self.input_type = arg;
+            self
+        }
+
+        fn with_tests(&'args mut self, arg: Option<&'args str>) -> &'args mut RulegenTestRunner {
+            self.with_tests = arg;
+            self
+        }
     }
 
     impl<'args> CommandTestRunner for RulegenTestRunner<'args> {
@@ -44,11 +80,35 @@ mod rulegen_tests {
                 args.push(get_full_path_for_resource_file(self.template.unwrap()));
             }
 
+            for template in &self.templates {
+                args.push(format!("-{}", TEMPLATE.1));
+                args.push(get_full_path_for_resource_file(template));
+            }
+
             if self.output.is_some() {
                 args.push(format!("-{}", OUTPUT.1));
                 args.push(get_full_path_for_resource_file(self.output.unwrap()))
             }
 
+            if self.infer {
+                args.push(format!("--{}", INFER.0));
+            }
+
+            if let Some(enum_threshold) = self.enum_threshold {
+                args.push(format!("--{}", ENUM_THRESHOLD.0));
+                args.push(String::from(enum_threshold));
+            }
+
+            if let Some(input_type) = self.input_type {
+                args.push(format!("--{}", TYPE.0));
+                args.push(String::from(input_type));
+            }
+
+            if let Some(with_tests) = self.with_tests {
+                args.push(format!("--{}", WITH_TESTS.0));
+                args.push(String::from(with_tests));
+            }
+
             args
         }
     }
@@ -77,4 +137,144 @@ mod rulegen_tests {
         assert_eq!(expected_status_code, status_code);
         assert_output_from_file_eq!(expected_output_file_path, writer)
     }
+
+    #[rstest::rstest]
+    #[case(vec!["resources/rulegen/data-dir/infer"])]
+    #[case(vec![
+        "resources/rulegen/data-dir/infer/buckets.json",
+        "resources/rulegen/data-dir/infer/volumes-dev.yaml",
+        "resources/rulegen/data-dir/infer/volumes-prod.yaml",
+    ])]
+    fn test_rulegen_infer(#[case] templates: Vec<&str>) {
+        let mut writer = Writer::new(WBVec(vec![]), Stderr(std::io::stderr()));
+        let status_code = RulegenTestRunner::default()
+            .templates(templates)
+            .infer(true)
+            .enum_threshold(Some("2"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        assert_output_from_file_eq!(
+            "resources/rulegen/output-dir/test_rulegen_infer.out",
+            writer
+        )
+    }
+
+    #[test]
+    fn test_rulegen_infer_without_resources() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = RulegenTestRunner::default()
+            .templates(vec![
+                "resources/rulegen/data-dir/infer/volumes-dev.yaml",
+                "resources/test-command/fuzz/s3_bucket.yaml",
+            ])
+            .infer(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::INTERNAL_FAILURE, status_code);
+        assert!(writer
+            .err_to_stripped()
+            .unwrap()
+            .contains("s3_bucket.yaml has neither Resources nor resource_changes"));
+    }
+
+    #[rstest::rstest]
+    #[case(
+        "resources/rulegen/data-dir/terraform-plan.json",
+        None,
+        "resources/rulegen/output-dir/test_rulegen_from_terraform_plan.out"
+    )]
+    #[case(
+        "resources/rulegen/data-dir/terraform-plan.json",
+        Some("terraform"),
+        "resources/rulegen/output-dir/test_rulegen_from_terraform_plan.out"
+    )]
+    #[case(
+        "resources/rulegen/data-dir/generic-config.yaml",
+        None,
+        "resources/rulegen/output-dir/test_rulegen_from_generic_data.out"
+    )]
+    fn test_rulegen_input_type(
+        #[case] template: &str,
+        #[case] input_type: Option<&str>,
+        #[case] expected_output_file_path: &str,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), Stderr(std::io::stderr()));
+        let status_code = RulegenTestRunner::default()
+            .template(Some(template))
+            .input_type(input_type)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        assert_output_from_file_eq!(expected_output_file_path, writer)
+    }
+
+    #[test]
+    fn test_rulegen_infer_from_terraform_plan() {
+        let mut writer = Writer::new(WBVec(vec![]), Stderr(std::io::stderr()));
+        let status_code = RulegenTestRunner::default()
+            .template(Some("resources/rulegen/data-dir/terraform-plan.json"))
+            .infer(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains(
+            "let aws_s3_bucket_resources = resource_changes[ type == 'aws_s3_bucket' ]\n"
+        ));
+        assert!(output.contains("  %aws_s3_bucket_resources.change.after.force_destroy == false\n"));
+        assert!(!output.contains("change.after.tags"));
+        assert!(!output.contains("change.after.arn"));
+        assert!(!output.contains("aws_ebs_volume"));
+    }
+
+    struct GeneratedTestsRunner<'args> {
+        rules: &'args str,
+        tests: &'args str,
+    }
+
+    impl<'args> CommandTestRunner for GeneratedTestsRunner<'args> {
+        fn build_args(&self) -> Vec<String> {
+            vec![
+                String::from(TEST),
+                format!("-{}", RULES_FILE.1),
+                String::from(self.rules),
+                format!("-{}", TEST_DATA.1),
+                String::from(self.tests),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_rulegen_with_tests() {
+        let dir = std::env::temp_dir().join(format!("guard-rulegen-tests-{}", std::process::id()));
+        let dir_name = dir.display().to_string();
+        let mut writer = Writer::new(WBVec(vec![]), Stderr(std::io::stderr()));
+        let status_code = RulegenTestRunner::default()
+            .templates(vec![
+                "resources/rulegen/data-dir/infer/volumes-dev.yaml",
+                "resources/rulegen/data-dir/infer/volumes-prod.yaml",
+            ])
+            .infer(true)
+            .with_tests(Some(&dir_name))
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+
+        let rules = dir.join("rulegen.guard");
+        let tests = dir.join("rulegen_tests.yaml");
+        std::fs::write(&rules, writer.stripped().unwrap()).unwrap();
+        let specs = std::fs::read_to_string(&tests).unwrap();
+        assert!(specs.contains("- name: volumes-dev.yaml\n"));
+        assert!(specs.contains("- name: volumes-prod.yaml\n"));
+        assert!(specs.contains("- name: volumes-dev.yaml with AWS::EC2::Volume Size set to -1\n"));
+
+        let mut writer = Writer::new(WBVec(vec![]), Stderr(std::io::stderr()));
+        let status_code = GeneratedTestsRunner {
+            rules: &rules.display().to_string(),
+            tests: &tests.display().to_string(),
+        }
+        .run(&mut writer);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(StatusCode::SUCCESS, status_code);
+    }
 }
@@ -11,8 +11,10 @@ mod test_command_tests {
 
     use crate::assert_output_from_file_eq;
     use cfn_guard::commands::{
-        ALPHABETICAL, DIRECTORY, LAST_MODIFIED, PREVIOUS_ENGINE, RULES, RULES_AND_TEST_FILE,
-        RULES_FILE, TEST, TEST_DATA, VERBOSE,
+        ALPHABETICAL, COVERAGE, DIRECTORY, EXCLUDE, FAIL_FAST, FILTER, FUZZ, FUZZ_INPUT, FUZZ_SEED,
+        LAST_MODIFIED, OUTPUT_FORMAT, PREVIOUS_ENGINE, RULES, RULES_AND_TEST_FILE, RULES_FILE,
+        SNAPSHOT, TEST, TESTS_DIR, TEST_DATA, TEST_MAPPING, TEST_SUFFIX, TIMING, UPDATE_SNAPSHOTS,
+        VERBOSE,
     };
     use cfn_guard::utils::writer::WriteBuffer::Stderr;
     use cfn_guard::utils::writer::{WriteBuffer::Stdout, WriteBuffer::Vec as WBVec, Writer};
@@ -31,9 +33,28 @@ mod test_command_tests {
         alphabetical: bool,
         last_modified: bool,
         verbose: bool,
+        output_format: Option<&'args str>,
+        exclude: Vec<&'args str>,
+        coverage: bool,
+        snapshot: bool,
+        update_snapshots: bool,
+        filter: Option<&'args str>,
+        fail_fast: bool,
+        timing: bool,
+        tests_dir: Option<&'args str>,
+        test_suffix: Vec<&'args str>,
+        test_mapping: Option<&'args str>,
+        fuzz: Option<&'args str>,
+        fuzz_input: Vec<&'args str>,
+        fuzz_seed: Option<&'args str>,
     }
 
     impl<'args> TestCommandTestRunner<'args> {
+        fn exclude(&'args mut self, args: Vec<&'args str>) -> &'args mut TestCommandTestRunner {
+            self.exclude = args;
+            self
+        }
+
         fn test_data(&'args mut self, arg: Option<&'args str>) -> &'args mut TestCommandTestRunner {
             self.test_data = arg;
             self
@@ -97,6 +118,77 @@ mod test_command_tests {
             self.verbose = arg;
             self
         }
+
+        fn output_format(
+            &'args mut self,
+            arg: Option<&'args str>,
+        ) -> &'args mut TestCommandTestRunner {
+            self.output_format = arg;
+            self
+        }
+
+        fn coverage(&'args mut self, arg: bool) -> &'args mut TestCommandTestRunner {
+            self.coverage = arg;
+            self
+        }
+
+        fn snapshot(&'args mut self, arg: bool) -> &'args mut TestCommandTestRunner {
+            self.snapshot = arg;
+            self
+        }
+
+        fn update_snapshots(&'args mut self, arg: bool) -> &'args mut TestCommandTestRunner {
+            self.update_snapshots = arg;
+            self
+        }
+
+        fn filter(&'args mut self, arg: Option<&'args str>) -> &'args mut TestCommandTestRunner {
+            self.filter = arg;
+            self
+        }
+
+        fn fail_fast(&'args mut self, arg: bool) -> &'args mut TestCommandTestRunner {
+            self.fail_fast = arg;
+            self
+        }
+
+        fn timing(&'args mut self, arg: bool) -> &'args mut TestCommandTestRunner {
+            self.timing = arg;
+            self
+        }
+
+        fn tests_dir(&'args mut self, arg: Option<&'args str>) -> &'args mut TestCommandTestRunner {
+            self.tests_dir = arg;
+            self
+        }
+
+        fn test_suffix(&'args mut self, args: Vec<&'args str>) -> &'args mut TestCommandTestRunner {
+            self.test_suffix = args;
+            self
+        }
+
+        fn test_mapping(
+            &'args mut self,
+            arg: Option<&'args str>,
+        ) -> &'args mut TestCommandTestRunner {
+            self.test_mapping = arg;
+            self
+        }
+
+        fn fuzz(&'args mut self, arg: Option<&'args str>) -> &'args mut TestCommandTestRunner {
+            self.fuzz = arg;
+            self
+        }
+
+        fn fuzz_input(&'args mut self, args: Vec<&'args str>) -> &'args mut TestCommandTestRunner {
+            self.fuzz_input = args;
+            self
+        }
+
+        fn fuzz_seed(&'args mut self, arg: Option<&'args str>) -> &'args mut TestCommandTestRunner {
+            self.fuzz_seed = arg;
+            self
+        }
     }
 
     impl<'args> CommandTestRunner for TestCommandTestRunner<'args> {
@@ -140,6 +232,71 @@ mod test_command_tests {
             if self.verbose {
                 args.push(format!("-{}", VERBOSE.1));
             }
+
+            if let Some(output_format) = self.output_format {
+                args.push(format!("-{}", OUTPUT_FORMAT.1));
+                args.push(String::from(output_format));
+            }
+
+            for exclude in &self.exclude {
+                args.push(format!("-{}", EXCLUDE.1));
+                args.push(exclude.to_string());
+            }
+
+            if self.coverage {
+                args.push(format!("--{}", COVERAGE.0));
+            }
+
+            if self.snapshot {
+                args.push(format!("--{}", SNAPSHOT.0));
+            }
+
+            if self.update_snapshots {
+                args.push(format!("--{}", UPDATE_SNAPSHOTS.0));
+            }
+
+            if let Some(filter) = self.filter {
+                args.push(format!("-{}", FILTER.1));
+                args.push(String::from(filter));
+            }
+
+            if self.fail_fast {
+                args.push(format!("-{}", FAIL_FAST.1));
+            }
+
+            if self.timing {
+                args.push(format!("-{}", TIMING.1));
+            }
+
+            if let Some(tests_dir) = self.tests_dir {
+                args.push(format!("--{}", TESTS_DIR.0));
+                args.push(String::from(tests_dir));
+            }
+
+            for suffix in &self.test_suffix {
+                args.push(format!("--{}", TEST_SUFFIX.0));
+                args.push(suffix.to_string());
+            }
+
+            if let Some(test_mapping) = self.test_mapping {
+                args.push(format!("--{}", TEST_MAPPING.0));
+                args.push(String::from(test_mapping));
+            }
+
+            if let Some(fuzz) = self.fuzz {
+                args.push(format!("--{}", FUZZ.0));
+                args.push(String::from(fuzz));
+            }
+
+            for input in &self.fuzz_input {
+                args.push(format!("--{}", FUZZ_INPUT.0));
+                args.push(input.to_string());
+            }
+
+            if let Some(fuzz_seed) = self.fuzz_seed {
+                args.push(format!("--{}", FUZZ_SEED.0));
+                args.push(String::from(fuzz_seed));
+            }
             args
         }
     }
@@ -192,6 +349,28 @@ mod test_command_tests {
         Ok(())
     }
 
+    #[rstest]
+    #[case(vec![], StatusCode::TEST_COMMAND_FAILURE)]
+    #[case(vec!["*_wrong_*.yaml"], StatusCode::SUCCESS)]
+    #[case(vec!["*_wrong_{expectation,failures}.yaml"], StatusCode::SUCCESS)]
+    fn test_data_file_pattern_and_exclude(
+        #[case] exclude: Vec<&str>,
+        #[case] expected_status_code: i32,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled*",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .exclude(exclude)
+            .run(&mut writer);
+
+        assert_eq!(expected_status_code, status_code);
+    }
+
     #[test]
     fn test_parse_error_when_guard_rule_has_syntax_error() {
         let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
@@ -250,4 +429,584 @@ mod test_command_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_junit_output_passing_expectations() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .output_format(Some("junit"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(output.contains(r#"<testsuites tests="6" failures="0" skipped="0">"#));
+        assert!(output.contains(
+            r#"<testcase name="S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED" classname="S3 Bucket Encryption not set, FAIL"/>"#
+        ));
+        assert!(!output.contains("Test Case #"));
+    }
+
+    #[test]
+    fn test_junit_output_failing_expectation() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled_wrong_expectation.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .output_format(Some("junit"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains(r#"<testsuites tests="2" failures="1" skipped="0">"#));
+        assert!(output.contains(
+            r#"<testcase name="S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED" classname="S3 Bucket Encryption not set, expected PASS">
+      <failure message="Expected = PASS, Evaluated = [FAIL]"/>"#
+        ));
+        assert!(output.contains(
+            r#"<testcase name="S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED" classname="Test Case #2"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_json_output_has_expected_and_evaluated_status() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled_wrong_expectation.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .output_format(Some("json"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let report: serde_json::Value = serde_json::from_str(&writer.stripped().unwrap()).unwrap();
+        let files = report.as_array().unwrap();
+        assert_eq!(1, files.len());
+        assert!(files[0]["test_file"]
+            .as_str()
+            .unwrap()
+            .ends_with("s3_bucket_server_side_encryption_enabled_wrong_expectation.yaml"));
+
+        let test_cases = files[0]["test_cases"].as_array().unwrap();
+        assert_eq!(2, test_cases.len());
+        assert_eq!(
+            "S3 Bucket Encryption not set, expected PASS",
+            test_cases[0]["name"]
+        );
+        let rule = &test_cases[0]["rules"][0];
+        assert_eq!("S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED", rule["name"]);
+        assert_eq!("PASS", rule["expected"]);
+        assert_eq!(serde_json::json!(["FAIL"]), rule["evaluated"]);
+        assert_eq!("FAIL", rule["result"]);
+        assert!(rule.get("failures").is_none());
+
+        assert!(test_cases[1]["name"].is_null());
+        assert_eq!(2, test_cases[1]["number"]);
+        assert_eq!("PASS", test_cases[1]["rules"][0]["result"]);
+    }
+
+    #[test]
+    fn test_yaml_output_verbose_has_clause_failures() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled_wrong_expectation.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .output_format(Some("yaml"))
+            .verbose(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let report: serde_yaml::Value = serde_yaml::from_str(&writer.stripped().unwrap()).unwrap();
+        let rules = &report[0]["test_cases"][0]["rules"][0];
+        let failures = rules["failures"].as_sequence().unwrap();
+        assert_eq!(1, failures.len());
+        assert_eq!(
+            Some("S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED"),
+            failures[0]["Rule"]["name"].as_str()
+        );
+        assert!(!failures[0]["Rule"]["checks"]
+            .as_sequence()
+            .unwrap()
+            .is_empty());
+        assert!(report[0]["test_cases"][1]["rules"][0]
+            .get("failures")
+            .is_none());
+    }
+
+    #[test]
+    fn test_failure_expectations_met() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled_failures.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert_eq!(2, output.matches("PASS Rules:").count());
+        assert!(!output.contains("FAIL Rules:"));
+    }
+
+    #[test]
+    fn test_failure_expectations_not_met() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled_wrong_failures.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains(
+            r#"S3_BUCKET_LOGGING_ENABLED: Expected failure with message "logging", rule was not evaluated"#
+        ));
+        assert!(output.contains(
+            "S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED: Expected = FAIL, Expected failure at \
+             /Resources/ExampleS3/Properties/BucketEncryption/ServerSideEncryptionConfiguration/0/ServerSideEncryptionByDefault/SSEAlgorithm, not found"
+        ));
+        assert_eq!(
+            2,
+            output
+                .matches("S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED: Expected = FAIL, Unexpected failure at /Resources/ExampleS3/Properties: ")
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_json_output_has_unmet_failure_expectations() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled_wrong_failures.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .output_format(Some("json"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let report: serde_json::Value = serde_json::from_str(&writer.stripped().unwrap()).unwrap();
+        let rule = &report[0]["test_cases"][0]["rules"][0];
+        assert_eq!("FAIL", rule["expected"]);
+        assert_eq!(serde_json::json!(["FAIL"]), rule["evaluated"]);
+        assert_eq!("FAIL", rule["result"]);
+        assert_eq!(
+            3,
+            rule["unmet_failure_expectations"].as_array().unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_coverage_text_output() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage"))
+            .directory_only(true)
+            .coverage(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("  Rules tested: 2 of 3 (66.67%)"));
+        assert!(output.contains("    Untested rule ebs_volume_encrypted, SKIP 2 times"));
+        assert!(output.contains("  Clauses evaluated: 7 of 8 (87.50%)"));
+        assert!(output.contains("  When branches taken: 3 of 4 (75.00%)"));
+        assert!(output.contains("    Not taken ebs_volume_encrypted at line 15, column 32"));
+        assert!(output.ends_with("Overall coverage 80.00%\n"));
+    }
+
+    #[test]
+    fn test_coverage_tells_apart_clauses_with_the_same_text() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage-duplicate-clauses"))
+            .directory_only(true)
+            .coverage(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("  Clauses evaluated: 3 of 4 (75.00%)"));
+        assert!(output
+            .contains("    Not evaluated settings_present at line 6, column 9: Settings EXISTS"));
+        assert!(output.contains("  When branches taken: 1 of 2 (50.00%)"));
+        assert!(output.contains("    Not taken settings_present at line 5, column 10"));
+    }
+
+    #[test]
+    fn test_coverage_json_output() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage"))
+            .directory_only(true)
+            .coverage(true)
+            .output_format(Some("json"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        let documents = serde_json::Deserializer::from_str(&output)
+            .into_iter::<serde_json::Value>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, documents.len());
+        let coverage = &documents[1]["coverage"];
+        assert_eq!(80.0, coverage["percentage"]);
+        let rules = &coverage["rules_files"][0]["rules"];
+        assert_eq!(2, rules["covered"]);
+        assert_eq!(3, rules["total"]);
+        assert_eq!(
+            3,
+            coverage["rules_files"][0]["never_failing_clauses"]
+                .as_array()
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_coverage_with_junit_output() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage"))
+            .directory_only(true)
+            .coverage(true)
+            .output_format(Some("junit"))
+            .run(&mut writer);
+
+        assert_eq!(1, status_code);
+        assert!(writer
+            .err_to_stripped()
+            .unwrap()
+            .contains("--coverage is not supported with --output-format junit"));
+    }
+
+    #[test]
+    fn test_input_file_with_patch() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/input-file/tests/bucket_tests.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert_eq!(4, output.matches("PASS Rules:").count());
+        assert_eq!(
+            2,
+            output
+                .matches("S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED: Expected = FAIL")
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_input_file_errors() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/input-file/tests/bucket_missing_input_tests.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .run(&mut writer);
+
+        assert_eq!(1, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("Unable to read input_file "));
+        assert!(output.contains("missing.yaml"));
+        assert!(
+            output.contains("Test case has both input and input_file, only one of them can be set")
+        );
+        assert_eq!(1, output.matches("PASS Rules:").count());
+    }
+
+    #[test]
+    fn test_snapshots_match() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/snapshot"))
+            .directory_only(true)
+            .snapshot(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        assert_eq!(
+            "2 snapshots, 2 matched, 0 mismatched, 0 missing, 0 errors\n",
+            writer.stripped().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_snapshots_mismatch_and_update() {
+        let dir = std::env::temp_dir().join(format!("guard-snapshot-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("snapshots")).unwrap();
+        let source = get_full_path_for_resource_file("resources/test-command/snapshot");
+        for file in [
+            "s3_bucket_encryption.guard",
+            "snapshots/s3_bucket_encryption_enabled.yaml",
+            "snapshots/s3_bucket_encryption_enabled.yaml.snap",
+            "snapshots/s3_bucket_encryption_missing.yaml",
+        ]
+        .iter()
+        {
+            std::fs::copy(format!("{source}/{file}"), dir.join(file)).unwrap();
+        }
+        let enabled = dir.join("snapshots/s3_bucket_encryption_enabled.yaml");
+        let content = std::fs::read_to_string(&enabled).unwrap();
+        std::fs::write(&enabled, content.replace("AES256", "DES")).unwrap();
+        let dir_arg = dir.display().to_string();
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some(&dir_arg))
+            .directory_only(true)
+            .snapshot(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("Mismatch snapshots/s3_bucket_encryption_enabled.yaml.snap\n"));
+        assert!(output.contains("+++ snapshots/s3_bucket_encryption_enabled.yaml.snap (actual)\n"));
+        assert!(output.contains("\n@@ -1,12 +1,91 @@\n --- single-line-summary\n"));
+        assert!(output.contains("\n+snapshots/s3_bucket_encryption_enabled.yaml Status = FAIL\n"));
+        assert!(output.contains(
+            "Missing snapshots/s3_bucket_encryption_missing.yaml.snap, run with --update-snapshots to write it"
+        ));
+        assert!(output.ends_with("2 snapshots, 0 matched, 1 mismatched, 1 missing, 0 errors\n"));
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some(&dir_arg))
+            .directory_only(true)
+            .snapshot(true)
+            .update_snapshots(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        assert_eq!(
+            "Updated snapshots/s3_bucket_encryption_enabled.yaml.snap\n\
+             Written snapshots/s3_bucket_encryption_missing.yaml.snap\n\
+             2 snapshots, 0 unchanged, 1 updated, 1 written, 0 errors\n",
+            writer.stripped().unwrap()
+        );
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some(&dir_arg))
+            .directory_only(true)
+            .snapshot(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[rstest]
+    #[case(Some("s3_bucket_named"), vec!["Test Case #1", "Test Case #2"], "s3_bucket_versioning_enabled")]
+    #[case(Some("Named bucket*"), vec!["Test Case #2", "s3_bucket_versioning_enabled"], "Test Case #1")]
+    fn test_filter(
+        #[case] filter: Option<&str>,
+        #[case] expected: Vec<&str>,
+        #[case] not_expected: &str,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage"))
+            .directory_only(true)
+            .filter(filter)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        for each in expected {
+            assert!(output.contains(each));
+        }
+        assert!(!output.contains(not_expected));
+        assert!(!output.contains("No Test expectation was set"));
+    }
+
+    #[test]
+    fn test_filter_without_match() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage"))
+            .directory_only(true)
+            .filter(Some("no_such_rule"))
+            .timing(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(!output.contains("Test Case #"));
+        assert!(output.contains("No test case or rule matched --filter no_such_rule\n"));
+        assert!(output.ends_with(", 0 passed, 0 failed, 2 filtered out\n"));
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .test_data(Some(
+                "resources/test-command/data-dir/s3_bucket_server_side_encryption_enabled_wrong_expectation.yaml",
+            ))
+            .rules(Some(
+                "resources/validate/rules-dir/s3_bucket_server_side_encryption_enabled.guard",
+            ))
+            .fail_fast(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("Test Case #1"));
+        assert!(!output.contains("Test Case #2"));
+        assert!(output.ends_with("Stopped at the first failing test case, --fail-fast is set\n"));
+    }
+
+    #[test]
+    fn test_timing() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage"))
+            .directory_only(true)
+            .timing(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        assert_eq!(
+            2,
+            output
+                .matches("\n  PASS, 3 of 3 rule expectations met in ")
+                .count()
+        );
+        assert!(output.contains("\nRan 2 test cases in "));
+        assert!(output.ends_with("ms, 2 passed, 0 failed, 0 filtered out\n"));
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/coverage"))
+            .directory_only(true)
+            .timing(true)
+            .output_format(Some("json"))
+            .run(&mut writer);
+        let report: serde_json::Value = serde_json::from_str(&writer.stripped().unwrap()).unwrap();
+        assert!(report[0]["test_cases"][0]["duration_ms"].is_f64());
+    }
+
+    #[rstest]
+    #[case(None, vec![
+        "Testing Guard File resources/test-command/layout/s3_bucket.guard\n",
+        "2 of 3 guard files have no tests\n  resources/test-command/layout/mapped.guard\n  resources/test-command/layout/untested.guard\n",
+    ])]
+    #[case(Some("resources/test-command/layout/mapping.yaml"), vec![
+        "Testing Guard File resources/test-command/layout/mapped.guard\n",
+        "Name: Bucket without encryption\n",
+        "Testing Guard File resources/test-command/layout/s3_bucket.guard\n",
+        "1 of 3 guard files have no tests\n  resources/test-command/layout/untested.guard\n",
+    ])]
+    fn test_layout_with_suffix_and_mapping(
+        #[case] test_mapping: Option<&str>,
+        #[case] expected: Vec<&str>,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/layout"))
+            .directory_only(true)
+            .tests_dir(Some("."))
+            .test_suffix(vec![".test.yaml"])
+            .test_mapping(test_mapping)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        let output = writer.stripped().unwrap();
+        for each in expected {
+            assert!(output.contains(each));
+        }
+        assert!(!output.contains("s3_bucket_template.yaml"));
+    }
+
+    #[test]
+    fn test_layout_mapping_with_unknown_rules_file() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .directory(Some("resources/test-command/layout"))
+            .directory_only(true)
+            .test_mapping(Some("resources/test-command/layout/missing_mapping.yaml"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::INTERNAL_FAILURE, status_code);
+        assert!(writer.err_to_stripped().unwrap().contains(
+            "Rules file resources/test-command/layout/missing.guard of the test mapping was not found in the directory"
+        ));
+    }
+
+    #[test]
+    fn test_fuzz_reports_minimized_reproducer() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .rules(Some("resources/test-command/fuzz/s3_bucket_tags.guard"))
+            .fuzz(Some("300"))
+            .fuzz_input(vec!["resources/test-command/fuzz/s3_bucket.yaml"])
+            .fuzz_seed(Some("1"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::TEST_COMMAND_FAILURE, status_code);
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("Failure 1 of 1, IncompatibleError"));
+        assert!(output.contains("Attempting EMPTY operation on type"));
+        assert!(output.contains("- name: fuzz seed 1 case"));
+        assert!(output.contains("Ran 300 fuzz cases with seed 1"));
+        assert!(!output.contains("BucketEncryption"));
+    }
+
+    #[test]
+    fn test_fuzz_without_failures() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = TestCommandTestRunner::default()
+            .rules(Some(
+                "resources/test-command/fuzz/s3_bucket_encrypted.guard",
+            ))
+            .fuzz(Some("300"))
+            .fuzz_input(vec!["resources/test-command/fuzz/s3_bucket.yaml"])
+            .fuzz_seed(Some("1"))
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        assert!(writer
+            .stripped()
+            .unwrap()
+            .contains("Ran 300 fuzz cases with seed 1, 0 failed, 0 distinct failures"));
+    }
 }
@@ -26,6 +26,9 @@ impl StatusCode {
     pub const INCORRECT_STATUS_ERROR: i32 = 1;
     pub const TEST_COMMAND_FAILURE: i32 = 7;
     pub const PARSING_ERROR: i32 = 5;
+    pub const RULES_FILE_PARSE_ERROR: i32 = 6;
+    pub const DATA_FILE_PARSE_ERROR: i32 = 7;
+    pub const ALL_SKIPPED: i32 = 8;
 }
 
 pub fn read_from_resource_file(path: &str) -> String {
@@ -12,10 +12,14 @@ mod validate_tests {
     use strip_ansi_escapes;
 
     use cfn_guard;
+    use cfn_guard::command::Command;
     use cfn_guard::commands::validate::Validate;
     use cfn_guard::commands::{
-        ALPHABETICAL, DATA, INPUT_PARAMETERS, LAST_MODIFIED, OUTPUT_FORMAT, PAYLOAD,
-        PREVIOUS_ENGINE, PRINT_JSON, RULES, SHOW_CLAUSE_FAILURES, SHOW_SUMMARY, VALIDATE, VERBOSE,
+        ALPHABETICAL, BASELINE, CACHE_DIR, CONFIG, DATA, DATA_EXTENSIONS, DETAILED_EXIT_CODES,
+        DISALLOW_SUPPRESSIONS, EXCLUDE, EXCLUDE_RULE, FAIL_ON_SKIP, INPUT_PARAMETERS, JOBS,
+        LAST_MODIFIED, NO_CONFIG, OUTPUT_FORMAT, PAYLOAD, PREVIOUS_ENGINE, PRINT_JSON, RULES,
+        RULE_NAME, RUN_SUMMARY, SHOW_CLAUSE_FAILURES, SHOW_SUMMARY, TAGS, VALIDATE, VERBOSE,
+        WRITE_BASELINE,
     };
     use cfn_guard::utils::writer::WriteBuffer::Stderr;
     use cfn_guard::utils::writer::{WriteBuffer::Stdout, WriteBuffer::Vec as WBVec, Writer};
@@ -40,6 +44,21 @@ mod validate_tests {
         verbose: bool,
         print_json: bool,
         payload: Option<&'args str>,
+        jobs: Option<&'args str>,
+        cache_dir: Option<&'args str>,
+        run_summary: bool,
+        baseline: Option<&'args str>,
+        write_baseline: Option<&'args str>,
+        disallow_suppressions: bool,
+        detailed_exit_codes: bool,
+        fail_on_skip: bool,
+        config: Option<&'args str>,
+        no_config: bool,
+        exclude: Vec<&'args str>,
+        data_extensions: Vec<&'args str>,
+        rule_name: Vec<&'args str>,
+        exclude_rule: Vec<&'args str>,
+        tags: Vec<&'args str>,
     }
 
     impl<'args> ValidateTestRunner<'args> {
@@ -128,6 +147,87 @@ mod validate_tests {
             self.print_json = arg;
             self
         }
+
+        fn jobs(&'args mut self, arg: Option<&'args str>) -> &'args mut ValidateTestRunner {
+            self.jobs = arg;
+            self
+        }
+
+        fn cache_dir(&'args mut self, arg: Option<&'args str>) -> &'args mut ValidateTestRunner {
+            self.cache_dir = arg;
+            self
+        }
+
+        fn run_summary(&'args mut self, arg: bool) -> &'args mut ValidateTestRunner {
+            self.run_summary = arg;
+            self
+        }
+
+        fn baseline(&'args mut self, arg: Option<&'args str>) -> &'args mut ValidateTestRunner {
+            self.baseline = arg;
+            self
+        }
+
+        fn write_baseline(
+            &'args mut self,
+            arg: Option<&'args str>,
+        ) -> &'args mut ValidateTestRunner {
+            self.write_baseline = arg;
+            self
+        }
+
+        fn disallow_suppressions(&'args mut self, arg: bool) -> &'args mut ValidateTestRunner {
+            self.disallow_suppressions = arg;
+            self
+        }
+
+        fn detailed_exit_codes(&'args mut self, arg: bool) -> &'args mut ValidateTestRunner {
+            self.detailed_exit_codes = arg;
+            self
+        }
+
+        fn fail_on_skip(&'args mut self, arg: bool) -> &'args mut ValidateTestRunner {
+            self.fail_on_skip = arg;
+            self
+        }
+
+        fn config(&'args mut self, arg: Option<&'args str>) -> &'args mut ValidateTestRunner {
+            self.config = arg;
+            self
+        }
+
+        fn no_config(&'args mut self, arg: bool) -> &'args mut ValidateTestRunner {
+            self.no_config = arg;
+            self
+        }
+
+        fn exclude(&'args mut self, args: Vec<&'args str>) -> &'args mut ValidateTestRunner {
+            self.exclude = args;
+            self
+        }
+
+        fn data_extensions(
+            &'args mut self,
+            args: Vec<&'args str>,
+        ) -> &'args mut ValidateTestRunner {
+            self.data_extensions = args;
+            self
+        }
+
+        fn rule_name(&'args mut self, args: Vec<&'args str>) -> &'args mut ValidateTestRunner {
+            self.rule_name = args;
+            self
+        }
+
+        fn exclude_rule(&'args mut self, args: Vec<&'args str>) -> &'args mut ValidateTestRunner {
+            self.exclude_rule = args;
+            self
+        }
+
+        fn tags(&'args mut self, args: Vec<&'args str>) -> &'args mut ValidateTestRunner {
+            self.tags = args;
+            self
+        }
     }
 
     impl<'args> CommandTestRunner for ValidateTestRunner<'args> {
@@ -165,6 +265,7 @@ mod validate_tests {
 
             if let Some(output_format) = self.output_format {
                 args.push(format!("-{}", OUTPUT_FORMAT.1));
+                args.push(output_format.to_string());
             }
 
             if self.previous_engine {
@@ -196,6 +297,76 @@ mod validate_tests {
                 args.push(payload.to_string());
             }
 
+            if let Some(jobs) = self.jobs {
+                args.push(format!("-{}", JOBS.1));
+                args.push(jobs.to_string());
+            }
+
+            if let Some(cache_dir) = self.cache_dir {
+                args.push(format!("-{}", CACHE_DIR.1));
+                args.push(cache_dir.to_string());
+            }
+
+            if self.run_summary {
+                args.push(format!("-{}", RUN_SUMMARY.1));
+            }
+
+            if let Some(baseline) = self.baseline {
+                args.push(format!("-{}", BASELINE.1));
+                args.push(baseline.to_string());
+            }
+
+            if let Some(write_baseline) = self.write_baseline {
+                args.push(format!("-{}", WRITE_BASELINE.1));
+                args.push(write_baseline.to_string());
+            }
+
+            if self.disallow_suppressions {
+                args.push(format!("-{}", DISALLOW_SUPPRESSIONS.1));
+            }
+
+            if self.detailed_exit_codes {
+                args.push(format!("-{}", DETAILED_EXIT_CODES.1));
+            }
+
+            if self.fail_on_skip {
+                args.push(format!("-{}", FAIL_ON_SKIP.1));
+            }
+
+            if let Some(config) = self.config {
+                args.push(format!("-{}", CONFIG.1));
+                args.push(get_path_for_resource_file(config));
+            }
+
+            if self.no_config {
+                args.push(format!("-{}", NO_CONFIG.1));
+            }
+
+            for each in &self.exclude {
+                args.push(format!("-{}", EXCLUDE.1));
+                args.push(each.to_string());
+            }
+
+            if !self.data_extensions.is_empty() {
+                args.push(format!("-{}", DATA_EXTENSIONS.1));
+                args.push(self.data_extensions.join(","));
+            }
+
+            for each in &self.rule_name {
+                args.push(format!("-{}", RULE_NAME.1));
+                args.push(each.to_string());
+            }
+
+            for each in &self.exclude_rule {
+                args.push(format!("-{}", EXCLUDE_RULE.1));
+                args.push(each.to_string());
+            }
+
+            if !self.tags.is_empty() {
+                args.push(format!("-{}", TAGS.1));
+                args.push(self.tags.join(","));
+            }
+
             args
         }
     }
@@ -208,11 +379,11 @@ mod validate_tests {
     #[case(vec!["data-dir/s3-public-read-prohibited-template-compliant.yaml"], vec!["rules-dir/s3_bucket_public_read_prohibited.guard"], StatusCode::SUCCESS)]
     #[case(vec!["data-dir/s3-public-read-prohibited-template-non-compliant.yaml"], vec!["rules-dir/s3_bucket_public_read_prohibited.guard"], StatusCode::PARSING_ERROR)]
     #[case(vec!["s3-server-side-encryption-template-non-compliant-2.yaml"], vec!["malformed-rule.guard"], StatusCode::INTERNAL_FAILURE)]
-    #[case(vec!["malformed-template.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard"], StatusCode::INTERNAL_FAILURE)]
+    #[case(vec!["malformed-template.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard"], StatusCode::PARSING_ERROR)]
     #[case(vec!["s3-server-side-encryption-template-non-compliant-2.yaml"], vec!["blank-rule.guard"], StatusCode::PARSING_ERROR)]
     #[case(vec!["s3-server-side-encryption-template-non-compliant-2.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard", "blank-rule.guard"], StatusCode::PARSING_ERROR )]
-    #[case(vec!["blank-template.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard"], StatusCode::INTERNAL_FAILURE)]
-    #[case(vec!["blank-template.yaml", "s3-server-side-encryption-template-non-compliant-2.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard"], StatusCode::INTERNAL_FAILURE)]
+    #[case(vec!["blank-template.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard"], StatusCode::PARSING_ERROR)]
+    #[case(vec!["blank-template.yaml", "s3-server-side-encryption-template-non-compliant-2.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard"], StatusCode::PARSING_ERROR)]
     #[case(vec!["dne.yaml"], vec!["rules-dir/s3_bucket_public_read_prohibited.guard"], StatusCode::INTERNAL_FAILURE)]
     #[case(vec!["data-dir/s3-public-read-prohibited-template-non-compliant.yaml"], vec!["dne.guard"], StatusCode::INTERNAL_FAILURE)]
     fn test_single_data_file_single_rules_file_status(
@@ -347,6 +518,971 @@ mod validate_tests {
     }
 
     #[rstest::rstest]
+    #[case(vec!["data-dir/"], vec!["rules-dir/"], StatusCode::PARSING_ERROR)]
+    #[case(vec!["data-dir/s3-public-read-prohibited-template-compliant.yaml"], vec!["rules-dir/s3_bucket_public_read_prohibited.guard"], StatusCode::SUCCESS)]
+    #[case(vec!["s3-server-side-encryption-template-non-compliant-2.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard", "blank-rule.guard"], StatusCode::PARSING_ERROR)]
+    fn test_parallel_evaluation_matches_sequential(
+        #[case] data_arg: Vec<&str>,
+        #[case] rules_arg: Vec<&str>,
+        #[case] expected_status_code: i32,
+    ) {
+        let mut sequential = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let sequential_status_code = ValidateTestRunner::default()
+            .data(data_arg.clone())
+            .rules(rules_arg.clone())
+            .show_summary(vec!["all"])
+            .run(&mut sequential);
+
+        let mut parallel = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let parallel_status_code = ValidateTestRunner::default()
+            .data(data_arg)
+            .rules(rules_arg)
+            .show_summary(vec!["all"])
+            .jobs(Some("4"))
+            .run(&mut parallel);
+
+        assert_eq!(expected_status_code, sequential_status_code);
+        assert_eq!(sequential_status_code, parallel_status_code);
+        assert_eq!(
+            sequential.into_strings().unwrap(),
+            parallel.into_strings().unwrap()
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("rules-dir", vec!["data-dir/"], vec!["rules-dir/"], StatusCode::PARSING_ERROR)]
+    #[case("encryption", vec!["s3-server-side-encryption-template-non-compliant-2.yaml"], vec!["s3_bucket_server_side_encryption_enabled_2.guard"], StatusCode::PARSING_ERROR)]
+    fn test_cached_rules_match_parsed_rules(
+        #[case] name: &str,
+        #[case] data_arg: Vec<&str>,
+        #[case] rules_arg: Vec<&str>,
+        #[case] expected_status_code: i32,
+    ) {
+        let cache_dir = std::env::temp_dir().join(format!(
+            "guard-validate-cache-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let cache_dir = format!("{}", cache_dir.display());
+
+        let mut uncached = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let uncached_status_code = ValidateTestRunner::default()
+            .data(data_arg.clone())
+            .rules(rules_arg.clone())
+            .verbose(true)
+            .run(&mut uncached);
+        assert_eq!(expected_status_code, uncached_status_code);
+        let uncached = uncached.stripped().unwrap();
+
+        for _populate_then_reuse in 0..2 {
+            let mut cached = Writer::new(WBVec(vec![]), WBVec(vec![]));
+            let cached_status_code = ValidateTestRunner::default()
+                .data(data_arg.clone())
+                .rules(rules_arg.clone())
+                .verbose(true)
+                .cache_dir(Some(&cache_dir))
+                .run(&mut cached);
+
+            assert_eq!(uncached_status_code, cached_status_code);
+            assert_eq!(uncached, cached.stripped().unwrap());
+        }
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sarif_output_for_failing_clause() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .output_format(Some("sarif"))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let log: serde_json::Value = serde_json::from_str(&writer.stripped().unwrap()).unwrap();
+        assert_eq!("2.1.0", log["version"]);
+        let run = &log["runs"][0];
+        assert_eq!("cfn-guard", run["tool"]["driver"]["name"]);
+        assert_eq!(
+            "S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED_2",
+            run["tool"]["driver"]["rules"][0]["id"]
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(1, results.len());
+        let result = &results[0];
+        assert_eq!(
+            "S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED_2",
+            result["ruleId"]
+        );
+        assert_eq!("error", result["level"]);
+        let message = result["message"]["text"].as_str().unwrap();
+        assert!(message.starts_with("Violation: S3 Bucket must enable server-side encryption #2."));
+        assert!(message.contains("BucketEncryption.ServerSideEncryptionConfiguration"));
+
+        let location = &result["locations"][0]["physicalLocation"];
+        assert!(location["artifactLocation"]["uri"]
+            .as_str()
+            .unwrap()
+            .ends_with(
+                "resources/validate/s3-server-side-encryption-template-non-compliant-2.yaml"
+            ));
+        assert_eq!(13, location["region"]["startLine"]);
+        assert_eq!(24, location["region"]["startColumn"]);
+    }
+
+    #[test]
+    fn test_sarif_output_is_a_single_log_for_all_pairs() {
+        let mut sequential = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some("sarif"))
+            .run(&mut sequential);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+        let sequential = sequential.stripped().unwrap();
+
+        let log: serde_json::Value = serde_json::from_str(&sequential).unwrap();
+        assert_eq!(1, log["runs"].as_array().unwrap().len());
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert!(results
+            .iter()
+            .any(|result| result["ruleId"] == "S3_BUCKET_PUBLIC_READ_PROHIBITED"));
+        assert!(results
+            .iter()
+            .any(|result| result["ruleId"] == "S3_BUCKET_LOGGING_ENABLED"));
+
+        let mut parallel = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some("sarif"))
+            .jobs(Some("4"))
+            .run(&mut parallel);
+        assert_eq!(sequential, parallel.stripped().unwrap());
+    }
+
+    #[test]
+    fn test_sarif_output_with_previous_engine() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .output_format(Some("sarif"))
+            .previous_engine(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::INCORRECT_STATUS_ERROR, status_code);
+    }
+
+    #[test]
+    fn test_junit_output_for_failing_rule() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .output_format(Some("junit"))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let output = writer.stripped().unwrap();
+        assert!(output.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(output.contains(r#"<testsuites tests="1" failures="1" skipped="0">"#));
+        assert!(output.contains(
+            r#"<testsuite name="s3_bucket_server_side_encryption_enabled_2.guard" tests="1" failures="1" skipped="0">"#
+        ));
+        assert!(output.contains(
+            r#"<testcase name="S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED_2" classname="s3-server-side-encryption-template-non-compliant-2.yaml">"#
+        ));
+        assert!(output.contains(
+            r#"<failure message="Rule S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED_2 is not compliant">Violation: S3 Bucket must enable server-side encryption #2."#
+        ));
+        assert!(output.contains("&quot;aws:kms&quot;"));
+    }
+
+    #[test]
+    fn test_junit_output_is_a_single_report_for_all_pairs() {
+        let mut sequential = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some("junit"))
+            .run(&mut sequential);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+        let sequential = sequential.stripped().unwrap();
+
+        assert_eq!(1, sequential.matches("<testsuites ").count());
+        assert!(sequential.contains(r#"<testsuite name="s3_bucket_logging_enabled.guard""#));
+        assert!(sequential.contains(r#"<testsuite name="s3_bucket_public_read_prohibited.guard""#));
+        assert!(sequential.contains("<skipped/>"));
+
+        let mut parallel = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some("junit"))
+            .jobs(Some("4"))
+            .run(&mut parallel);
+        assert_eq!(sequential, parallel.stripped().unwrap());
+    }
+
+    #[test]
+    fn test_markdown_output_groups_failures_by_cfn_resource() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .output_format(Some("markdown"))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let output = writer.stripped().unwrap();
+        assert!(output.starts_with("# cfn-guard validation report\n"));
+        assert!(output.contains(
+            "| s3-server-side-encryption-template-non-compliant-2.yaml | s3_bucket_server_side_encryption_enabled_2.guard | FAIL | 0 | 1 | 0 |"
+        ));
+        assert!(output.contains("| S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED_2 | FAIL |"));
+        assert!(output.contains("### Resource MyBucket (AWS::S3::Bucket)"));
+        assert!(output.contains(
+            "| S3_BUCKET_SERVER_SIDE_ENCRYPTION_ENABLED_2 | /Resources/MyBucket/Properties/BucketEncryption | 13 | \"\" | Violation: S3 Bucket must enable server-side encryption #2."
+        ));
+    }
+
+    #[test]
+    fn test_html_output_groups_failures_by_terraform_resource() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["tf-plan-s3-buckets.json"])
+            .rules(vec!["tf_s3_bucket_private_acl.guard"])
+            .output_format(Some("html"))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let output = writer.stripped().unwrap();
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        assert!(output.trim_end().ends_with("</html>"));
+        assert!(output.contains("<h3>Resource aws_s3_bucket.logs (aws_s3_bucket)</h3>"));
+        assert!(!output.contains("aws_s3_bucket.data"));
+        assert!(output.contains(
+            "<td><code>/resource_changes/0/change/after/acl</code></td><td>12</td><td><code>&quot;public-read&quot;</code></td><td>Violation: S3 buckets must use a private ACL<br>"
+        ));
+    }
+
+    #[test]
+    fn test_markdown_output_is_a_single_report_for_all_pairs() {
+        let mut sequential = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some("markdown"))
+            .run(&mut sequential);
+        let sequential = sequential.stripped().unwrap();
+        assert_eq!(
+            1,
+            sequential.matches("# cfn-guard validation report").count()
+        );
+
+        let mut parallel = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some("markdown"))
+            .jobs(Some("4"))
+            .run(&mut parallel);
+        assert_eq!(sequential, parallel.stripped().unwrap());
+    }
+
+    #[test]
+    fn test_run_summary_counts_rules_across_data_files() {
+        let mut sequential = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .show_summary(vec!["none"])
+            .run_summary(true)
+            .run(&mut sequential);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+        let sequential = sequential.stripped().unwrap();
+
+        let summary = &sequential[sequential.find("Run summary").unwrap()..];
+        let lines = summary
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>();
+        assert!(lines.contains(&String::from(
+            "s3_bucket_logging_enabled.guard/S3_BUCKET_LOGGING_ENABLED 0 4 2"
+        )));
+        assert!(lines.contains(&String::from(
+            "s3-public-read-prohibited-template-non-compliant.yaml 3"
+        )));
+        assert!(!summary.contains("Rules that were always SKIP"));
+
+        let mut parallel = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .show_summary(vec!["none"])
+            .run_summary(true)
+            .jobs(Some("4"))
+            .run(&mut parallel);
+        assert_eq!(sequential, parallel.stripped().unwrap());
+    }
+
+    #[test]
+    fn test_run_summary_is_final_yaml_document() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec!["db_resource.yaml"])
+            .rules(vec!["rules-dir/s3_bucket_logging_enabled.guard"])
+            .output_format(Some("yaml"))
+            .show_summary(vec!["none"])
+            .run_summary(true)
+            .run(&mut writer);
+
+        let output = writer.stripped().unwrap();
+        let last_document = output.rsplit("---\n").next().unwrap();
+        let summary: serde_yaml::Value = serde_yaml::from_str(last_document).unwrap();
+        let summary = &summary["run_summary"];
+        assert_eq!(
+            Some("S3_BUCKET_LOGGING_ENABLED"),
+            summary["rules"][0]["rule"].as_str()
+        );
+        assert_eq!(Some(1), summary["rules"][0]["skip"].as_u64());
+        assert!(summary["most_failed_data_files"]
+            .as_sequence()
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Some("s3_bucket_logging_enabled.guard"),
+            summary["always_skipped_rules"][0]["rules_file"].as_str()
+        );
+    }
+
+    #[test]
+    fn test_run_summary_with_sarif_output() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some("sarif"))
+            .run_summary(true)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::INCORRECT_STATUS_ERROR, status_code);
+    }
+
+    #[rstest::rstest]
+    #[case("sarif", true, false)]
+    #[case("junit", false, true)]
+    #[case("html", true, false)]
+    fn test_tree_and_json_output_with_run_log_format(
+        #[case] output_format: &str,
+        #[case] verbose: bool,
+        #[case] print_json: bool,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .output_format(Some(output_format))
+            .verbose(verbose)
+            .print_json(print_json)
+            .run(&mut writer);
+
+        assert_eq!(StatusCode::INCORRECT_STATUS_ERROR, status_code);
+        let flag = if verbose { "verbose" } else { "print-json" };
+        assert_eq!(
+            format!("--{flag} is not supported with --output-format {output_format}\n"),
+            writer.err_to_stripped().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_written_baseline_suppresses_all_failures() {
+        let baseline = std::env::temp_dir().join(format!(
+            "guard-validate-baseline-{}.yaml",
+            std::process::id()
+        ));
+        let baseline = format!("{}", baseline.display());
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["data-dir/"])
+            .rules(vec!["rules-dir/"])
+            .write_baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        for jobs in [None, Some("4")] {
+            let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+            let status_code = ValidateTestRunner::default()
+                .data(vec!["data-dir/"])
+                .rules(vec!["rules-dir/"])
+                .baseline(Some(&baseline))
+                .jobs(jobs)
+                .run(&mut writer);
+            assert_eq!(StatusCode::SUCCESS, status_code);
+            assert!(!writer
+                .err_to_stripped()
+                .unwrap()
+                .contains("Stale baseline entry"));
+        }
+        std::fs::remove_file(&baseline).unwrap();
+    }
+
+    #[test]
+    fn test_written_baseline_keys_terraform_resources_by_address() {
+        let baseline = std::env::temp_dir().join(format!(
+            "guard-validate-tf-baseline-{}.yaml",
+            std::process::id()
+        ));
+        let baseline = format!("{}", baseline.display());
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["tf-plan-s3-buckets.json"])
+            .rules(vec!["tf_s3_bucket_private_acl.guard"])
+            .write_baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let written: serde_yaml::Value =
+            serde_yaml::from_str(&std::fs::read_to_string(&baseline).unwrap()).unwrap();
+        assert_eq!(
+            Some("aws_s3_bucket.logs"),
+            written["violations"][0]["path"].as_str()
+        );
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["tf-plan-s3-buckets.json"])
+            .rules(vec!["tf_s3_bucket_private_acl.guard"])
+            .baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+        std::fs::remove_file(&baseline).unwrap();
+    }
+
+    #[test]
+    fn test_baseline_keys_rules_files_by_relative_path() {
+        let baseline = std::env::temp_dir().join(format!(
+            "guard-validate-nested-baseline-{}.yaml",
+            std::process::id()
+        ));
+        let baseline = format!("{}", baseline.display());
+
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["baseline-nested/rules"])
+            .write_baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let written: serde_yaml::Value =
+            serde_yaml::from_str(&std::fs::read_to_string(&baseline).unwrap()).unwrap();
+        let rules_files = written["violations"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|violation| violation["rules_file"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a/s3.guard", "b/s3.guard"], rules_files);
+        std::fs::remove_file(&baseline).unwrap();
+
+        let baseline = get_path_for_resource_file("baseline-nested/baseline-a.yaml");
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["baseline-nested/rules"])
+            .baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+        assert!(!writer
+            .err_to_stripped()
+            .unwrap()
+            .contains("Stale baseline entry"));
+    }
+
+    #[test]
+    fn test_baseline_with_unknown_field() {
+        let baseline = get_path_for_resource_file("baseline-nested/unknown-field.yaml");
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["baseline-nested/rules"])
+            .baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::INTERNAL_FAILURE, status_code);
+
+        let err = writer.err_to_stripped().unwrap();
+        assert!(err.contains("unknown field `resource`"));
+    }
+
+    #[test]
+    fn test_baseline_reports_suppressed_and_stale_entries() {
+        let baseline = get_path_for_resource_file("baseline-s3-encryption.yaml");
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "s3-server-side-encryption-template-non-compliant-2.yaml",
+            ])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .output_format(Some("yaml"))
+            .show_summary(vec!["none"])
+            .baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+
+        let output = writer.stripped().unwrap();
+        let last_document = output.rsplit("---\n").next().unwrap();
+        let document: serde_yaml::Value = serde_yaml::from_str(last_document).unwrap();
+        let baseline = &document["baseline"];
+        assert_eq!(
+            Some("/Resources/MyBucket"),
+            baseline["suppressed"][0]["path"].as_str()
+        );
+        assert_eq!(
+            Some("/Resources/RemovedBucket"),
+            baseline["stale"][0]["path"].as_str()
+        );
+    }
+
+    #[test]
+    fn test_baseline_stale_entries_on_stderr() {
+        let baseline = get_path_for_resource_file("baseline-s3-encryption.yaml");
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["db_resource.yaml"])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .baseline(Some(&baseline))
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+
+        let err = writer.err_to_stripped().unwrap();
+        assert_eq!(2, err.matches("Stale baseline entry").count());
+    }
+
+    #[rstest::rstest]
+    #[case(
+        "suppressions/s3-buckets-with-suppressed-encryption.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        false,
+        StatusCode::SUCCESS
+    )]
+    #[case(
+        "suppressions/s3-buckets-with-suppressed-encryption.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        true,
+        StatusCode::PARSING_ERROR
+    )]
+    #[case(
+        "suppressions/s3-buckets-partially-suppressed.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        false,
+        StatusCode::PARSING_ERROR
+    )]
+    #[case(
+        "suppressions/s3-buckets-with-suppressed-encryption.yaml",
+        "suppressions/s3_bucket_encryption_dependent.guard",
+        false,
+        StatusCode::SUCCESS
+    )]
+    #[case(
+        "suppressions/s3-buckets-with-suppressed-encryption.yaml",
+        "suppressions/s3_bucket_encryption_dependent.guard",
+        true,
+        StatusCode::PARSING_ERROR
+    )]
+    #[case(
+        "suppressions/tf-plan-s3-buckets-suppressed.json",
+        "tf_s3_bucket_private_acl.guard",
+        false,
+        StatusCode::SUCCESS
+    )]
+    #[case(
+        "suppressions/tf-plan-s3-buckets-suppressed.json",
+        "tf_s3_bucket_private_acl.guard",
+        true,
+        StatusCode::PARSING_ERROR
+    )]
+    fn test_rules_suppressed_by_resources(
+        #[case] data_arg: &str,
+        #[case] rules_arg: &str,
+        #[case] disallow_suppressions: bool,
+        #[case] expected_status_code: i32,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![data_arg])
+            .rules(vec![rules_arg])
+            .disallow_suppressions(disallow_suppressions)
+            .run(&mut writer);
+        assert_eq!(expected_status_code, status_code);
+
+        let err = writer.err_to_stripped().unwrap();
+        assert_eq!(disallow_suppressions, err.contains("is not allowed"));
+    }
+
+    #[test]
+    fn test_suppressed_resource_left_out_of_failures() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec!["suppressions/s3-buckets-partially-suppressed.yaml"])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .output_format(Some("json"))
+            .show_summary(vec!["none"])
+            .run(&mut writer);
+
+        let output = writer.stripped().unwrap();
+        let (report, suppressions) =
+            output.split_at(output.rfind("{\n  \"suppressed_by_resources\"").unwrap());
+        assert!(report.contains("/Resources/UnencryptedBucket"));
+        assert!(!report.contains("/Resources/LegacyBucket"));
+
+        let suppressions: serde_json::Value = serde_json::from_str(suppressions).unwrap();
+        let suppressed = &suppressions["suppressed_by_resources"][0];
+        assert_eq!(Some("LegacyBucket"), suppressed["resource"].as_str());
+        assert_eq!(
+            Some("Replicated from a bucket that is encrypted at the source"),
+            suppressed["reason"].as_str()
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(
+        "data-dir/s3-public-read-prohibited-template-compliant.yaml",
+        "rules-dir/s3_bucket_public_read_prohibited.guard",
+        true,
+        true,
+        0
+    )]
+    #[case(
+        "s3-server-side-encryption-template-non-compliant-2.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        true,
+        false,
+        5
+    )]
+    #[case(
+        "s3-server-side-encryption-template-non-compliant-2.yaml",
+        "blank-rule.guard",
+        false,
+        false,
+        5
+    )]
+    #[case(
+        "s3-server-side-encryption-template-non-compliant-2.yaml",
+        "blank-rule.guard",
+        true,
+        false,
+        6
+    )]
+    #[case(
+        "malformed-template.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        false,
+        false,
+        StatusCode::PARSING_ERROR
+    )]
+    #[case(
+        "malformed-template.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        true,
+        false,
+        7
+    )]
+    #[case(
+        "db_resource.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        false,
+        false,
+        0
+    )]
+    #[case(
+        "db_resource.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        true,
+        false,
+        0
+    )]
+    #[case(
+        "db_resource.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        false,
+        true,
+        5
+    )]
+    #[case(
+        "db_resource.yaml",
+        "s3_bucket_server_side_encryption_enabled_2.guard",
+        true,
+        true,
+        8
+    )]
+    fn test_exit_codes(
+        #[case] data_arg: &str,
+        #[case] rules_arg: &str,
+        #[case] detailed_exit_codes: bool,
+        #[case] fail_on_skip: bool,
+        #[case] expected_status_code: i32,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![data_arg])
+            .rules(vec![rules_arg])
+            .detailed_exit_codes(detailed_exit_codes)
+            .fail_on_skip(fail_on_skip)
+            .run(&mut writer);
+        assert_eq!(expected_status_code, status_code);
+    }
+
+    #[test]
+    fn test_malformed_data_file_does_not_stop_the_run() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "malformed-template.yaml",
+                "data-dir/s3-public-read-prohibited-template-non-compliant.yaml",
+            ])
+            .rules(vec!["rules-dir/s3_bucket_public_read_prohibited.guard"])
+            .detailed_exit_codes(true)
+            .run(&mut writer);
+        assert_eq!(StatusCode::DATA_FILE_PARSE_ERROR, status_code);
+
+        let err = writer.err_to_stripped().unwrap();
+        assert!(err.contains(
+            "Parsing error handling data file = malformed-template.yaml, Error = mapping values are not allowed in this context at line 14 column 23"
+        ));
+    }
+
+    #[rstest::rstest]
+    #[case("json")]
+    #[case("yaml")]
+    fn test_malformed_data_files_listed_after_reports(#[case] output_format: &str) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        ValidateTestRunner::default()
+            .data(vec![
+                "malformed-template.yaml",
+                "blank-template.yaml",
+                "data-dir/s3-public-read-prohibited-template-compliant.yaml",
+            ])
+            .rules(vec!["rules-dir/s3_bucket_public_read_prohibited.guard"])
+            .output_format(Some(output_format))
+            .show_summary(vec!["none"])
+            .run(&mut writer);
+
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("S3_BUCKET_PUBLIC_READ_PROHIBITED"));
+        let (_, errors) = output.split_at(output.rfind("data_file_errors").unwrap());
+        assert!(errors.contains("malformed-template.yaml"));
+        assert!(errors.contains("blank-template.yaml"));
+    }
+
+    #[test]
+    fn test_malformed_data_file_in_junit_report() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec!["malformed-template.yaml"])
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .output_format(Some("junit"))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let output = writer.stripped().unwrap();
+        assert!(output.contains(
+            r#"<testsuite name="data files" tests="1" failures="0" skipped="0" errors="1">"#
+        ));
+        assert!(output.contains(
+            r#"<testcase name="malformed-template.yaml" classname="malformed-template.yaml">"#
+        ));
+    }
+
+    #[test]
+    fn test_options_from_config_file() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .config(Some("config/guard.toml"))
+            .run(&mut writer);
+        assert_eq!(StatusCode::PARSING_ERROR, status_code);
+
+        let output = writer.stripped().unwrap();
+        let reports = serde_json::Deserializer::from_str(&output)
+            .into_iter::<serde_json::Value>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, reports.len());
+        assert_eq!(Some("FAIL"), reports[0]["status"].as_str());
+        assert_eq!(Some("PASS"), reports[1]["status"].as_str());
+    }
+
+    #[test]
+    fn test_command_line_takes_precedence_over_config_file() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .config(Some("config/guard.toml"))
+            .data(vec![
+                "data-dir/s3-public-read-prohibited-template-compliant.yaml",
+            ])
+            .output_format(Some("single-line-summary"))
+            .show_summary(vec!["all"])
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+
+        let output = writer.stripped().unwrap();
+        assert!(output.contains("s3-public-read-prohibited-template-compliant.yaml Status = PASS"));
+        assert!(!output.contains("s3-server-side-encryption-template-non-compliant-2.yaml"));
+    }
+
+    #[test]
+    fn test_unknown_key_in_config_file() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .config(Some("config/unknown-key.toml"))
+            .run(&mut writer);
+        assert_eq!(StatusCode::INTERNAL_FAILURE, status_code);
+
+        let err = writer.err_to_stripped().unwrap();
+        assert!(err.contains("Unknown key `rulez` in configuration file"));
+        assert!(err.contains("expected one of alphabetical, baseline, cache-dir, data,"));
+    }
+
+    #[test]
+    fn test_no_config() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .no_config(true)
+            .rules(vec!["s3_bucket_server_side_encryption_enabled_2.guard"])
+            .data(vec![
+                "data-dir/s3-public-read-prohibited-template-compliant.yaml",
+            ])
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+    }
+
+    #[test]
+    fn test_no_config_conflicts_with_config() {
+        let result = Validate::new().command().try_get_matches_from(vec![
+            VALIDATE,
+            "--config",
+            "guard.toml",
+            "--no-config",
+            "--rules",
+            "rules.guard",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[rstest::rstest]
+    #[case(vec!["data-dir"], vec![], vec![], StatusCode::PARSING_ERROR)]
+    #[case(vec!["data-dir"], vec!["*non-compliant.yaml"], vec![], StatusCode::SUCCESS)]
+    #[case(vec!["data-dir/s3-public-read-*.yaml"], vec![], vec![], StatusCode::PARSING_ERROR)]
+    #[case(vec!["data-dir/s3-public-read-*.yaml"], vec!["*-non-compliant.yaml"], vec![], StatusCode::SUCCESS)]
+    #[case(vec!["data-dir/**/*-compliant.yaml"], vec!["**/data-dir/s3-public-read-*"], vec![], StatusCode::SUCCESS)]
+    #[case(vec!["data-dir"], vec![], vec![".yml"], StatusCode::SUCCESS)]
+    #[case(vec!["data-dir/s3-public-read-*.yaml"], vec!["*-{non-compliant,unknown}.yaml"], vec![], StatusCode::SUCCESS)]
+    fn test_data_patterns_excludes_and_extensions(
+        #[case] data_arg: Vec<&str>,
+        #[case] exclude_arg: Vec<&str>,
+        #[case] data_extensions_arg: Vec<&str>,
+        #[case] expected_status_code: i32,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(data_arg)
+            .rules(vec!["rules-dir/s3_bucket_public_read_*.guard"])
+            .exclude(exclude_arg)
+            .data_extensions(data_extensions_arg)
+            .run(&mut writer);
+
+        assert_eq!(expected_status_code, status_code);
+    }
+
+    #[rstest::rstest]
+    #[case(vec![], vec![], vec![], StatusCode::PARSING_ERROR)]
+    #[case(vec!["s3_*"], vec![], vec![], StatusCode::PARSING_ERROR)]
+    #[case(vec!["s3_*"], vec!["*_logging_*"], vec![], StatusCode::SUCCESS)]
+    #[case(vec![], vec!["s3_bucket_logging_enabled"], vec![], StatusCode::SUCCESS)]
+    #[case(vec![], vec![], vec!["pci"], StatusCode::SUCCESS)]
+    #[case(vec![], vec![], vec!["pci", "logging"], StatusCode::PARSING_ERROR)]
+    #[case(vec!["legacy_*"], vec![], vec!["encryption"], StatusCode::SUCCESS)]
+    #[case(vec!["s3_bucket_{encryption_configured,sse_enabled}"], vec![], vec![], StatusCode::SUCCESS)]
+    #[case(vec![], vec!["s3_bucket_{logging,versioning}_enabled"], vec![], StatusCode::SUCCESS)]
+    fn test_rule_selection(
+        #[case] rule_name_arg: Vec<&str>,
+        #[case] exclude_rule_arg: Vec<&str>,
+        #[case] tags_arg: Vec<&str>,
+        #[case] expected_status_code: i32,
+    ) {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "data-dir/s3-server-side-encryption-template-compliant.yaml",
+            ])
+            .rules(vec!["rule-selection/s3_bucket_rules.guard"])
+            .rule_name(rule_name_arg)
+            .exclude_rule(exclude_rule_arg)
+            .tags(tags_arg)
+            .run(&mut writer);
+
+        assert_eq!(expected_status_code, status_code);
+    }
+
+    #[test]
+    fn test_rule_selection_reports_only_selected_rules() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "data-dir/s3-server-side-encryption-template-compliant.yaml",
+            ])
+            .rules(vec!["rule-selection/s3_bucket_rules.guard"])
+            .tags(vec!["pci"])
+            .show_summary(vec!["all"])
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+
+        let out = writer.stripped().unwrap();
+        assert!(out.contains("s3_bucket_rules.guard/s3_bucket_sse_enabled"));
+        assert!(!out.contains("s3_bucket_rules.guard/s3_bucket_encryption_configured"));
+        assert!(!out.contains("s3_bucket_rules.guard/s3_bucket_logging_enabled"));
+    }
+
+    #[test]
+    fn test_rule_selection_listed_in_verbose_output() {
+        let mut writer = Writer::new(WBVec(vec![]), WBVec(vec![]));
+        let status_code = ValidateTestRunner::default()
+            .data(vec![
+                "data-dir/s3-server-side-encryption-template-compliant.yaml",
+            ])
+            .rules(vec!["rule-selection/s3_bucket_rules.guard"])
+            .tags(vec!["pci"])
+            .verbose(true)
+            .run(&mut writer);
+        assert_eq!(StatusCode::SUCCESS, status_code);
+
+        let err = writer.err_to_stripped().unwrap();
+        assert!(err.contains("Rules selected from s3_bucket_rules.guard: s3_bucket_sse_enabled\n"));
+        assert!(err.contains(
+            "Rules evaluated as dependencies from s3_bucket_rules.guard: s3_bucket_encryption_configured\n"
+        ));
+    }
+
+    #[rstest::rstest]
+    #[case(vec!["db_resource.yaml"], vec!["db_param_port_rule.guard"], vec!["input-parameters-dir/db_*.yaml"], StatusCode::SUCCESS)]
     #[case(vec!["db_resource.yaml"], vec!["db_param_port_rule.guard"], vec!["input-parameters-dir/db_params.yaml"], StatusCode::PARSING_ERROR)]
     #[case(vec!["db_resource.yaml"], vec!["db_param_port_rule.guard"], vec!["input-parameters-dir/db_params.yaml", "input-parameters-dir/db_metadata.yaml"], StatusCode::SUCCESS)]
     #[case(vec!["db_resource.yaml"], vec!["db_param_port_rule.guard"], vec!["input-parameters-dir/"], StatusCode::SUCCESS)]